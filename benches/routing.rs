@@ -0,0 +1,109 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use hyper::{service::Service, Body, Request, Response};
+use routerify::{Middleware, RequestServiceBuilder, RouteMatcher, Router};
+use std::convert::Infallible;
+use tokio::runtime::Runtime;
+
+fn router(matcher: RouteMatcher, routes: usize) -> Router<Body, Infallible> {
+    let mut builder = Router::builder().route_matcher(matcher);
+
+    for idx in 0..routes {
+        builder = builder.get(format!("/api/v1/resource{}/:id", idx), |_| async move {
+            Ok(Response::new(Body::empty()))
+        });
+    }
+
+    builder.build().unwrap()
+}
+
+fn bench_route_matchers(c: &mut Criterion) {
+    let mut rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("route_matcher");
+
+    for routes in &[1, 50, 500] {
+        // The last registered route is hit, so the whole route list is relevant for the match.
+        let uri = format!("/api/v1/resource{}/42", routes - 1);
+
+        for (name, matcher) in &[("regex_set", RouteMatcher::RegexSet), ("trie", RouteMatcher::Trie)] {
+            let builder = RequestServiceBuilder::new(router(*matcher, *routes)).unwrap();
+            let mut service = builder.build(([127, 0, 0, 1], 8080).into());
+
+            group.bench_with_input(BenchmarkId::new(*name, routes), &uri, |b, uri| {
+                b.iter(|| {
+                    let req = Request::get(uri.as_str()).body(Body::empty()).unwrap();
+                    rt.block_on(service.call(req)).unwrap()
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn nested_router(routes: usize) -> Router<Body, Infallible> {
+    let mut level3 = Router::builder();
+    for idx in 0..routes {
+        level3 = level3.get(format!("/resource{}/:id", idx), |_| async move {
+            Ok(Response::new(Body::empty()))
+        });
+    }
+
+    let level2 = Router::builder().scope("/v1", level3.build().unwrap()).build().unwrap();
+    let level1 = Router::builder().scope("/api", level2).build().unwrap();
+
+    Router::builder().scope("/", level1).build().unwrap()
+}
+
+fn bench_nested_scopes(c: &mut Criterion) {
+    let mut rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("nested_scopes");
+    let uri = "/api/v1/resource49/42";
+
+    for (name, router) in [("flat", router(RouteMatcher::Trie, 50)), ("nested", nested_router(50))] {
+        let builder = RequestServiceBuilder::new(router).unwrap();
+        let mut service = builder.build(([127, 0, 0, 1], 8080).into());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let req = Request::get(uri).body(Body::empty()).unwrap();
+                rt.block_on(service.call(req)).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_match_cache(c: &mut Criterion) {
+    let mut rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("match_cache");
+    let uri = "/api/v1/resource499/42";
+
+    for (name, capacity) in [("uncached", 0), ("cached", 128)] {
+        let mut builder = Router::<Body, Infallible>::builder().match_cache(capacity);
+        for idx in 0..500 {
+            let middleware =
+                Middleware::pre_with_path(format!("/api/v1/resource{}/*", idx), |req| async move { Ok(req) });
+            builder = builder
+                .get(format!("/api/v1/resource{}/:id", idx), |_| async move {
+                    Ok(Response::new(Body::empty()))
+                })
+                .middleware(middleware.unwrap());
+        }
+
+        let builder = RequestServiceBuilder::new(builder.build().unwrap()).unwrap();
+        let mut service = builder.build(([127, 0, 0, 1], 8080).into());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let req = Request::get(uri).body(Body::empty()).unwrap();
+                rt.block_on(service.call(req)).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_route_matchers, bench_nested_scopes, bench_match_cache);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+use hyper::{Body, Request, Response, Server};
+use routerify::{Router, RouterService};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{convert::Infallible, net::SocketAddr};
+
+// A slow handler, the in-flight requests are completed even after the shutdown is triggered.
+async fn slow_handler(_: Request<Body>) -> Result<Response<Body>, Infallible> {
+    tokio::time::delay_for(Duration::from_secs(5)).await;
+    Ok(Response::new(Body::from("Done")))
+}
+
+fn router() -> Router<Body, Infallible> {
+    Router::builder().get("/slow", slow_handler).build().unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let router = router();
+
+    // Track the number of active connections with the connection hooks.
+    let active_connections = Arc::new(AtomicUsize::new(0));
+
+    let service = RouterService::new(router)
+        .unwrap()
+        .on_connection_open({
+            let active_connections = active_connections.clone();
+            move |addr| {
+                let count = active_connections.fetch_add(1, Ordering::SeqCst) + 1;
+                println!("Connection opened: {}, active connections: {}", addr, count);
+            }
+        })
+        .on_connection_close({
+            let active_connections = active_connections.clone();
+            move |addr| {
+                let count = active_connections.fetch_sub(1, Ordering::SeqCst) - 1;
+                println!("Connection closed: {}, active connections: {}", addr, count);
+            }
+        });
+
+    // Trigger the shutdown on Ctrl+C. The new requests are answered with 503 from now on.
+    let handle = service.shutdown_handle();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            tokio::signal::ctrl_c().await.unwrap();
+            println!("Shutting down, in-flight requests: {}", handle.in_flight_requests());
+            handle.shutdown();
+        }
+    });
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
+
+    // Stop accepting new connections and wait for the open ones once the shutdown is triggered.
+    let server = Server::bind(&addr).serve(service).with_graceful_shutdown(handle.wait());
+
+    println!("App is running on: {}", addr);
+    if let Err(err) = server.await {
+        eprintln!("Server error: {}", err);
+    }
+}
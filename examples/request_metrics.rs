@@ -0,0 +1,87 @@
+use hyper::{Body, Request, Response, Server};
+use routerify::prelude::*;
+use routerify::{RequestMetrics, Router, RouterService};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+// A tiny stand-in for a metrics registry e.g. the `prometheus` crate, where the request counter and the latency sum are
+// labeled by the route template, the method and the status class.
+#[derive(Default)]
+struct Metrics {
+    requests: Mutex<BTreeMap<Labels, (u64, f64)>>,
+}
+
+// The route template, the method and the status class.
+type Labels = (String, String, String);
+
+impl Metrics {
+    fn observe(&self, metrics: &RequestMetrics<'_>) {
+        let status_class = format!("{}xx", metrics.status.as_u16() / 100);
+        let key = (metrics.route_path.to_owned(), metrics.method.to_string(), status_class);
+
+        let mut requests = self.requests.lock().unwrap();
+        let entry = requests.entry(key).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += metrics.latency.as_secs_f64();
+    }
+
+    // Renders the metrics in the Prometheus text format.
+    fn render(&self) -> String {
+        let mut text = String::new();
+
+        for ((route, method, status), (count, latency_sum)) in self.requests.lock().unwrap().iter() {
+            let labels = format!("route=\"{}\",method=\"{}\",status=\"{}\"", route, method, status);
+            text.push_str(&format!("http_requests_total{{{}}} {}\n", labels, count));
+            text.push_str(&format!(
+                "http_request_duration_seconds_sum{{{}}} {}\n",
+                labels, latency_sum
+            ));
+        }
+
+        text
+    }
+}
+
+async fn user_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let user_id = req.param("userId").unwrap();
+    Ok(Response::new(Body::from(format!("Hello {}", user_id))))
+}
+
+async fn metrics_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let metrics = req.data::<Arc<Metrics>>().unwrap();
+    Ok(Response::new(Body::from(metrics.render())))
+}
+
+fn router() -> Router<Body, Infallible> {
+    let metrics = Arc::new(Metrics::default());
+
+    Router::builder()
+        .data(metrics.clone())
+        // All the requests to e.g. `/users/alice` and `/users/bob` are counted under the `/users/:userId` route.
+        .get("/users/:userId", user_handler)
+        .get("/metrics", metrics_handler)
+        .on_request_complete(move |request_metrics| metrics.observe(request_metrics))
+        .build()
+        .unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let router = router();
+
+    // Create a Service from the router above to handle incoming requests.
+    let service = RouterService::new(router).unwrap();
+
+    // The address on which the server will be listening.
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
+
+    // Create a server by passing the created service to `.serve` method.
+    let server = Server::bind(&addr).serve(service);
+
+    println!("App is running on: {}", addr);
+    if let Err(err) = server.await {
+        eprintln!("Server error: {}", err);
+    }
+}
@@ -0,0 +1,36 @@
+use futures::StreamExt;
+use hyper::{Body, Request, Response, Server};
+use routerify::sse::{self, SseEvent};
+use routerify::{Router, RouterService};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+// A handler which streams a counter every second.
+async fn counter_handler(_: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let events = tokio::time::interval(Duration::from_secs(1))
+        .enumerate()
+        .map(|(count, _)| SseEvent::new(count.to_string()).event("count").id(count.to_string()));
+
+    // Send a keep-alive comment if no event is sent for 15 seconds.
+    Ok(sse::sse_response_with_keep_alive(events, Duration::from_secs(15)))
+}
+
+fn router() -> Router<Body, Infallible> {
+    Router::builder().get("/counter", counter_handler).build().unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let service = RouterService::new(router()).unwrap();
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
+
+    let server = Server::bind(&addr).serve(service);
+
+    println!("App is running on: {}", addr);
+    println!("Try: curl -N http://{}/counter", addr);
+    if let Err(err) = server.await {
+        eprintln!("Server error: {}", err);
+    }
+}
@@ -0,0 +1,40 @@
+use hyper::{server::accept, Body, Request, Response, Server};
+use routerify::prelude::*;
+use routerify::{PeerAddr, Router, RouterService};
+use std::convert::Infallible;
+use tokio::net::UnixListener;
+
+// A handler which greets the local user connected over the Unix domain socket.
+async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let text = match req.peer_addr() {
+        PeerAddr::Unix { uid: Some(uid), .. } => format!("Hello user {}", uid),
+        peer => format!("Hello {}", peer),
+    };
+    Ok(Response::new(Body::from(text)))
+}
+
+fn router() -> Router<Body, Infallible> {
+    Router::builder().get("/", home_handler).build().unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let path = "/tmp/app.sock";
+
+    // Remove the socket file left by a previous run.
+    let _ = std::fs::remove_file(path);
+
+    let mut listener = UnixListener::bind(path).unwrap();
+
+    // The service accepts any connection type which implements the `routerify::Connection` trait.
+    let service = RouterService::new(router()).unwrap();
+
+    // Create a server which accepts the connections from the Unix domain socket.
+    let server = Server::builder(accept::from_stream(listener.incoming())).serve(service);
+
+    println!("App is running on: unix:{}", path);
+    println!("Try: curl --unix-socket {} http://localhost/", path);
+    if let Err(err) = server.await {
+        eprintln!("Server error: {}", err);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::body::read::{media_type, read_body};
+use crate::types::QueryParams;
+use crate::Error;
+use hyper::{Body, Request};
+
+/// Options for the [form_with_options](./fn.form_with_options.html) and the
+/// [form_pairs_with_options](./fn.form_pairs_with_options.html) functions.
+#[derive(Debug, Clone)]
+pub struct FormOptions {
+    /// The maximum size of the body in bytes, a larger body fails with an
+    /// [`Error::RequestBodyTooLarge`](../enum.Error.html#variant.RequestBodyTooLarge) error. There's no limit if it's `None`.
+    pub max_size: Option<u64>,
+
+    /// Requires the `Content-Type` header to be `application/x-www-form-urlencoded`, otherwise it fails with an
+    /// [`Error::UnsupportedContentType`](../enum.Error.html#variant.UnsupportedContentType) error.
+    pub check_content_type: bool,
+}
+
+impl Default for FormOptions {
+    fn default() -> FormOptions {
+        FormOptions {
+            max_size: Some(64 * 1024),
+            check_content_type: true,
+        }
+    }
+}
+
+/// Buffers the `application/x-www-form-urlencoded` request body and deserializes it with the
+/// [default options](./struct.FormOptions.html), a body larger than `64 KiB` or a body with another `Content-Type` is
+/// rejected.
+///
+/// The fields are decoded like the query params, the `+` characters become spaces and the values of a repeated field are
+/// collected into a sequence e.g. a `Vec<String>` field. The body is taken out of the request and replaced with an empty
+/// one.
+///
+/// The default error handler responds with `400 Bad Request` to a missing or an invalid field, `413 Payload Too Large` to
+/// a large body and `415 Unsupported Media Type` to a wrong content type.
+///
+/// This function is available only if the `serde` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{body, Router};
+/// use hyper::{Body, StatusCode};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Login {
+///     username: String,
+///     password: String,
+///     remember_me: Option<bool>,
+/// }
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .post("/login", |mut req| async move {
+///         let login: Login = body::form(&mut req).await?;
+///
+///         if login.username == "alice" && login.password == "secret" {
+///             Ok((StatusCode::OK, format!("Welcome {}", login.username)))
+///         } else {
+///             Ok((StatusCode::UNAUTHORIZED, "Invalid credentials".to_owned()))
+///         }
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[cfg(feature = "serde")]
+pub async fn form<T: serde::de::DeserializeOwned>(req: &mut Request<Body>) -> crate::Result<T> {
+    form_with_options(req, &FormOptions::default()).await
+}
+
+/// Same as [form](./fn.form.html), but with the specified options.
+///
+/// This function is available only if the `serde` feature is enabled.
+#[cfg(feature = "serde")]
+pub async fn form_with_options<T: serde::de::DeserializeOwned>(
+    req: &mut Request<Body>,
+    options: &FormOptions,
+) -> crate::Result<T> {
+    let pairs = read_form(req, options).await?;
+    crate::types::from_form_pairs(&pairs)
+}
+
+/// Buffers the `application/x-www-form-urlencoded` request body and returns the decoded fields in their original order with
+/// the [default options](./struct.FormOptions.html).
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{body, Router};
+/// use hyper::Body;
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .post("/settings", |mut req| async move {
+///         let fields = body::form_pairs(&mut req).await?;
+///         Ok(format!("{} fields updated", fields.len()))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub async fn form_pairs(req: &mut Request<Body>) -> crate::Result<Vec<(String, String)>> {
+    form_pairs_with_options(req, &FormOptions::default()).await
+}
+
+/// Same as [form_pairs](./fn.form_pairs.html), but with the specified options.
+pub async fn form_pairs_with_options(
+    req: &mut Request<Body>,
+    options: &FormOptions,
+) -> crate::Result<Vec<(String, String)>> {
+    Ok(read_form(req, options).await?.into_pairs())
+}
+
+async fn read_form(req: &mut Request<Body>, options: &FormOptions) -> crate::Result<QueryParams> {
+    if options.check_content_type {
+        let media_type = media_type(req);
+        if media_type != "application/x-www-form-urlencoded" {
+            return Err(Error::UnsupportedContentType(media_type));
+        }
+    }
+
+    let bytes = read_body(req, options.max_size).await?;
+
+    Ok(QueryParams::parse(Some(&String::from_utf8_lossy(&bytes))))
+}
@@ -0,0 +1,138 @@
+use crate::body::read::{media_type, read_body};
+use crate::Error;
+use hyper::{Body, Request};
+use serde::de::DeserializeOwned;
+
+/// Options for the [json_with_options](./fn.json_with_options.html) function.
+#[derive(Debug, Clone)]
+pub struct JsonOptions {
+    /// The maximum size of the body in bytes, a larger body fails with an
+    /// [`Error::RequestBodyTooLarge`](../enum.Error.html#variant.RequestBodyTooLarge) error. There's no limit if it's `None`.
+    pub max_size: Option<u64>,
+
+    /// Requires the `Content-Type` header to be `application/json` or a `+json` type e.g. `application/problem+json`,
+    /// otherwise it fails with an [`Error::UnsupportedContentType`](../enum.Error.html#variant.UnsupportedContentType) error.
+    pub check_content_type: bool,
+}
+
+impl Default for JsonOptions {
+    fn default() -> JsonOptions {
+        JsonOptions {
+            max_size: Some(1024 * 1024),
+            check_content_type: true,
+        }
+    }
+}
+
+/// Buffers the request body and deserializes it from JSON with the [default options](./struct.JsonOptions.html), a body
+/// larger than `1 MiB` or a body whose `Content-Type` is not JSON is rejected.
+///
+/// The body is taken out of the request and replaced with an empty one, so the request can still be used to read e.g.
+/// the route params.
+///
+/// The errors are client errors, the default error handler responds with `400 Bad Request` to an invalid JSON,
+/// `413 Payload Too Large` to a large body and `415 Unsupported Media Type` to a wrong content type.
+///
+/// This function is available only if the `serde` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{body, Router};
+/// use routerify::ext::RequestExt;
+/// use hyper::{Body, StatusCode};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Book {
+///     title: String,
+/// }
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .post("/users/:id/books", |mut req| async move {
+///         let book: Book = body::json(&mut req).await?;
+///         let user_id = req.param("id").unwrap();
+///         Ok((StatusCode::CREATED, format!("{} added {}", user_id, book.title)))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub async fn json<T: DeserializeOwned>(req: &mut Request<Body>) -> crate::Result<T> {
+    json_with_options(req, &JsonOptions::default()).await
+}
+
+/// Same as [json](./fn.json.html), but with the specified options.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{body, Router};
+/// use routerify::body::JsonOptions;
+/// use hyper::Body;
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .post("/events", |mut req| async move {
+///         let options = JsonOptions {
+///             max_size: Some(16 * 1024),
+///             check_content_type: false,
+///         };
+///         let events: Vec<String> = body::json_with_options(&mut req, &options).await?;
+///         Ok(format!("{} events", events.len()))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub async fn json_with_options<T: DeserializeOwned>(
+    req: &mut Request<Body>,
+    options: &JsonOptions,
+) -> crate::Result<T> {
+    if options.check_content_type {
+        check_json_content_type(req)?;
+    }
+
+    let bytes = read_body(req, options.max_size).await?;
+
+    serde_json::from_slice(&bytes).map_err(Error::DeserializeBody)
+}
+
+fn check_json_content_type<ReqB>(req: &Request<ReqB>) -> crate::Result<()> {
+    let media_type = media_type(req);
+
+    if media_type == "application/json" || (media_type.starts_with("application/") && media_type.ends_with("+json")) {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedContentType(media_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header;
+
+    #[test]
+    fn test_check_json_content_type() {
+        let req = |content_type: Option<&str>| {
+            let mut builder = Request::builder();
+            if let Some(content_type) = content_type {
+                builder = builder.header(header::CONTENT_TYPE, content_type);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        assert!(check_json_content_type(&req(Some("application/json"))).is_ok());
+        assert!(check_json_content_type(&req(Some("Application/JSON; charset=utf-8"))).is_ok());
+        assert!(check_json_content_type(&req(Some("application/problem+json"))).is_ok());
+        assert!(check_json_content_type(&req(Some("text/plain"))).is_err());
+        assert!(check_json_content_type(&req(Some("text/+json"))).is_err());
+        assert!(check_json_content_type(&req(None)).is_err());
+    }
+}
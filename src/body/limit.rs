@@ -0,0 +1,117 @@
+use crate::regex_generator::generate_exact_match_regex;
+use crate::Error;
+use futures::Stream;
+use hyper::body::{Bytes, HttpBody};
+use hyper::{header, Body, Request};
+use regex::Regex;
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub(crate) struct ScopedBodyLimit {
+    pub(crate) path: String,
+    pub(crate) regex: Regex,
+    pub(crate) limit: u64,
+}
+
+impl ScopedBodyLimit {
+    pub fn new<P: Into<String>>(path: P, limit: u64) -> crate::Result<ScopedBodyLimit> {
+        let path = path.into();
+        let (re, _) = generate_exact_match_regex(path.as_str())?;
+
+        Ok(ScopedBodyLimit { path, regex: re, limit })
+    }
+}
+
+impl Debug for ScopedBodyLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ path: {:?}, regex: {:?}, limit: {:?} }}",
+            self.path, self.regex, self.limit
+        )
+    }
+}
+
+/// Fails early if the `Content-Length` header already exceeds the limit.
+pub(crate) fn check_content_length<ReqB>(req: &Request<ReqB>, limit: u64) -> crate::Result<()> {
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<u64>().ok());
+
+    if let Some(content_length) = content_length {
+        if content_length > limit {
+            return Err(Error::RequestBodyTooLarge(limit));
+        }
+    }
+
+    Ok(())
+}
+
+/// Wraps the request body to count the streamed bytes, the returned flag is raised once the limit is exceeded.
+/// It's required for the chunked requests which don't carry any `Content-Length` header.
+///
+/// Only a `hyper::Body` can be wrapped without changing the request body type, so the other body types are limited
+/// by the `Content-Length` header only and no flag is returned for them.
+pub(crate) fn limit_body<ReqB: 'static>(req: Request<ReqB>, limit: u64) -> (Request<ReqB>, Option<Arc<AtomicBool>>) {
+    let (parts, body) = req.into_parts();
+
+    let body: Box<dyn Any> = Box::new(body);
+    let (body, exceeded): (Box<dyn Any>, _) = match body.downcast::<Body>() {
+        Ok(body) => {
+            let exceeded = Arc::new(AtomicBool::new(false));
+            let body = Body::wrap_stream(LimitedBody {
+                inner: *body,
+                read: 0,
+                limit,
+                exceeded: exceeded.clone(),
+            });
+            (Box::new(body), Some(exceeded))
+        }
+        Err(body) => (body, None),
+    };
+
+    let body = body
+        .downcast::<ReqB>()
+        .expect("The request body type is changed while limiting the body size");
+
+    (Request::from_parts(parts, *body), exceeded)
+}
+
+struct LimitedBody {
+    inner: Body,
+    read: u64,
+    limit: u64,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl Stream for LimitedBody {
+    type Item = crate::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.exceeded.load(Ordering::SeqCst) {
+            return Poll::Ready(None);
+        }
+
+        match Pin::new(&mut self.inner).poll_data(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.read += chunk.len() as u64;
+
+                if self.read > self.limit {
+                    self.exceeded.store(true, Ordering::SeqCst);
+                    Poll::Ready(Some(Err(Error::RequestBodyTooLarge(self.limit))))
+                } else {
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(Error::ReadRequestBody(err)))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Helpers to read the request body.
+//!
+//! The [`json`](./fn.json.html) and the [`form`](./fn.form.html) functions which deserialize the body are available only if
+//! the `serde` feature is enabled.
+
+#[cfg(feature = "serde")]
+pub use self::form::{form, form_with_options};
+pub use self::form::{form_pairs, form_pairs_with_options, FormOptions};
+#[cfg(feature = "serde")]
+pub use self::json::{json, json_with_options, JsonOptions};
+pub(crate) use self::limit::{check_content_length, limit_body, ScopedBodyLimit};
+pub(crate) use self::read::media_type;
+#[cfg(feature = "cookies")]
+pub(crate) use self::read::read_body;
+pub use self::stream::{stream, stream_with_options, BodyStream, StreamOptions};
+
+mod form;
+#[cfg(feature = "serde")]
+mod json;
+mod limit;
+mod read;
+mod stream;
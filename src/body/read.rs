@@ -0,0 +1,46 @@
+use crate::body::check_content_length;
+use crate::Error;
+use hyper::body::{Bytes, HttpBody};
+use hyper::{header, Body, Request};
+
+/// Returns the lowercased media type of the `Content-Type` header without the parameters, it's empty if there's no such
+/// header.
+pub(crate) fn media_type<ReqB>(req: &Request<ReqB>) -> String {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .unwrap_or_default();
+
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Takes the body out of the request and buffers it. The `Content-Length` header is checked beforehand, the size of the
+/// chunked bodies is checked while reading.
+pub(crate) async fn read_body(req: &mut Request<Body>, max_size: Option<u64>) -> crate::Result<Bytes> {
+    if let Some(max_size) = max_size {
+        check_content_length(req, max_size)?;
+    }
+
+    let mut body = std::mem::replace(req.body_mut(), Body::empty());
+    let mut buf = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(Error::ReadRequestBody)?;
+
+        if let Some(max_size) = max_size {
+            if (buf.len() + chunk.len()) as u64 > max_size {
+                return Err(Error::RequestBodyTooLarge(max_size));
+            }
+        }
+
+        buf.extend_from_slice(&chunk);
+    }
+
+    Ok(Bytes::from(buf))
+}
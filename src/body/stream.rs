@@ -0,0 +1,140 @@
+use crate::body::check_content_length;
+use crate::Error;
+use futures::stream::Stream;
+use hyper::body::{Bytes, HttpBody};
+use hyper::{Body, Request};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Delay;
+
+/// Options for the [stream_with_options](./fn.stream_with_options.html) function.
+#[derive(Debug, Clone)]
+pub struct StreamOptions {
+    /// The maximum cumulative size of the body chunks in bytes, the stream fails with an
+    /// [`Error::RequestBodyTooLarge`](../enum.Error.html#variant.RequestBodyTooLarge) error once it's exceeded. There's no
+    /// limit if it's `None`.
+    pub max_size: Option<u64>,
+
+    /// The maximum time to wait for the next chunk, the stream fails with an
+    /// [`Error::RequestBodyTimeout`](../enum.Error.html#variant.RequestBodyTimeout) error if the client sends nothing for
+    /// longer. There's no timeout if it's `None`.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for StreamOptions {
+    fn default() -> StreamOptions {
+        StreamOptions {
+            max_size: None,
+            idle_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// A [`Stream`](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html) of the request body chunks created by the
+/// [stream](./fn.stream.html) function, which guards the size of the body and the idle time between the chunks.
+///
+/// The stream ends after the first error.
+#[derive(Debug)]
+pub struct BodyStream {
+    body: Body,
+    options: StreamOptions,
+    read: u64,
+    idle: Option<Delay>,
+    done: bool,
+}
+
+/// Takes the body out of the request as a stream of chunks with the [default options](./struct.StreamOptions.html), the
+/// client must send the next chunk within `30` seconds.
+///
+/// It's useful to proxy or store a large body without buffering it in the memory. The default error handler responds to a
+/// large body with `413 Payload Too Large` and to an idle client with `408 Request Timeout`.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{body, Router};
+/// use futures::TryStreamExt;
+/// use hyper::Body;
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .put("/files/:name", |mut req| async move {
+///         let mut chunks = body::stream(&mut req)?;
+///         let mut size = 0;
+///         while let Some(chunk) = chunks.try_next().await? {
+///             size += chunk.len();
+///         }
+///         Ok(format!("Received {} bytes", size))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn stream(req: &mut Request<Body>) -> crate::Result<BodyStream> {
+    stream_with_options(req, &StreamOptions::default())
+}
+
+/// Same as [stream](./fn.stream.html), but with the specified options.
+///
+/// A body whose `Content-Length` header exceeds the `max_size` is rejected right away.
+pub fn stream_with_options(req: &mut Request<Body>, options: &StreamOptions) -> crate::Result<BodyStream> {
+    if let Some(max_size) = options.max_size {
+        check_content_length(req, max_size)?;
+    }
+
+    Ok(BodyStream {
+        body: std::mem::replace(req.body_mut(), Body::empty()),
+        options: options.clone(),
+        read: 0,
+        idle: None,
+        done: false,
+    })
+}
+
+impl Stream for BodyStream {
+    type Item = crate::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let result = match Pin::new(&mut this.body).poll_data(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                this.idle = None;
+                this.read += chunk.len() as u64;
+
+                match this.options.max_size {
+                    Some(max_size) if this.read > max_size => Err(Error::RequestBodyTooLarge(max_size)),
+                    _ => return Poll::Ready(Some(Ok(chunk))),
+                }
+            }
+            Poll::Ready(Some(Err(err))) => Err(Error::ReadRequestBody(err)),
+            Poll::Ready(None) => {
+                this.done = true;
+                return Poll::Ready(None);
+            }
+            Poll::Pending => {
+                let idle_timeout = match this.options.idle_timeout {
+                    Some(idle_timeout) => idle_timeout,
+                    None => return Poll::Pending,
+                };
+
+                // The timer starts when the stream waits for a chunk for the first time after the last one.
+                let idle = this.idle.get_or_insert_with(|| tokio::time::delay_for(idle_timeout));
+                match Pin::new(idle).poll(cx) {
+                    Poll::Ready(()) => Err(Error::RequestBodyTimeout(idle_timeout)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        };
+
+        this.done = true;
+        Poll::Ready(Some(result))
+    }
+}
@@ -1,7 +1,10 @@
 use hyper::Method;
 
-pub(crate) const HEADER_NAME_X_POWERED_BY: &'static str = "x-powered-by";
-pub(crate) const HEADER_VALUE_X_POWERED_BY: &'static str = concat!("Routerify v", env!("CARGO_PKG_VERSION"));
+pub(crate) const HEADER_NAME_X_POWERED_BY: &str = "x-powered-by";
+pub(crate) const HEADER_VALUE_X_POWERED_BY: &str = concat!("Routerify v", env!("CARGO_PKG_VERSION"));
+
+// The number of times the pre middlewares can rewrite the path of a single request.
+pub(crate) const MAX_REWRITES: usize = 4;
 
 pub(crate) const ALL_POSSIBLE_HTTP_METHODS: [Method; 9] = [
     Method::GET,
@@ -0,0 +1,363 @@
+//! The built-in `CORS` support, which lets the browsers call the routes from the pages of other origins.
+//!
+//! The [`Cors`](./struct.Cors.html) options create a pair of middlewares: a pre middleware which responds to the preflight
+//! `OPTIONS` requests without reaching the routes, and a post middleware which adds the `Access-Control-Allow-*` headers to
+//! the other responses of an allowed origin, including the error responses. The pair can be added to a scoped router to
+//! enable `CORS` for its routes only.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::cors::Cors;
+//! use routerify::Router;
+//! use hyper::{Body, Method};
+//! use std::time::Duration;
+//!
+//! # fn run() -> routerify::Result<Router<Body, routerify::Error>> {
+//! let cors = Cors::new()
+//!     .allow_origin("https://example.com")
+//!     .allow_origin_regex(r"https://[a-z0-9-]+\.example\.com")?
+//!     .allow_methods(vec![Method::GET, Method::POST])
+//!     .allow_credentials(true)
+//!     .max_age(Duration::from_secs(3600));
+//!
+//! let api = Router::builder()
+//!     .cors(cors)
+//!     .get("/users", |_| async move { Ok("Users") })
+//!     .build()
+//!     .unwrap();
+//!
+//! let router = Router::builder()
+//!     .scope("/api", api)
+//!     .get("/", |_| async move { Ok("Home page") })
+//!     .build()
+//!     .unwrap();
+//! # Ok(router)
+//! # }
+//! # run().unwrap();
+//! ```
+
+use crate::middleware::{Middleware, PreOutcome};
+use crate::types::RequestInfo;
+use crate::Error;
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue};
+use hyper::{body::HttpBody, Body, Method, Request, Response, StatusCode};
+use regex::Regex;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The `CORS` options, which create the [pre](#method.pre_middleware) and the [post](#method.post_middleware) middlewares.
+///
+/// No origin is allowed by default, the allowed origins are added by the [`allow_origin`](#method.allow_origin), the
+/// [`allow_origin_regex`](#method.allow_origin_regex) or the [`allow_any_origin`](#method.allow_any_origin) methods. Please
+/// refer to the [cors](./index.html) module for an example.
+#[derive(Debug, Clone)]
+pub struct Cors {
+    any_origin: bool,
+    origins: Vec<String>,
+    origin_regexes: Vec<Regex>,
+    methods: Vec<Method>,
+    // The requested headers are allowed if it's `None`.
+    headers: Option<Vec<HeaderName>>,
+    expose_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Default for Cors {
+    fn default() -> Cors {
+        Cors {
+            any_origin: false,
+            origins: Vec::new(),
+            origin_regexes: Vec::new(),
+            methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ],
+            headers: None,
+            expose_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+impl Cors {
+    /// Creates the options which allow no origin yet, the `GET`, `HEAD`, `POST`, `PUT`, `PATCH` and `DELETE` methods and any
+    /// request header, without the credentials.
+    pub fn new() -> Cors {
+        Cors::default()
+    }
+
+    /// Allows an exact origin e.g. `https://example.com`, the scheme and the port must match too.
+    pub fn allow_origin<O: Into<String>>(mut self, origin: O) -> Self {
+        self.origins.push(origin.into());
+        self
+    }
+
+    /// Allows the origins matching the regex e.g. `https://[a-z]+\.example\.com`, the regex must match the whole origin so
+    /// it's anchored at both ends. It fails with an [`Error::InvalidCorsOrigin`](../enum.Error.html#variant.InvalidCorsOrigin)
+    /// error if the regex is not valid.
+    pub fn allow_origin_regex<R: AsRef<str>>(mut self, regex: R) -> crate::Result<Self> {
+        let regex = regex.as_ref();
+        let regex = Regex::new(&format!("^(?:{})$", regex))
+            .map_err(|err| Error::InvalidCorsOrigin(format!("{}: {}", regex, err)))?;
+        self.origin_regexes.push(regex);
+        Ok(self)
+    }
+
+    /// Allows any origin, the `Access-Control-Allow-Origin` header is `*`. The credentials are never allowed for these
+    /// origins, even if [`allow_credentials`](#method.allow_credentials) is set, only for the origins allowed by the
+    /// [`allow_origin`](#method.allow_origin) or the [`allow_origin_regex`](#method.allow_origin_regex) methods.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.any_origin = true;
+        self
+    }
+
+    /// Sets the methods the cross-origin requests can use.
+    pub fn allow_methods<I: IntoIterator<Item = Method>>(mut self, methods: I) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Sets the request headers the cross-origin requests can send, the headers which the browsers always allow e.g.
+    /// `Accept` don't need to be listed. Any requested header is allowed if it's not set.
+    pub fn allow_headers<I: IntoIterator<Item = HeaderName>>(mut self, headers: I) -> Self {
+        self.headers = Some(headers.into_iter().collect());
+        self
+    }
+
+    /// Sets the response headers the browsers expose to the scripts of other origins, besides the always exposed ones e.g.
+    /// `Content-Type`.
+    pub fn expose_headers<I: IntoIterator<Item = HeaderName>>(mut self, headers: I) -> Self {
+        self.expose_headers = headers.into_iter().collect();
+        self
+    }
+
+    /// Allows the cross-origin requests to send the cookies and the `Authorization` header. It applies only to the origins
+    /// allowed explicitly, not to the ones allowed by [`allow_any_origin`](#method.allow_any_origin).
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Sets the time the browsers can cache the result of a preflight request.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Creates the pre middleware which responds to the preflight requests with `204 No Content`, or with `403 Forbidden` if
+    /// the origin, the method or a header is not allowed.
+    pub fn pre_middleware<E, ReqB>(&self) -> Middleware<Body, E, ReqB>
+    where
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    {
+        let cors = Arc::new(self.clone());
+
+        Middleware::pre_with_response(move |req: Request<ReqB>| {
+            let outcome = if is_preflight(req.method(), req.headers()) {
+                PreOutcome::Respond(cors.preflight(req.headers()))
+            } else {
+                PreOutcome::Continue(req)
+            };
+
+            async move { Ok(outcome) }
+        })
+    }
+
+    /// Creates the post middleware which adds the `CORS` headers to the responses of the allowed origins.
+    pub fn post_middleware<E, ReqB>(&self) -> Middleware<Body, E, ReqB>
+    where
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    {
+        let cors = Arc::new(self.clone());
+
+        Middleware::post_with_info(move |mut resp: Response<Body>, req_info: RequestInfo| {
+            // The preflight responses are complete already.
+            if !is_preflight(req_info.method(), req_info.headers()) {
+                cors.add_headers(req_info.headers(), resp.headers_mut());
+            }
+
+            async move { Ok(resp) }
+        })
+    }
+
+    fn allowed_origin(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        if self.any_origin && !self.credentials {
+            return Some(HeaderValue::from_static("*"));
+        }
+
+        // Any origin could read the credentialed responses if it was echoed back, so only the explicit ones are.
+        let explicit = origin.to_str().is_ok_and(|origin| {
+            self.origins.iter().any(|allowed| allowed == origin)
+                || self.origin_regexes.iter().any(|regex| regex.is_match(origin))
+        });
+
+        if explicit {
+            Some(origin.clone())
+        } else {
+            self.any_origin.then(|| HeaderValue::from_static("*"))
+        }
+    }
+
+    fn add_headers(&self, req_headers: &HeaderMap, resp_headers: &mut HeaderMap) {
+        let allow_origin = req_headers
+            .get(header::ORIGIN)
+            .and_then(|origin| self.allowed_origin(origin));
+
+        // The header depends on the origin unless any origin gets the same one.
+        if !self.any_origin || self.credentials {
+            resp_headers.append(header::VARY, HeaderValue::from_static("origin"));
+        }
+
+        let allow_origin = match allow_origin {
+            Some(allow_origin) => allow_origin,
+            None => return,
+        };
+
+        // The browsers reject the credentials with a `*` origin anyway.
+        let credentials = self.credentials && allow_origin != "*";
+        resp_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if credentials {
+            resp_headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(val) = join_header_names(&self.expose_headers) {
+            resp_headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, val);
+        }
+    }
+
+    fn preflight(&self, req_headers: &HeaderMap) -> Response<Body> {
+        let mut resp = Response::new(Body::empty());
+        *resp.status_mut() = StatusCode::NO_CONTENT;
+
+        let method = req_headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|val| Method::from_bytes(val.as_bytes()).ok());
+        let requested_headers = req_headers
+            .get_all(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+
+        let method_allowed = method.is_some_and(|method| self.methods.contains(&method));
+        let headers_allowed = self.headers.as_ref().is_none_or(|headers| {
+            requested_headers
+                .iter()
+                .all(|name| headers.iter().any(|allowed| allowed.as_str() == name))
+        });
+        let origin_allowed = req_headers
+            .get(header::ORIGIN)
+            .and_then(|origin| self.allowed_origin(origin))
+            .is_some();
+
+        if !origin_allowed || !method_allowed || !headers_allowed {
+            *resp.status_mut() = StatusCode::FORBIDDEN;
+            resp.headers_mut()
+                .append(header::VARY, HeaderValue::from_static("origin"));
+            return resp;
+        }
+
+        self.add_headers(req_headers, resp.headers_mut());
+
+        let headers = resp.headers_mut();
+        let methods = self.methods.iter().map(Method::as_str).collect::<Vec<_>>().join(", ");
+        if let Ok(val) = HeaderValue::from_str(&methods) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, val);
+        }
+
+        let allow_headers = match self.headers {
+            Some(ref allowed) => join_header_names(allowed),
+            None => HeaderValue::from_str(&requested_headers.join(", ")).ok(),
+        };
+        if let Some(val) = allow_headers.filter(|val| !val.is_empty()) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, val);
+        }
+        if self.headers.is_none() {
+            headers.append(header::VARY, HeaderValue::from_static("access-control-request-headers"));
+        }
+
+        if let Some(max_age) = self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age.as_secs()));
+        }
+
+        resp
+    }
+}
+
+fn is_preflight(method: &Method, headers: &HeaderMap) -> bool {
+    method == Method::OPTIONS
+        && headers.contains_key(header::ORIGIN)
+        && headers.contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+fn join_header_names(names: &[HeaderName]) -> Option<HeaderValue> {
+    if names.is_empty() {
+        return None;
+    }
+
+    let joined = names.iter().map(HeaderName::as_str).collect::<Vec<_>>().join(", ");
+    HeaderValue::from_str(&joined).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_origin() {
+        let cors = Cors::new()
+            .allow_origin("https://example.com")
+            .allow_origin_regex(r"https://[a-z]+\.example\.com")
+            .unwrap();
+
+        let origin = |val| cors.allowed_origin(&HeaderValue::from_static(val));
+        assert_eq!(origin("https://example.com").unwrap(), "https://example.com");
+        assert_eq!(origin("https://api.example.com").unwrap(), "https://api.example.com");
+        assert_eq!(origin("https://example.com.evil.com"), None);
+        assert_eq!(origin("https://x.example.com.evil.com"), None);
+        assert_eq!(origin("https://evil.com/https://x.example.com"), None);
+        assert_eq!(origin("http://example.com"), None);
+
+        let any = Cors::new().allow_any_origin();
+        assert_eq!(
+            any.allowed_origin(&HeaderValue::from_static("https://a.com")).unwrap(),
+            "*"
+        );
+        // The origins aren't echoed back with the credentials unless they're allowed explicitly.
+        let any = any.allow_credentials(true).allow_origin("https://b.com");
+        assert_eq!(
+            any.allowed_origin(&HeaderValue::from_static("https://a.com")).unwrap(),
+            "*"
+        );
+        assert_eq!(any.allowed_origin(&HeaderValue::from_static("null")).unwrap(), "*");
+        assert_eq!(
+            any.allowed_origin(&HeaderValue::from_static("https://b.com")).unwrap(),
+            "https://b.com"
+        );
+
+        let mut headers = HeaderMap::new();
+        any.add_headers(
+            &vec![(header::ORIGIN, HeaderValue::from_static("https://a.com"))]
+                .into_iter()
+                .collect(),
+            &mut headers,
+        );
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS), None);
+
+        assert!(Cors::new().allow_origin_regex("(").is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use hyper::Method;
+use std::fmt;
+use std::panic::Location;
+
 /// The error type used by the `Routerify` library.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -13,12 +17,39 @@ pub enum Error {
     #[error("Could not create an exact match regex for the route path: {1}")]
     GeneratePrefixMatchRegex(#[source] regex::Error, String),
 
+    #[error("Invalid route header matcher: {0}")]
+    InvalidHeaderMatcher(String),
+
+    #[error("Invalid route query matcher: {0}")]
+    InvalidQueryMatcher(String),
+
+    #[error("Invalid host pattern: {0}")]
+    InvalidHostPattern(String),
+
+    #[error("Invalid CORS origin: {0}")]
+    InvalidCorsOrigin(String),
+
+    #[error("Invalid IP range: {0}")]
+    InvalidIpRange(String),
+
+    #[error("Couldn't build the router:{}", BuildError::list(.0))]
+    BuildRouter(Vec<BuildError>),
+
     #[error("No handlers added to handle non-existent routes. Tips: Please add an '.any' route at the bottom to handle any routes.")]
     HandleNonExistentRoute,
 
+    #[error("The request method is not allowed for the route, the allowed methods: {}", join_methods(.0))]
+    MethodNotAllowed(Vec<Method>),
+
     #[error("A route was unable to handle the pre middleware request")]
     HandlePreMiddlewareRequest(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
 
+    #[error("An around middleware was unable to handle the request")]
+    HandleAroundMiddlewareRequest(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("The rest of the request processing is no longer available to the around middleware")]
+    AroundMiddlewareNextUnavailable,
+
     #[error("A route was unable to handle the request for target: {1}")]
     HandleRequest(#[source] Box<dyn std::error::Error + Send + Sync + 'static>, String),
 
@@ -27,4 +58,418 @@ pub enum Error {
 
     #[error("One of the post middlewares (with info) couldn't process the response")]
     HandlePostMiddlewareWithInfoRequest(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Invalid route param '{0}': {1}")]
+    InvalidRouteParam(String, String),
+
+    #[cfg(feature = "serde")]
+    #[error("Couldn't deserialize the route params: {0}")]
+    DeserializeRouteParams(String),
+
+    #[cfg(feature = "serde")]
+    #[error("Couldn't deserialize the query string: {0}")]
+    DeserializeQuery(String),
+
+    #[cfg(feature = "cookies")]
+    #[error("Invalid cookie: {0}")]
+    InvalidCookie(String),
+
+    #[error("Couldn't create the response")]
+    CreateResponse(#[source] http::Error),
+
+    #[error("Invalid redirect status: {0}, it must be a 3xx status")]
+    InvalidRedirectStatus(hyper::StatusCode),
+
+    #[cfg(feature = "serde")]
+    #[error("Couldn't serialize the response body as JSON")]
+    SerializeJson(#[source] serde_json::Error),
+
+    #[error("The request body exceeded the size limit of {0} bytes")]
+    RequestBodyTooLarge(u64),
+
+    #[error("Couldn't read the request body")]
+    ReadRequestBody(#[source] hyper::Error),
+
+    #[error("The request body wasn't received within the idle timeout of {0:?}")]
+    RequestBodyTimeout(std::time::Duration),
+
+    #[cfg(feature = "serde")]
+    #[error("Couldn't deserialize the request body: {0}")]
+    DeserializeBody(#[source] serde_json::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("Couldn't deserialize the request form: {0}")]
+    DeserializeForm(String),
+
+    #[error("Unsupported request content type: {0:?}")]
+    UnsupportedContentType(String),
+
+    #[cfg(feature = "multipart")]
+    #[error("Couldn't parse the multipart body: {0}")]
+    ParseMultipart(String),
+
+    #[cfg(feature = "multipart")]
+    #[error("Couldn't save the multipart field")]
+    SaveMultipartField(#[source] std::io::Error),
+
+    #[cfg(feature = "jwt")]
+    #[error("Invalid JWT key: {0}")]
+    InvalidJwtKey(String),
+
+    #[error("The session or the rate limit store failed")]
+    Store(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("None of the available media types is acceptable: {}", .0.join(", "))]
+    NotAcceptable(Vec<String>),
+
+    #[error("The request path was rewritten too many times by the pre middlewares, the last path: {0}")]
+    TooManyRewrites(String),
+
+    #[error("The route handler didn't respond within the timeout of {0:?}")]
+    HandlerTimeout(std::time::Duration),
+
+    #[error("A route handler or middleware panicked: {0}")]
+    Panicked(String),
+
+    #[error("The service is shutting down and doesn't accept new requests")]
+    ShuttingDown,
+
+    #[error("No data of the type is shared with the request: {0}")]
+    MissingData(&'static str),
+
+    #[error("The route name is used more than once: {0}")]
+    DuplicateRouteName(String),
+
+    #[error("No route is named: {0}")]
+    UnknownRouteName(String),
+
+    #[error("Missing the param '{1}' to generate the URL of the route: {0}")]
+    MissingRouteParam(String, String),
+}
+
+/// The kind of an [`Error`](./enum.Error.html), which lets an error handler tell the router generated errors apart from
+/// the errors raised by the route handlers and the middlewares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The request path couldn't be decoded.
+    DecodeRequestPath,
+
+    /// The router couldn't be built e.g. a route path is not valid.
+    Build,
+
+    /// No route matched the request.
+    NoRoute,
+
+    /// A route matched the request path but not the request method, the allowed methods are carried by the
+    /// [`Error::MethodNotAllowed`](./enum.Error.html#variant.MethodNotAllowed) error.
+    MethodNotAllowed,
+
+    /// A route handler or a middleware returned an error, the original error can be accessed with
+    /// [`downcast_ref`](./enum.Error.html#method.downcast_ref).
+    Handler,
+
+    /// The route params couldn't be parsed or deserialized e.g. a param is missing or it's not a valid number.
+    RouteParams,
+
+    /// The query string couldn't be deserialized e.g. a required key is missing or it's not a valid number.
+    #[cfg(feature = "serde")]
+    Query,
+
+    /// A response couldn't be created by the [response helpers](./response/index.html), or a cookie is not valid.
+    Response,
+
+    /// The request body exceeded the configured size limit.
+    BodyTooLarge,
+
+    /// The request body couldn't be read e.g. the client sent nothing for too long.
+    ReadBody,
+
+    /// The request body couldn't be deserialized e.g. it's not a valid JSON or a form field is missing.
+    #[cfg(feature = "serde")]
+    Body,
+
+    /// The request body has an unexpected `Content-Type`.
+    UnsupportedContentType,
+
+    /// The [multipart](./multipart/index.html) request body couldn't be parsed, or a field couldn't be saved into a file.
+    #[cfg(feature = "multipart")]
+    Multipart,
+
+    /// A [session store](./session/trait.SessionStore.html) or a [rate limit store](./middleware/trait.RateLimitStore.html)
+    /// failed.
+    Store,
+
+    /// The client doesn't accept any of the media types a [negotiated](./response/struct.Negotiated.html) response is
+    /// available in.
+    NotAcceptable,
+
+    /// The pre middlewares rewrote the request path too many times, most likely in a loop.
+    TooManyRewrites,
+
+    /// A route handler didn't respond within the [timeout](./struct.RouterBuilder.html#method.timeout).
+    Timeout,
+
+    /// A route handler or a middleware panicked.
+    Panic,
+
+    /// The service is shutting down.
+    ShuttingDown,
+
+    /// The data requested by an [extractor](./handler/struct.Data.html) isn't shared with the request.
+    MissingData,
+
+    /// The URL of a named route couldn't be generated by [`Router::url_for`](./struct.Router.html#method.url_for) e.g. no
+    /// route has the name or a param is missing.
+    UrlFor,
+}
+
+impl Error {
+    /// Returns the kind of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, ErrorKind};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .err_handler(|err: routerify::Error| async move {
+    ///         let status = match err.kind() {
+    ///             ErrorKind::NoRoute => StatusCode::NOT_FOUND,
+    ///             ErrorKind::BodyTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+    ///             _ => StatusCode::INTERNAL_SERVER_ERROR,
+    ///         };
+    ///         Response::builder().status(status).body(Body::empty()).unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::DecodeRequestPath(_) => ErrorKind::DecodeRequestPath,
+            Error::CreateRouterRegexSet(_)
+            | Error::GenerateExactMatchRegex(..)
+            | Error::GeneratePrefixMatchRegex(..)
+            | Error::InvalidHeaderMatcher(_)
+            | Error::InvalidQueryMatcher(_)
+            | Error::InvalidHostPattern(_)
+            | Error::InvalidCorsOrigin(_)
+            | Error::InvalidIpRange(_)
+            | Error::BuildRouter(_)
+            | Error::DuplicateRouteName(_) => ErrorKind::Build,
+            #[cfg(feature = "jwt")]
+            Error::InvalidJwtKey(_) => ErrorKind::Build,
+            Error::HandleNonExistentRoute => ErrorKind::NoRoute,
+            Error::MethodNotAllowed(_) => ErrorKind::MethodNotAllowed,
+            Error::HandlePreMiddlewareRequest(_)
+            | Error::HandleAroundMiddlewareRequest(_)
+            | Error::AroundMiddlewareNextUnavailable
+            | Error::HandleRequest(..)
+            | Error::HandlePostMiddlewareWithoutInfoRequest(_)
+            | Error::HandlePostMiddlewareWithInfoRequest(_) => ErrorKind::Handler,
+            Error::CreateResponse(_) | Error::InvalidRedirectStatus(_) => ErrorKind::Response,
+            #[cfg(feature = "cookies")]
+            Error::InvalidCookie(_) => ErrorKind::Response,
+            #[cfg(feature = "serde")]
+            Error::SerializeJson(_) => ErrorKind::Response,
+            Error::InvalidRouteParam(..) => ErrorKind::RouteParams,
+            #[cfg(feature = "serde")]
+            Error::DeserializeRouteParams(_) => ErrorKind::RouteParams,
+            #[cfg(feature = "serde")]
+            Error::DeserializeQuery(_) => ErrorKind::Query,
+            Error::RequestBodyTooLarge(_) => ErrorKind::BodyTooLarge,
+            Error::ReadRequestBody(_) | Error::RequestBodyTimeout(_) => ErrorKind::ReadBody,
+            #[cfg(feature = "serde")]
+            Error::DeserializeBody(_) | Error::DeserializeForm(_) => ErrorKind::Body,
+            Error::UnsupportedContentType(_) => ErrorKind::UnsupportedContentType,
+            #[cfg(feature = "multipart")]
+            Error::ParseMultipart(_) | Error::SaveMultipartField(_) => ErrorKind::Multipart,
+            Error::Store(_) => ErrorKind::Store,
+            Error::NotAcceptable(_) => ErrorKind::NotAcceptable,
+            Error::TooManyRewrites(_) => ErrorKind::TooManyRewrites,
+            Error::HandlerTimeout(_) => ErrorKind::Timeout,
+            Error::Panicked(_) => ErrorKind::Panic,
+            Error::ShuttingDown => ErrorKind::ShuttingDown,
+            Error::MissingData(_) => ErrorKind::MissingData,
+            Error::UnknownRouteName(_) | Error::MissingRouteParam(..) => ErrorKind::UrlFor,
+        }
+    }
+
+    /// Returns a reference to the original error if it is of type `E`.
+    ///
+    /// It's mostly useful in an error handler to access the error returned by a route handler or a middleware.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, StatusCode};
+    /// use std::io;
+    ///
+    /// # fn run() -> Router<Body, io::Error> {
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Err::<Response<Body>, _>(io::Error::new(io::ErrorKind::TimedOut, "Database timed out")) })
+    ///     .err_handler(|err: routerify::Error| async move {
+    ///         let status = match err.downcast_ref::<io::Error>() {
+    ///             Some(e) if e.kind() == io::ErrorKind::TimedOut => StatusCode::GATEWAY_TIMEOUT,
+    ///             _ => StatusCode::INTERNAL_SERVER_ERROR,
+    ///         };
+    ///         Response::builder().status(status).body(Body::empty()).unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        std::error::Error::source(self).and_then(|err| err.downcast_ref::<E>())
+    }
+
+    /// Consumes this error and returns the original error if it is of type `E`, otherwise the error itself is
+    /// returned back.
+    ///
+    /// Only the errors returned by the route handlers and the middlewares can be extracted this way, use
+    /// [`downcast_ref`](#method.downcast_ref) to access the source of the other errors.
+    pub fn downcast<E: std::error::Error + 'static>(self) -> Result<E, Error> {
+        match self {
+            Error::HandlePreMiddlewareRequest(err) => err
+                .downcast::<E>()
+                .map(|err| *err)
+                .map_err(Error::HandlePreMiddlewareRequest),
+            Error::HandleAroundMiddlewareRequest(err) => err
+                .downcast::<E>()
+                .map(|err| *err)
+                .map_err(Error::HandleAroundMiddlewareRequest),
+            Error::HandleRequest(err, target) => err
+                .downcast::<E>()
+                .map(|err| *err)
+                .map_err(|err| Error::HandleRequest(err, target)),
+            Error::HandlePostMiddlewareWithoutInfoRequest(err) => err
+                .downcast::<E>()
+                .map(|err| *err)
+                .map_err(Error::HandlePostMiddlewareWithoutInfoRequest),
+            Error::HandlePostMiddlewareWithInfoRequest(err) => err
+                .downcast::<E>()
+                .map(|err| *err)
+                .map_err(Error::HandlePostMiddlewareWithInfoRequest),
+            err => Err(err),
+        }
+    }
+}
+
+/// Joins the methods as the value of the `Allow` header.
+pub(crate) fn join_methods(methods: &[Method]) -> String {
+    methods.iter().map(Method::as_str).collect::<Vec<_>>().join(", ")
+}
+
+/// A problem with one of the registrations on a [`RouterBuilder`](./struct.RouterBuilder.html) e.g. a route with an invalid path.
+///
+/// All the problems are collected by the builder and returned together from the [`build`](./struct.RouterBuilder.html#method.build)
+/// method as an [`Error::BuildRouter`](./enum.Error.html#variant.BuildRouter) error.
+#[derive(Debug)]
+pub struct BuildError {
+    methods: Vec<Method>,
+    path: String,
+    location: &'static Location<'static>,
+    source: Box<Error>,
+}
+
+impl BuildError {
+    pub(crate) fn new<P: Into<String>>(
+        methods: &[Method],
+        path: P,
+        location: &'static Location<'static>,
+        source: Error,
+    ) -> BuildError {
+        BuildError {
+            methods: methods.to_vec(),
+            path: path.into(),
+            location,
+            source: Box::new(source),
+        }
+    }
+
+    /// Returns the methods of the failed route, it's empty for the other registrations e.g. a middleware or a host scope.
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Returns the path of the failed registration including the prefix of any scope it's mounted on, or the host pattern of
+    /// a host scope.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the source location of the registration.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    fn list(errors: &[BuildError]) -> String {
+        errors.iter().map(|err| format!("\n  {}", err)).collect()
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, method) in self.methods.iter().enumerate() {
+            let sep = if idx + 1 == self.methods.len() { " " } else { "," };
+            write!(f, "{}{}", method, sep)?;
+        }
+
+        // Some causes e.g. a regex syntax error span multiple lines, they're joined to keep a problem on a single line.
+        let source = self.source.to_string();
+        let source = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        write!(f, "{} (at {}): {}", self.path, self.location, source.join(" "))
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_downcast() {
+        let err = Error::HandleRequest(io::Error::other("Database timed out").into(), "/".to_owned());
+        assert_eq!(err.kind(), ErrorKind::Handler);
+        assert_eq!(
+            err.downcast_ref::<io::Error>().unwrap().to_string(),
+            "Database timed out"
+        );
+        assert!(err.downcast_ref::<std::fmt::Error>().is_none());
+
+        let err = match err.downcast::<std::fmt::Error>() {
+            Err(err) => err,
+            Ok(_) => panic!("Downcasted to the wrong type"),
+        };
+        assert_eq!(err.downcast::<io::Error>().unwrap().kind(), io::ErrorKind::Other);
+
+        let err = Error::HandleNonExistentRoute;
+        assert_eq!(err.kind(), ErrorKind::NoRoute);
+        assert!(err.downcast::<io::Error>().is_err());
+
+        let err = Error::MethodNotAllowed(vec![Method::GET, Method::POST]);
+        assert_eq!(err.kind(), ErrorKind::MethodNotAllowed);
+        assert!(err.to_string().ends_with("the allowed methods: GET, POST"));
+    }
 }
@@ -1,3 +1,7 @@
 pub use request::RequestExt;
+#[cfg(feature = "cookies")]
+pub use response::ResponseExt;
 
 mod request;
+#[cfg(feature = "cookies")]
+mod response;
@@ -1,7 +1,11 @@
 use crate::data_map::SharedDataMap;
-use crate::types::{RequestMeta, RouteParams};
+#[cfg(feature = "cookies")]
+use crate::session::Session;
+#[cfg(feature = "cookies")]
+use crate::types::Cookies;
+use crate::types::{resolve_client_ip, Accept, PeerAddr, QueryParams, RequestContext, RequestMeta, RouteParams};
 use hyper::Request;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 
 /// A extension trait which extends the [`hyper::Request`](https://docs.rs/hyper/0.13.5/hyper/struct.Request.html) type with some helpful methods.
 pub trait RequestExt {
@@ -58,8 +62,276 @@ pub trait RequestExt {
     /// ```
     fn param<P: Into<String>>(&self, param_name: P) -> Option<&String>;
 
+    /// It parses the route parameter value by the name of the parameter into a type which implements the `FromStr` trait
+    /// e.g. a number or a UUID, instead of parsing the value returned by the [`param`](#tymethod.param) method by hand.
+    ///
+    /// It returns an [`Error::InvalidRouteParam`](../enum.Error.html#variant.InvalidRouteParam) error if the parameter is
+    /// missing or it couldn't be parsed, the default error handler responds to it with `400 Bad Request`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///     .get("/users/:userId/books/:bookId", |req| async move {
+    ///         let user_id: u64 = req.param_as("userId")?;
+    ///         let book_id: u32 = req.param_as("bookId")?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("User #{}, Book #{}", user_id, book_id))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn param_as<T, P>(&self, param_name: P) -> crate::Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+        P: Into<String>;
+
+    /// It returns the route parameter value by the name of the parameter as it's sent in the request path i.e. before the
+    /// percent-decoding, while the [`param`](#tymethod.param) method returns the decoded value.
+    ///
+    /// It's useful with the [`PercentDecodeMode::KeepEncodedSlashes`](../enum.PercentDecodeMode.html#variant.KeepEncodedSlashes)
+    /// mode if a parameter value is itself a path or a URL which contains the encoded slashes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, PercentDecodeMode};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .percent_decode(PercentDecodeMode::KeepEncodedSlashes)
+    ///     .get("/artifacts/:name", |req| async move {
+    ///         // A request to `/artifacts/libs%2Fcore-1.0.jar` responds with `libs/core-1.0.jar (libs%2Fcore-1.0.jar)`.
+    ///         let name = req.param("name").unwrap();
+    ///         let raw_name = req.raw_param("name").unwrap();
+    ///
+    ///         Ok(Response::new(Body::from(format!("{} ({})", name, raw_name))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn raw_param<P: Into<String>>(&self, param_name: P) -> Option<&str>;
+
+    /// It deserializes the route parameters into a type which implements the serde `Deserialize` trait, usually a struct with
+    /// a field for each parameter. The numbers and the booleans are parsed from the parameter values and a missing `Option`
+    /// field becomes `None`.
+    ///
+    /// It returns an [`Error::DeserializeRouteParams`](../enum.Error.html#variant.DeserializeRouteParams) error which names
+    /// the missing or the invalid parameter, the default error handler responds to it with `400 Bad Request`.
+    ///
+    /// This method is available only if the `serde` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct IssuePath {
+    ///     owner: String,
+    ///     name: String,
+    ///     number: u64,
+    /// }
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///     .get("/repos/:owner/:name/issues/:number", |req| async move {
+    ///         let path: IssuePath = req.params_as()?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("Issue #{} of {}/{}", path.number, path.owner, path.name))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "serde")]
+    fn params_as<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T>;
+
+    /// It returns the decoded pairs of the request query string as [QueryParams](../struct.QueryParams.html) type.
+    ///
+    /// The query string is parsed once on the first access, it's parsed again only if a pre middleware changes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/posts", |req| async move {
+    ///         let tags = req.query_params().get_all("tag").collect::<Vec<_>>();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Tags: {}", tags.join(", ")))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn query_params(&self) -> &QueryParams;
+
+    /// It returns the first decoded value of the specified key in the request query string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/search", |req| async move {
+    ///         let term = req.query("q").unwrap_or("");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Searching for: {}", term))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn query(&self, key: &str) -> Option<&str>;
+
+    /// It deserializes the request query string into a type which implements the serde `Deserialize` trait, usually a struct
+    /// with a field for each key. The values of a repeated key are collected into a sequence field e.g. a `Vec`, while a scalar
+    /// field takes the first value. A missing `Option` field becomes `None`, a missing `Vec` field needs the `#[serde(default)]`
+    /// attribute to default to an empty one.
+    ///
+    /// It returns an [`Error::DeserializeQuery`](../enum.Error.html#variant.DeserializeQuery) error which names the missing
+    /// or the invalid key, the default error handler responds to it with `400 Bad Request`.
+    ///
+    /// This method is available only if the `serde` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Pagination {
+    ///     page: u32,
+    ///     per_page: Option<u32>,
+    ///     #[serde(default)]
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///     .get("/posts", |req| async move {
+    ///         let pagination: Pagination = req.query_as()?;
+    ///         let per_page = pagination.per_page.unwrap_or(20);
+    ///
+    ///         Ok(Response::new(Body::from(format!("Page {} of {} posts tagged {:?}", pagination.page, per_page, pagination.tags))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "serde")]
+    fn query_as<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T>;
+
+    /// It returns the cookies sent in the `Cookie` request headers as [Cookies](../struct.Cookies.html) type.
+    ///
+    /// The headers are parsed once on the first access, the malformed pairs are skipped.
+    #[cfg(feature = "cookies")]
+    fn cookies(&self) -> &Cookies;
+
+    /// It returns the value of the specified cookie sent in the `Cookie` request headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/profile", |req| async move {
+    ///         match req.cookie("sid") {
+    ///             Some(sid) => Ok(Response::new(Body::from(format!("Session: {}", sid)))),
+    ///             None => Ok(Response::builder().status(StatusCode::UNAUTHORIZED).body(Body::empty()).unwrap()),
+    ///         }
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[cfg(feature = "cookies")]
+    fn cookie(&self, name: &str) -> Option<&str>;
+
+    /// It returns the media types accepted by the client in the `Accept` request headers as [Accept](../struct.Accept.html)
+    /// type, ranked by their quality values.
+    ///
+    /// The headers are parsed once on the first access, the malformed media ranges are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/report", |req| async move {
+    ///         if !req.accepts().accepts("text/csv") {
+    ///             return Ok(Response::builder().status(StatusCode::NOT_ACCEPTABLE).body(Body::empty()).unwrap());
+    ///         }
+    ///
+    ///         Ok(Response::new(Body::from("id,name\n1,alice\n")))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn accepts(&self) -> &Accept;
+
     /// It returns the remote address of the incoming request.
     ///
+    /// # Panics
+    ///
+    /// It panics if the request is not received over a TCP connection, use the [`peer_addr`](#tymethod.peer_addr) method
+    /// to handle the other kind of peers e.g. a Unix domain socket client.
+    ///
     /// # Examples
     ///
     /// ```
@@ -83,14 +355,224 @@ pub trait RequestExt {
     /// ```
     fn remote_addr(&self) -> SocketAddr;
 
+    /// It returns the peer of the connection which the request is received over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, PeerAddr};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/hello", |req| async move {
+    ///         let peer = match req.peer_addr() {
+    ///             PeerAddr::Tcp(addr) => addr.to_string(),
+    ///             PeerAddr::Unix { uid: Some(uid), .. } => format!("local user {}", uid),
+    ///             _ => "unknown".to_owned(),
+    ///         };
+    ///
+    ///         Ok(Response::new(Body::from(format!("Hello from : {}", peer))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn peer_addr(&self) -> &PeerAddr;
+
+    /// It returns the IP address of the client, which may be behind the proxies.
+    ///
+    /// The address is taken from the `Forwarded`, the `X-Forwarded-For` or the `X-Real-IP` header only if the request is sent
+    /// by a proxy in the [trusted proxies](../struct.RouterBuilder.html#method.trusted_proxy) of the router, as any client can
+    /// send them. The hops are walked from the nearest one and the first address which isn't a trusted proxy is the client.
+    /// Otherwise, it's the address of the connection. It returns `None` if the request is not received over a TCP connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{IpRange, Router};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> routerify::Result<Router<Body, Infallible>> {
+    /// let router = Router::builder()
+    ///     .trusted_proxy("10.0.0.0/24".parse::<IpRange>()?)
+    ///     .get("/hello", |req| async move {
+    ///         let client_ip = req.client_ip().map(|ip| ip.to_string()).unwrap_or_default();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Hello from : {}", client_ip))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # Ok(router)
+    /// # }
+    /// # run().unwrap();
+    /// ```
+    fn client_ip(&self) -> Option<IpAddr>;
+
+    /// It returns the portion of the request host matched by the wildcard of a host scoped router, e.g. `tenant` for the
+    /// `tenant.example.com` host when the router is mounted with the `*.example.com` host pattern.
+    ///
+    /// It returns an empty string for a router mounted with an exact host pattern, the value of the first param for a host
+    /// pattern with the named params e.g. `:tenant.example.com`, and `None` if the request is not handled by a host scoped router.
+    ///
+    /// Please refer to the [`host_scope`](../struct.RouterBuilder.html#method.host_scope) method for an example.
+    fn host_param(&self) -> Option<&str>;
+
+    /// It returns the named params of the host pattern of a host scoped router e.g. `tenant` and `region` for the
+    /// `:tenant.:region.example.com` host pattern, each param matches a single label of the request host.
+    ///
+    /// It's empty for a router mounted without the named params and `None` if the request is not handled by a host scoped router.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let tenant_router = Router::builder()
+    ///     .get("/", |req| async move {
+    ///         // A request to `acme.eu.example.com` responds with `Welcome acme (eu)`.
+    ///         let params = req.host_params().unwrap();
+    ///         let tenant = params.get("tenant").unwrap();
+    ///         let region = params.get("region").unwrap();
+    ///
+    ///         Ok(Response::new(Body::from(format!("Welcome {} ({})", tenant, region))))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .host_scope(":tenant.:region.example.com", tenant_router)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn host_params(&self) -> Option<&RouteParams>;
+
+    /// It returns the path template of the matched route e.g. `/users/:id`, including the prefix of the scope the route is
+    /// mounted on. It's useful to label the logs and the metrics without the high cardinality of the actual request paths.
+    ///
+    /// It returns `None` in the pre middlewares, and for the requests handled by the default 404 route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let api = Router::builder()
+    ///     .get("/users/:id", |req| async move {
+    ///         // It prints `/api/users/:id` for a request to `/api/users/10`.
+    ///         println!("{}", req.matched_route().unwrap());
+    ///
+    ///         Ok(Response::new(Body::empty()))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///      .scope("/api", api)
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn matched_route(&self) -> Option<&str>;
+
+    /// It returns the path template of the scope the matched route is mounted on e.g. `/tenants/:tenant_id/blog`, including
+    /// the prefixes of the outer scopes. It's useful to build the links relative to where a router is mounted.
+    ///
+    /// It returns an empty string for the routes of the root router, in the pre middlewares and for the requests handled by the
+    /// default 404 route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// fn blog_router() -> Router<Body, Infallible> {
+    ///     Router::builder()
+    ///         .post("/posts", |req| async move {
+    ///             // It redirects a request to `/tenants/acme/blog/posts` to `/tenants/acme/blog/posts/1`, and the
+    ///             // `mount_prefix` is `/tenants/:tenant_id/blog`.
+    ///             let location = format!("{}/posts/1", req.matched_mount_prefix());
+    ///
+    ///             Ok(Response::builder()
+    ///                 .status(StatusCode::SEE_OTHER)
+    ///                 .header("location", location)
+    ///                 .body(Body::empty())
+    ///                 .unwrap())
+    ///         })
+    ///         .build()
+    ///         .unwrap()
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .scope("/tenants/:tenant_id/blog", blog_router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    fn mount_prefix(&self) -> &str;
+
+    /// It returns the part of the request path matched by the [`mount_prefix`](#tymethod.mount_prefix), e.g.
+    /// `/tenants/acme/blog` for a request to `/tenants/acme/blog/posts`. The path is returned as it's sent by the client, so
+    /// the percent-encoded characters are kept.
+    ///
+    /// It returns an empty string for the routes of the root router, in the pre middlewares and for the requests handled by the
+    /// default 404 route.
+    fn matched_mount_prefix(&self) -> &str;
+
     /// Access data which was shared by the [`RouterBuilder`](../struct.RouterBuilder.html) method
     /// [`data`](../struct.RouterBuilder.html#method.data).
     ///
     /// Please refer to the [Data and State Sharing](../index.html#data-and-state-sharing) for more info.
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T>;
+
+    /// Sets a request-local value of type `T`, replacing the previous value of the same type. It's mostly used by a pre
+    /// middleware to pass a value e.g. the authenticated user to the route handler.
+    ///
+    /// Unlike the [`data`](#tymethod.data) method, which accesses the app state shared by all the requests, the value is
+    /// only visible while processing this request.
+    ///
+    /// Please refer to the [Request Context](../index.html#request-context) for more info.
+    fn set_context<T: Send + Sync + 'static>(&mut self, value: T);
+
+    /// Access the request-local value of type `T` which was set by the [`set_context`](#tymethod.set_context) method.
+    ///
+    /// Please refer to the [Request Context](../index.html#request-context) for more info.
+    fn context<T: Send + Sync + 'static>(&self) -> Option<&T>;
+
+    /// Access the session of the request, which is loaded by the [`session`](../session/fn.session.html) middleware. It's
+    /// `None` if the middleware isn't added to the router.
+    ///
+    /// Please refer to the [session](../session/index.html) module for an example.
+    #[cfg(feature = "cookies")]
+    fn session(&self) -> Option<&Session>;
 }
 
-impl RequestExt for Request<hyper::Body> {
+impl<B> RequestExt for Request<B> {
     fn params(&self) -> &RouteParams {
         self.extensions()
             .get::<RequestMeta>()
@@ -99,15 +581,120 @@ impl RequestExt for Request<hyper::Body> {
     }
 
     fn param<P: Into<String>>(&self, param_name: P) -> Option<&String> {
-        self.params().get(&param_name.into())
+        self.params().get(param_name.into())
     }
 
-    fn remote_addr(&self) -> SocketAddr {
+    fn param_as<T, P>(&self, param_name: P) -> crate::Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+        P: Into<String>,
+    {
+        self.params().get_as(param_name)
+    }
+
+    fn raw_param<P: Into<String>>(&self, param_name: P) -> Option<&str> {
+        self.params().get_raw(param_name.into())
+    }
+
+    #[cfg(feature = "serde")]
+    fn params_as<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T> {
+        crate::types::from_route_params(self.params())
+    }
+
+    fn query_params(&self) -> &QueryParams {
+        self.extensions()
+            .get::<RequestMeta>()
+            .map(|meta| meta.query_params(self.uri().query()))
+            .expect("Routerify: No RequestMeta added while processing request")
+    }
+
+    fn query(&self, key: &str) -> Option<&str> {
+        self.query_params().get(key)
+    }
+
+    #[cfg(feature = "serde")]
+    fn query_as<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T> {
+        crate::types::from_query_params(self.query_params())
+    }
+
+    #[cfg(feature = "cookies")]
+    fn cookies(&self) -> &Cookies {
+        self.extensions()
+            .get::<RequestMeta>()
+            .map(|meta| meta.cookies(self.headers()))
+            .expect("Routerify: No RequestMeta added while processing request")
+    }
+
+    #[cfg(feature = "cookies")]
+    fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies().get(name)
+    }
+
+    fn accepts(&self) -> &Accept {
         self.extensions()
             .get::<RequestMeta>()
-            .and_then(|meta| meta.remote_addr())
+            .map(|meta| meta.accept(self.headers()))
+            .expect("Routerify: No RequestMeta added while processing request")
+    }
+
+    fn remote_addr(&self) -> SocketAddr {
+        self.peer_addr()
+            .as_socket_addr()
             .copied()
-            .expect("Routerify: No remote address added while processing request")
+            .expect("Routerify: The request is not received over a TCP connection")
+    }
+
+    fn peer_addr(&self) -> &PeerAddr {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.peer_addr())
+            .expect("Routerify: No peer address added while processing request")
+    }
+
+    fn client_ip(&self) -> Option<IpAddr> {
+        let peer = self.peer_addr().as_socket_addr()?;
+        let trusted_proxies = self
+            .extensions()
+            .get::<RequestMeta>()
+            .map(|meta| meta.trusted_proxies())
+            .unwrap_or_default();
+
+        Some(resolve_client_ip(peer.ip(), self.headers(), trusted_proxies))
+    }
+
+    fn host_param(&self) -> Option<&str> {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.host_param())
+    }
+
+    fn host_params(&self) -> Option<&RouteParams> {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.host_params())
+    }
+
+    fn matched_route(&self) -> Option<&str> {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.matched_route())
+    }
+
+    fn mount_prefix(&self) -> &str {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.mount_prefix())
+            .map(|(template, _)| &**template)
+            .unwrap_or("")
+    }
+
+    fn matched_mount_prefix(&self) -> &str {
+        self.extensions()
+            .get::<RequestMeta>()
+            .and_then(|meta| meta.mount_prefix())
+            .map(|(_, matched)| matched.as_str())
+            .unwrap_or("")
     }
 
     fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
@@ -121,6 +708,29 @@ impl RequestExt for Request<hyper::Body> {
             }
         }
 
-        return None;
+        None
+    }
+
+    fn set_context<T: Send + Sync + 'static>(&mut self, value: T) {
+        let ext = self.extensions_mut();
+
+        if let Some(context) = ext.get_mut::<RequestContext>() {
+            context.set(value);
+        } else {
+            let mut context = RequestContext::default();
+            context.set(value);
+            ext.insert(context);
+        }
+    }
+
+    fn context<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions()
+            .get::<RequestContext>()
+            .and_then(|context| context.get::<T>())
+    }
+
+    #[cfg(feature = "cookies")]
+    fn session(&self) -> Option<&Session> {
+        self.context::<Session>()
     }
 }
@@ -0,0 +1,28 @@
+use crate::types::SetCookie;
+use hyper::header::{self, HeaderValue};
+use hyper::Response;
+
+/// A extension trait which extends the [`hyper::Response`](https://docs.rs/hyper/0.13.5/hyper/struct.Response.html) type with some helpful methods.
+///
+/// This trait is available only if the `cookies` feature is enabled.
+pub trait ResponseExt {
+    /// It appends a `Set-Cookie` header built from the specified [`SetCookie`](../struct.SetCookie.html), the existing
+    /// `Set-Cookie` headers are kept.
+    ///
+    /// It returns an [`Error::InvalidCookie`](../enum.Error.html#variant.InvalidCookie) error if the cookie name, the value, the
+    /// path or the domain contains a character which is not allowed in the header.
+    ///
+    /// Please refer to the [`SetCookie`](../struct.SetCookie.html) type for an example.
+    fn set_cookie(&mut self, cookie: &SetCookie) -> crate::Result<()>;
+}
+
+impl<B> ResponseExt for Response<B> {
+    fn set_cookie(&mut self, cookie: &SetCookie) -> crate::Result<()> {
+        cookie.validate()?;
+
+        let val =
+            HeaderValue::from_str(&cookie.to_string()).map_err(|_| crate::Error::InvalidCookie(cookie.name.clone()))?;
+        self.headers_mut().append(header::SET_COOKIE, val);
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use crate::ext::RequestExt;
+use crate::types::RouteParams;
+use crate::Error;
+use futures::future::{self, BoxFuture};
+use hyper::{HeaderMap, Method, Request, Uri};
+use std::future::Future;
+use std::sync::Arc;
+
+/// A value which can be extracted from a request, so it can be an argument of a handler adapted by
+/// [`extract`](./fn.extract.html).
+///
+/// It's implemented for [`Method`](https://docs.rs/http/0.2/http/method/struct.Method.html), [`Uri`](https://docs.rs/http/0.2/http/uri/struct.Uri.html),
+/// [`HeaderMap`](https://docs.rs/http/0.2/http/header/struct.HeaderMap.html), [`RouteParams`](../struct.RouteParams.html)
+/// and [`Data`](./struct.Data.html). With the `serde` feature, it's also implemented for [`Params`](./struct.Params.html),
+/// [`Query`](./struct.Query.html), and [`Json`](../struct.Json.html) and [`Form`](./struct.Form.html) which read the request
+/// body. It's also implemented for [`BodyStream`](../body/struct.BodyStream.html) which streams the request body. With the
+/// `multipart` feature, it's implemented for [`Multipart`](../multipart/struct.Multipart.html) too.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::handler::{extract, FromRequest};
+/// use routerify::Router;
+/// use futures::future::{self, BoxFuture};
+/// use hyper::{Body, Request, Response};
+///
+/// struct UserAgent(String);
+///
+/// impl FromRequest for UserAgent {
+///     fn from_request(req: &mut Request<Body>) -> BoxFuture<'_, routerify::Result<Self>> {
+///         let user_agent = req.headers().get("user-agent").and_then(|v| v.to_str().ok()).unwrap_or_default();
+///         Box::pin(future::ready(Ok(UserAgent(user_agent.to_owned()))))
+///     }
+/// }
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .get("/", extract(|UserAgent(user_agent): UserAgent| async move {
+///         Ok(Response::new(Body::from(user_agent)))
+///     }))
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub trait FromRequest<ReqB = hyper::Body>: Sized {
+    /// Extracts the value from the request, the extractors of a handler run in the order of its arguments. An extractor which
+    /// reads the request body should be the last one.
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>>;
+}
+
+/// A handler function whose arguments are [extracted](./trait.FromRequest.html) from the request, it's implemented for the
+/// functions with up to 8 arguments. Refer to [`extract`](./fn.extract.html) for more info.
+pub trait Handler<Args, ReqB>: Send + Sync + 'static {
+    /// The type of the successful output of the handler e.g. a response.
+    type Output;
+
+    /// The error type of the handler, an error of an extractor is converted into it.
+    type Error;
+
+    /// Extracts the arguments from the request and runs the handler.
+    fn call(self: Arc<Self>, req: Request<ReqB>) -> BoxFuture<'static, Result<Self::Output, Self::Error>>;
+}
+
+macro_rules! impl_handler {
+    ($($arg:ident),*) => {
+        impl<F, R, T, E, ReqB, $($arg,)*> Handler<($($arg,)*), ReqB> for F
+        where
+            F: Fn($($arg,)*) -> R + Send + Sync + 'static,
+            R: Future<Output = Result<T, E>> + Send + 'static,
+            E: From<Error> + 'static,
+            ReqB: Send + 'static,
+            $($arg: FromRequest<ReqB> + Send + 'static,)*
+        {
+            type Output = T;
+            type Error = E;
+
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(self: Arc<Self>, mut req: Request<ReqB>) -> BoxFuture<'static, Result<T, E>> {
+                Box::pin(async move {
+                    $(let $arg = $arg::from_request(&mut req).await?;)*
+                    (*self)($($arg,)*).await
+                })
+            }
+        }
+    };
+}
+
+impl_handler!();
+impl_handler!(A1);
+impl_handler!(A1, A2);
+impl_handler!(A1, A2, A3);
+impl_handler!(A1, A2, A3, A4);
+impl_handler!(A1, A2, A3, A4, A5);
+impl_handler!(A1, A2, A3, A4, A5, A6);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7);
+impl_handler!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+/// Adapts a handler whose arguments are [extracted](./trait.FromRequest.html) from the request e.g. the route params, the
+/// query string, the shared data or the JSON body, into a route handler.
+///
+/// The errors of the extractors are converted into the router's error type, so it must implement `From<routerify::Error>`.
+/// The default error handler responds to e.g. an invalid route param or JSON body with `400 Bad Request`.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::handler::{extract, Data};
+/// use routerify::{RouteParams, Router};
+/// use hyper::{Body, Method, Response};
+/// # use std::collections::HashMap;
+/// # use std::sync::{Arc, Mutex};
+///
+/// #[derive(Clone, Default)]
+/// struct Db(Arc<Mutex<HashMap<String, String>>>);
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .data(Db::default())
+///     .get("/", extract(|method: Method| async move {
+///         Ok(Response::new(Body::from(format!("{} /", method))))
+///     }))
+///     .put("/users/:name", extract(|Data(db): Data<Db>, params: RouteParams| async move {
+///         let name = params.get("name").unwrap().to_owned();
+///         db.0.lock().unwrap().insert(name.clone(), name);
+///         Ok(Response::new(Body::from("Created")))
+///     }))
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn extract<H, Args, ReqB>(
+    handler: H,
+) -> impl Fn(Request<ReqB>) -> BoxFuture<'static, Result<H::Output, H::Error>> + Send + Sync + 'static
+where
+    H: Handler<Args, ReqB>,
+{
+    let handler = Arc::new(handler);
+    move |req| handler.clone().call(req)
+}
+
+/// Extracts a clone of the data shared by the [`data`](../struct.RouterBuilder.html#method.data) method, it fails with an
+/// [`Error::MissingData`](../enum.Error.html#variant.MissingData) error if there's no data of the type.
+#[derive(Debug, Clone)]
+pub struct Data<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static, ReqB> FromRequest<ReqB> for Data<T> {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        let data = req
+            .data::<T>()
+            .cloned()
+            .map(Data)
+            .ok_or_else(|| Error::MissingData(std::any::type_name::<T>()));
+        Box::pin(future::ready(data))
+    }
+}
+
+/// Extracts the route params deserialized by the [`params_as`](../ext/trait.RequestExt.html#tymethod.params_as) method.
+///
+/// This type is available only if the `serde` feature is enabled.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct Params<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned + Send + 'static, ReqB> FromRequest<ReqB> for Params<T> {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(req.params_as().map(Params)))
+    }
+}
+
+/// Extracts the query string deserialized by the [`query_as`](../ext/trait.RequestExt.html#tymethod.query_as) method.
+///
+/// This type is available only if the `serde` feature is enabled.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct Query<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned + Send + 'static, ReqB> FromRequest<ReqB> for Query<T> {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(req.query_as().map(Query)))
+    }
+}
+
+/// Reads the request body by the [`body::json`](../body/fn.json.html) function.
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned + Send + 'static> FromRequest<hyper::Body> for crate::Json<T> {
+    fn from_request(req: &mut Request<hyper::Body>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(async move { crate::body::json(req).await.map(crate::Json) })
+    }
+}
+
+/// Extracts the `application/x-www-form-urlencoded` request body deserialized by the [`body::form`](../body/fn.form.html)
+/// function, so the content type is checked and the body size is limited to `64 KiB`.
+///
+/// This type is available only if the `serde` feature is enabled.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct Form<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned + Send + 'static> FromRequest<hyper::Body> for Form<T> {
+    fn from_request(req: &mut Request<hyper::Body>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(async move { crate::body::form(req).await.map(Form) })
+    }
+}
+
+/// Takes the request body by the [`body::stream`](../body/fn.stream.html) function.
+impl FromRequest<hyper::Body> for crate::body::BodyStream {
+    fn from_request(req: &mut Request<hyper::Body>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(crate::body::stream(req)))
+    }
+}
+
+/// Creates a parser of the request body by the [`Multipart::new`](../multipart/struct.Multipart.html#method.new) function.
+#[cfg(feature = "multipart")]
+impl FromRequest<hyper::Body> for crate::multipart::Multipart {
+    fn from_request(req: &mut Request<hyper::Body>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(crate::multipart::Multipart::new(req)))
+    }
+}
+
+impl<ReqB> FromRequest<ReqB> for RouteParams {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(Ok(req.params().clone())))
+    }
+}
+
+impl<ReqB> FromRequest<ReqB> for Method {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(Ok(req.method().clone())))
+    }
+}
+
+impl<ReqB> FromRequest<ReqB> for Uri {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(Ok(req.uri().clone())))
+    }
+}
+
+impl<ReqB> FromRequest<ReqB> for HeaderMap {
+    fn from_request(req: &mut Request<ReqB>) -> BoxFuture<'_, crate::Result<Self>> {
+        Box::pin(future::ready(Ok(req.headers().clone())))
+    }
+}
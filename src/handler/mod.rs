@@ -0,0 +1,155 @@
+//! Adapters for the route handlers and the middlewares.
+
+use crate::types::ErrHandlerFailure;
+use futures::future::{MapErr, TryFutureExt};
+use hyper::{header, Response, StatusCode};
+use std::future::Future;
+
+pub use self::extract::{extract, Data, FromRequest, Handler};
+#[cfg(feature = "serde")]
+pub use self::extract::{Form, Params, Query};
+
+mod extract;
+
+/// Adapts a route handler or a pre/post middleware handler which returns an error type `E2` convertible into the router's
+/// error type `E`.
+///
+/// The router accepts the handlers which return the exact error type `E` it was built with. This adapter converts the
+/// handler's error via `Into<E>`, so the individual modules can use their own error types without `map_err` in every
+/// handler. The converted error is still passed to the error handler as the router's `E` type.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, Middleware};
+/// use routerify::handler::err_into;
+/// use hyper::{Response, Request, Body};
+///
+/// #[derive(Debug)]
+/// struct AppError(String);
+///
+/// impl std::fmt::Display for AppError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "{}", self.0)
+///     }
+/// }
+///
+/// impl std::error::Error for AppError {}
+///
+/// impl From<std::num::ParseIntError> for AppError {
+///     fn from(err: std::num::ParseIntError) -> AppError {
+///         AppError(err.to_string())
+///     }
+/// }
+///
+/// impl From<std::fmt::Error> for AppError {
+///     fn from(err: std::fmt::Error) -> AppError {
+///         AppError(err.to_string())
+///     }
+/// }
+///
+/// async fn user_handler(req: Request<Body>) -> Result<Response<Body>, std::num::ParseIntError> {
+///     let id: u64 = req.uri().path().trim_start_matches("/users/").parse()?;
+///     Ok(Response::new(Body::from(format!("User {}", id))))
+/// }
+///
+/// async fn logger(req: Request<Body>) -> Result<Request<Body>, std::fmt::Error> {
+///     println!("{} {}", req.method(), req.uri().path());
+///     Ok(req)
+/// }
+///
+/// # fn run() -> Router<Body, AppError> {
+/// let router: Router<Body, AppError> = Router::builder()
+///     .middleware(Middleware::pre(err_into(logger)))
+///     .get("/users/:id", err_into(user_handler))
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn err_into<H, T, R, U, E2, E>(handler: H) -> impl Fn(T) -> MapErr<R, fn(E2) -> E> + Send + Sync + 'static
+where
+    H: Fn(T) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<U, E2>>,
+    E2: Into<E>,
+{
+    move |input| handler(input).map_err(Into::into as fn(E2) -> E)
+}
+
+/// The output of an error handler, it's either a response or a `Result` of a response for an error handler which can fail
+/// itself e.g. while rendering an error page template.
+///
+/// If the error handler returns an error, a plain `500 Internal Server Error` response is sent instead. The error is passed to
+/// the [`on_request_complete`](../struct.RouterBuilder.html#method.on_request_complete) callback as
+/// [`RequestMetrics::err_handler_error`](../struct.RequestMetrics.html#structfield.err_handler_error), and it's recorded as a
+/// tracing event with the `tracing` feature enabled. A panic in the error handler is handled the same way if the router
+/// [catches the panics](../struct.RouterBuilder.html#method.catch_panics).
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use hyper::{Response, Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// async fn render_error_page(status: StatusCode) -> Result<String, std::fmt::Error> {
+///     Ok(format!("<h1>{}</h1>", status))
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .err_handler(|_| async move {
+///         let page = render_error_page(StatusCode::INTERNAL_SERVER_ERROR).await?;
+///
+///         Ok::<_, std::fmt::Error>(
+///             Response::builder()
+///                 .status(StatusCode::INTERNAL_SERVER_ERROR)
+///                 .body(Body::from(page))
+///                 .unwrap(),
+///         )
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub trait ErrHandlerOutput<B> {
+    /// Converts the output into the response, it's the fallback `500` response if the error handler failed.
+    fn into_response(self) -> Response<B>;
+}
+
+impl<B> ErrHandlerOutput<B> for Response<B> {
+    fn into_response(self) -> Response<B> {
+        self
+    }
+}
+
+impl<B, E> ErrHandlerOutput<B> for Result<Response<B>, E>
+where
+    B: From<&'static str>,
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    fn into_response(self) -> Response<B> {
+        match self {
+            Ok(resp) => resp,
+            Err(err) => err_handler_failed(B::from("Internal Server Error"), err.into()),
+        }
+    }
+}
+
+// Generates the fallback `500` response of a failed error handler. The failure is kept in the response extensions, so the
+// router passes it to the metrics callback.
+pub(crate) fn err_handler_failed<B>(body: B, err: Box<dyn std::error::Error + Send + Sync + 'static>) -> Response<B> {
+    #[cfg(feature = "tracing")]
+    crate::trace::record_err_handler_error(&*err);
+
+    let mut resp = Response::new(body);
+    *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    resp.headers_mut()
+        .insert(header::CONTENT_TYPE, header::HeaderValue::from_static("text/plain"));
+    resp.extensions_mut().insert(ErrHandlerFailure(err));
+    resp
+}
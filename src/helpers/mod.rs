@@ -1,7 +1,12 @@
-use crate::types::RequestMeta;
+use crate::types::{PercentDecodeMode, RequestMeta};
 use crate::Error;
-use http::Extensions;
+use futures::FutureExt;
+use http::{header, Extensions, Response, StatusCode};
 use percent_encoding::percent_decode_str;
+use std::any::Any;
+use std::borrow::Cow;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
 
 pub(crate) fn update_req_meta_in_extensions(ext: &mut Extensions, new_req_meta: RequestMeta) {
     if let Some(existing_req_meta) = ext.get_mut::<RequestMeta>() {
@@ -11,32 +16,348 @@ pub(crate) fn update_req_meta_in_extensions(ext: &mut Extensions, new_req_meta:
     }
 }
 
-pub(crate) fn percent_decode_request_path(val: &str) -> crate::Result<String> {
+/// Collapses the consecutive slashes in a path into a single one.
+pub(crate) fn collapse_slashes(path: &str) -> Cow<'_, str> {
+    if !path.contains("//") {
+        return Cow::Borrowed(path);
+    }
+
+    let mut collapsed = String::with_capacity(path.len());
+    for ch in path.chars() {
+        if ch != '/' || !collapsed.ends_with('/') {
+            collapsed.push(ch);
+        }
+    }
+
+    Cow::Owned(collapsed)
+}
+
+/// Creates a redirection to the path with the query string. The response body type can't be constructed generically, so the
+/// redirection is only supported for `hyper::Body`.
+pub(crate) fn redirect_response<B: 'static>(
+    status: StatusCode,
+    path: &str,
+    query: Option<&str>,
+) -> Option<Response<B>> {
+    let location = match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_owned(),
+    };
+
+    let resp = Response::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(hyper::Body::empty())
+        .ok()?;
+
+    let resp: Box<dyn Any> = Box::new(resp);
+    resp.downcast::<Response<B>>().ok().map(|resp| *resp)
+}
+
+/// Normalizes the mount path of a scoped router, so it can be prepended to the inner paths which always start with a slash.
+/// The trivial mount paths e.g. `/` or an empty string become empty and the other ones get a leading slash and lose the
+/// trailing ones, e.g. `api/` becomes `/api`.
+pub(crate) fn normalize_scope_path(path: String) -> String {
+    let trimmed = path.trim_end_matches('/');
+
+    if trimmed.is_empty() {
+        String::new()
+    } else if trimmed.starts_with('/') {
+        trimmed.to_owned()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+/// Splits a path like `/users/john/` into its segments, the leading and the trailing slashes are required.
+pub(crate) fn path_segments(path: &str) -> Option<Vec<&str>> {
+    if path == "/" {
+        return Some(Vec::new());
+    }
+
+    if path.len() < 2 || !path.starts_with('/') || !path.ends_with('/') {
+        return None;
+    }
+
+    Some(path[1..path.len() - 1].split('/').collect())
+}
+
+pub(crate) fn percent_decode_request_path(val: &str, mode: PercentDecodeMode) -> crate::Result<String> {
+    match mode {
+        PercentDecodeMode::Full => percent_decode(val),
+        PercentDecodeMode::KeepEncodedSlashes => {
+            let mut decoded = String::with_capacity(val.len());
+            let mut rest = val;
+
+            while let Some(idx) = find_kept_sequence(rest) {
+                decoded.push_str(&percent_decode(&rest[..idx])?);
+                decoded.push_str(&rest[idx..idx + 3]);
+                rest = &rest[idx + 3..];
+            }
+
+            decoded.push_str(&percent_decode(rest)?);
+            Ok(decoded)
+        }
+        PercentDecodeMode::Off => Ok(val.to_owned()),
+    }
+}
+
+fn percent_decode(val: &str) -> crate::Result<String> {
     percent_decode_str(val)
         .decode_utf8()
         .map_err(Error::DecodeRequestPath)
         .map(|val| val.to_string())
 }
 
+// Finds the next `%2F` or `%25` sequence in any case.
+fn find_kept_sequence(val: &str) -> Option<usize> {
+    val.as_bytes().windows(3).position(|window| {
+        window[0] == b'%' && (window[1] == b'2') && (window[2].eq_ignore_ascii_case(&b'f') || window[2] == b'5')
+    })
+}
+
+/// The request path which a target path is generated from, it's used to find the route param values as they're sent.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawPath<'a> {
+    pub(crate) path: &'a str,
+    pub(crate) collapse_slashes: bool,
+    pub(crate) mode: PercentDecodeMode,
+}
+
+impl RawPath<'_> {
+    /// Maps each byte offset of the target path to the offset of its source in the request path, the appended trailing
+    /// slash and the end of the target path map to the end of the request path. It mirrors the target path generation.
+    pub(crate) fn offsets(&self) -> Vec<usize> {
+        let bytes = self.path.as_bytes();
+        let mut offsets = Vec::with_capacity(bytes.len() + 2);
+        let mut ends_with_slash = false;
+        let mut idx = 0;
+
+        while idx < bytes.len() {
+            if self.collapse_slashes && bytes[idx] == b'/' && idx > 0 && bytes[idx - 1] == b'/' {
+                idx += 1;
+                continue;
+            }
+
+            offsets.push(idx);
+
+            let encoded = bytes[idx] == b'%'
+                && idx + 2 < bytes.len()
+                && bytes[idx + 1].is_ascii_hexdigit()
+                && bytes[idx + 2].is_ascii_hexdigit();
+
+            let decoded = encoded
+                && match self.mode {
+                    PercentDecodeMode::Full => true,
+                    PercentDecodeMode::KeepEncodedSlashes => find_kept_sequence(&self.path[idx..idx + 3]).is_none(),
+                    PercentDecodeMode::Off => false,
+                };
+
+            if decoded {
+                ends_with_slash = bytes[idx + 1] == b'2' && bytes[idx + 2].eq_ignore_ascii_case(&b'f');
+                idx += 3;
+            } else {
+                ends_with_slash = bytes[idx] == b'/';
+                idx += 1;
+            }
+        }
+
+        if !ends_with_slash {
+            offsets.push(bytes.len());
+        }
+        offsets.push(bytes.len());
+
+        offsets
+    }
+
+    /// Returns the decoded value of a route param and its raw value if it's different, the param is captured at the
+    /// `start..end` range of the target path.
+    pub(crate) fn param_value(
+        &self,
+        offsets: &[usize],
+        value: &str,
+        start: usize,
+        end: usize,
+    ) -> crate::Result<(String, Option<String>)> {
+        let raw = match (offsets.get(start), offsets.get(end)) {
+            (Some(raw_start), Some(raw_end)) => self.path.get(*raw_start..*raw_end),
+            _ => None,
+        };
+
+        let decoded = match (self.mode, raw) {
+            // The encoded slashes are kept for the matching only, so the param value is decoded as sent.
+            (PercentDecodeMode::KeepEncodedSlashes, Some(raw)) => percent_decode(raw)?,
+            _ => value.to_owned(),
+        };
+
+        let raw = raw.filter(|raw| *raw != decoded).map(str::to_owned);
+        Ok((decoded, raw))
+    }
+}
+
+/// Runs the future and converts a panic raised while polling it into an [`Error::Panicked`](../enum.Error.html#variant.Panicked).
+pub(crate) async fn catch_panic<T, F>(fut: F) -> crate::Result<T>
+where
+    F: Future<Output = crate::Result<T>>,
+{
+    // The handlers are not required to be `UnwindSafe`, the router state is not reused in a broken way after a panic
+    // as the panicking handler's future is dropped right away.
+    AssertUnwindSafe(fut)
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| Err(Error::Panicked(panic_message(payload))))
+}
+
+fn panic_message(payload: Box<dyn Any + Send + 'static>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Unknown panic payload".to_owned()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_percent_decode_request_path() {
+        let full = PercentDecodeMode::Full;
+
         let val = "/Alice%20John/do something";
         assert_eq!(
-            percent_decode_request_path(val).unwrap(),
+            percent_decode_request_path(val, full).unwrap(),
             "/Alice John/do something".to_owned()
         );
 
         let val = "Alice%20John";
-        assert_eq!(percent_decode_request_path(val).unwrap(), "Alice John".to_owned());
+        assert_eq!(percent_decode_request_path(val, full).unwrap(), "Alice John".to_owned());
 
         let val = "Go<>crazy";
-        assert_eq!(percent_decode_request_path(val).unwrap(), "Go<>crazy".to_owned());
+        assert_eq!(percent_decode_request_path(val, full).unwrap(), "Go<>crazy".to_owned());
 
         let val = "go%crazy";
-        assert_eq!(percent_decode_request_path(val).unwrap(), "go%crazy".to_owned());
+        assert_eq!(percent_decode_request_path(val, full).unwrap(), "go%crazy".to_owned());
+
+        let val = "/files/100%";
+        assert_eq!(
+            percent_decode_request_path(val, full).unwrap(),
+            "/files/100%".to_owned()
+        );
+
+        let val = "/files/%FF";
+        assert!(matches!(
+            percent_decode_request_path(val, full),
+            Err(Error::DecodeRequestPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_percent_decode_request_path_modes() {
+        let val = "/files/a%2Fb%2fc%20d%25";
+        assert_eq!(
+            percent_decode_request_path(val, PercentDecodeMode::Full).unwrap(),
+            "/files/a/b/c d%"
+        );
+        assert_eq!(
+            percent_decode_request_path(val, PercentDecodeMode::KeepEncodedSlashes).unwrap(),
+            "/files/a%2Fb%2fc d%25"
+        );
+        assert_eq!(percent_decode_request_path(val, PercentDecodeMode::Off).unwrap(), val);
+
+        let val = "/files/%252F";
+        assert_eq!(
+            percent_decode_request_path(val, PercentDecodeMode::KeepEncodedSlashes).unwrap(),
+            "/files/%252F"
+        );
+
+        let val = "/files/%2F%FF";
+        assert!(percent_decode_request_path(val, PercentDecodeMode::KeepEncodedSlashes).is_err());
+        assert_eq!(percent_decode_request_path(val, PercentDecodeMode::Off).unwrap(), val);
+    }
+
+    #[test]
+    fn test_raw_path_param_value() {
+        fn param(path: &str, collapse_slashes: bool, mode: PercentDecodeMode, value: &str) -> (String, Option<String>) {
+            let raw_path = RawPath {
+                path,
+                collapse_slashes,
+                mode,
+            };
+            let target_path = percent_decode_request_path(&collapse_slashes_if(path, collapse_slashes), mode).unwrap();
+            let start = target_path.find(value).unwrap();
+            raw_path
+                .param_value(&raw_path.offsets(), value, start, start + value.len())
+                .unwrap()
+        }
+
+        fn collapse_slashes_if(path: &str, collapse: bool) -> Cow<'_, str> {
+            if collapse {
+                collapse_slashes(path)
+            } else {
+                Cow::Borrowed(path)
+            }
+        }
+
+        let full = PercentDecodeMode::Full;
+        assert_eq!(
+            param("/files/a%20b", false, full, "a b"),
+            ("a b".to_owned(), Some("a%20b".to_owned()))
+        );
+        assert_eq!(param("/files/plain", false, full, "plain"), ("plain".to_owned(), None));
+        assert_eq!(
+            param("//files//%41%2Fb", true, full, "A/b"),
+            ("A/b".to_owned(), Some("%41%2Fb".to_owned()))
+        );
+
+        let keep = PercentDecodeMode::KeepEncodedSlashes;
+        assert_eq!(
+            param("/files/a%2Fb%20c", false, keep, "a%2Fb c"),
+            ("a/b c".to_owned(), Some("a%2Fb%20c".to_owned()))
+        );
+        assert_eq!(
+            param("/files/%2541", false, keep, "%2541"),
+            ("%41".to_owned(), Some("%2541".to_owned()))
+        );
+
+        let off = PercentDecodeMode::Off;
+        assert_eq!(param("/files/a%20b", false, off, "a%20b"), ("a%20b".to_owned(), None));
+    }
+
+    #[test]
+    fn test_collapse_slashes() {
+        assert_eq!(collapse_slashes("/users/3"), "/users/3");
+        assert_eq!(collapse_slashes("//users///3//"), "/users/3/");
+        assert_eq!(collapse_slashes("////"), "/");
+        assert_eq!(collapse_slashes("/files/a%2F%2Fb//c"), "/files/a%2F%2Fb/c");
+    }
+
+    #[test]
+    fn test_path_segments() {
+        assert_eq!(path_segments("/"), Some(vec![]));
+        assert_eq!(path_segments("//"), Some(vec![""]));
+        assert_eq!(path_segments("/users/john/"), Some(vec!["users", "john"]));
+        assert_eq!(path_segments("/users/john"), None);
+        assert_eq!(path_segments("*/"), None);
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let res = catch_panic(async { crate::Result::Ok(1) }).await;
+        assert_eq!(res.unwrap(), 1);
+
+        let res: crate::Result<()> = catch_panic(async { panic!("Something went wrong") }).await;
+        match res {
+            Err(Error::Panicked(msg)) => assert_eq!(msg, "Something went wrong"),
+            _ => panic!("The panic is not caught"),
+        }
+
+        let res: crate::Result<()> = catch_panic(async { panic!("Code: {}", 10) }).await;
+        match res {
+            Err(Error::Panicked(msg)) => assert_eq!(msg, "Code: 10"),
+            _ => panic!("The panic is not caught"),
+        }
     }
 }
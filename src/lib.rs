@@ -10,14 +10,26 @@
 //!
 //! - 🔨 Provides middleware support.
 //!
-//! - 🌀 Supports Route Parameters.
+//! - 🌀 Supports Route Parameters, which can be deserialized into a struct (feature `serde`).
 //!
 //! - 🚀 Fast as it's using [`RegexSet`](https://docs.rs/regex/1.3.7/regex/struct.RegexSet.html) to match routes.
 //!
+//! - 📎 Parses the [multipart](./multipart/index.html) request bodies as streams e.g. the file uploads (feature `multipart`).
+//!
+//! - 🍪 Parses the [request cookies](./ext/trait.RequestExt.html#tymethod.cookie) and builds the [`Set-Cookie`](./struct.SetCookie.html) headers, which can be [signed or encrypted](./struct.CookieKeys.html) (feature `cookies`).
+//!
+//! - 🔑 Provides server-side [sessions](./session/index.html) with pluggable stores (feature `cookies`).
+//!
 //! - 🍺 It supports any response body type as long as it implements the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait.
 //!
+//! - 🗼 Can be used as a `tower::Service` via [`RouterTowerService`](./struct.RouterTowerService.html) (feature `tower`).
+//!
+//! - 🧪 Provides an in-process [test client](./test/index.html) to test the routers without any TCP listener (feature `test-util`).
+//!
 //! - ❗ Provides a flexible [error handling](./index.html#error-handling) strategy.
 //!
+//! - 🔭 Instruments the requests with the [`tracing`](https://docs.rs/tracing) spans (feature `tracing`).
+//!
 //! - 💁 Provides `WebSocket` [support](https://github.com/routerify/routerify-websocket) out of the box.
 //!
 //! - 🔥 Allows data/state sharing across the route and middleware handlers.
@@ -169,6 +181,11 @@
 //! # run();
 //! ```
 //!
+//! The [`response`](./response/index.html) module provides helpers to create the common text, JSON, redirect and empty responses.
+//!
+//! A handler can also take the values it needs e.g. the route params, the shared data or the JSON body as its arguments, if it's
+//! wrapped with [`handler::extract`](./handler/fn.extract.html).
+//!
 //! ### Route Paths
 //!
 //! Route paths, in combination with a request method, define the endpoints at which requests can be made.
@@ -211,6 +228,9 @@
 //! # run();
 //! ```
 //!
+//! The rest of the path matched by the `*` is available as the `"*"` route parameter, or it can be named e.g.
+//! `/static/*filepath`, then it's available as the `"filepath"` parameter without the leading and the trailing slashes.
+//!
 //! #### Handle 404 Pages
 //!
 //! Here is an example to handle 404 pages.
@@ -239,6 +259,13 @@
 //! # run();
 //! ```
 //!
+//! The [`not_found`](./struct.RouterBuilder.html#method.not_found) method adds such a fallback regardless of the registration
+//! order, and a scoped router can have its own one for the paths under its scope.
+//!
+//! If a route matches the request path but not the method e.g. `POST /users` above, the request is responded with
+//! `405 Method Not Allowed` and an `Allow` header instead, unless it's disabled by the
+//! [`method_not_allowed`](./struct.RouterBuilder.html#method.method_not_allowed) method.
+//!
 //! ### Route Parameters
 //!
 //! Route parameters are named URL segments that are used to capture the values specified at their position in the URL.
@@ -274,6 +301,23 @@
 //! # run();
 //! ```
 //!
+//! A parameter can be constrained by a regex in the parentheses after its name e.g. `/users/:id(\\d+)`, then the route
+//! only matches if the value satisfies it, so `/users/new` can be routed to another route regardless of the order. The
+//! capture groups in the regex are made non-capturing.
+//!
+//! A parameter segment can be optional by a trailing `?` e.g. `/articles/:lang?/:slug`, which matches both `/articles/foo`
+//! and `/articles/en/foo`. A missing optional parameter is absent from [`req.params()`](./struct.RouteParams.html).
+//!
+//! A parameter name can be used more than once e.g. `/compare/:ver/:ver`, then `req.param` returns the first value and
+//! [`req.params().get_all`](./struct.RouteParams.html#method.get_all) returns all of them in the order of the path.
+//!
+//! With the `serde` feature, the route parameters can also be deserialized into a struct by the
+//! [`params_as`](./ext/trait.RequestExt.html#tymethod.params_as) method.
+//!
+//! A route added by [`add_named`](./struct.RouterBuilder.html#method.add_named) or the other `*_named` methods can be referred
+//! by its name, and [`Router::url_for`](./struct.Router.html#method.url_for) generates its URL from the params instead of
+//! hard-coding the path.
+//!
 //! ### Scoping/Mounting Router
 //!
 //! The `routerify::Router` is a modular, lightweight and mountable router component. A router can be scoped in or mount to a
@@ -309,6 +353,21 @@
 //! ```
 //! Now, the app can handle requests to `/api/books` as well as to `/api/books/:bookId`.
 //!
+//! A mounted router is not consulted at request time. Its routes and middlewares are moved into the parent router with the
+//! mount path prepended when `.scope()` is called, so the whole tree of routers is matched at once regardless of the nesting depth.
+//! The mounted middlewares are spliced at the position of the `.scope()` call, so they run in this order:
+//!
+//! - The parent's pre middlewares added before the `.scope()` call, then the mounted router's pre middlewares, then the parent's
+//!   pre middlewares added after the `.scope()` call.
+//! - The post middlewares and the around middlewares follow the same rule.
+//!
+//! The error handler of a mounted router handles the errors of its route handlers and middlewares, the other errors are still
+//! handled by the root router's error handler. The other root-only options of a mounted router are ignored.
+//!
+//! A hyper service which isn't built with Routerify, e.g. a metrics exporter, can be mounted at a path prefix by the
+//! [`mount_service`](./struct.RouterBuilder.html#method.mount_service) method, it handles every request under the prefix and it's
+//! wrapped by the middlewares as a route handler.
+//!
 //! ## Middleware
 //!
 //! The `Routerify` also supports Middleware functionality. If you are unfamiliar with Middleware, in short, here a middlewar is a function (or could be a closure
@@ -320,7 +379,7 @@
 //! - Execute any code.
 //! - Transform the request and the response object.
 //!
-//! Here, the `Routerify` categorizes the middlewares into two different types:
+//! Here, the `Routerify` categorizes the middlewares into three different types:
 //!
 //! ### Pre Middleware
 //!
@@ -382,6 +441,40 @@
 //! # run();
 //! ```
 //!
+//! A pre middleware created by [`Middleware::pre_with_response`](./enum.Middleware.html#method.pre_with_response) can respond
+//! to the request by itself with [`PreOutcome::Respond`](./enum.PreOutcome.html#variant.Respond) e.g. to reject it, then the
+//! remaining pre middlewares and the route handler are skipped and the response goes straight to the post middlewares.
+//!
+//! #### Rewriting the Request Path
+//!
+//! A pre middleware can rewrite the request path by replacing the request URI. The remaining pre middlewares, the routes and the
+//! post middlewares are then matched against the new path, while the pre middlewares which are registered before the rewriting one
+//! are not run again. A request path can be rewritten at most 4 times, further rewrites fail with the
+//! [`Error::TooManyRewrites`](./enum.Error.html#variant.TooManyRewrites) error.
+//!
+//! ```
+//! use routerify::{Router, Middleware};
+//! use hyper::{Request, Response, Body, Uri};
+//! use std::convert::Infallible;
+//!
+//! async fn rewrite_middleware_handler(mut req: Request<Body>) -> Result<Request<Body>, Infallible> {
+//!     if req.uri().path() == "/old" {
+//!         *req.uri_mut() = Uri::from_static("/new");
+//!     }
+//!     Ok(req)
+//! }
+//!
+//! # fn run() -> Router<Body, Infallible> {
+//! let router = Router::builder()
+//!      .middleware(Middleware::pre(rewrite_middleware_handler))
+//!      .get("/new", |_| async move { Ok(Response::new(Body::from("The new page"))) })
+//!      .build()
+//!      .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! ### Post Middleware
 //!
 //! The post Middlewares will be executed after all the route handlers process the request and generates a response and it will access that response object and the request info(optional)
@@ -479,11 +572,73 @@
 //! # run();
 //! ```
 //!
+//! ### Around Middleware
+//!
+//! The around Middlewares wrap the rest of the request processing, so they can run code both before and after it e.g. to measure
+//! the time or to keep a tracing span open, without passing any state from a pre middleware to a post middleware. The handler receives
+//! the `req` object and a [`Next`](./struct.Next.html), it runs the rest by calling `next.run(req)` and it can also change or
+//! replace the returned response. It may also not run the `Next` at all and respond by itself.
+//!
+//! The around middlewares are the outermost ones, they are run in the registration order before all the pre middlewares, and
+//! the response returned by `next.run(req)` has already gone through the post middlewares. An error raised after `next.run(req)`
+//! is called is passed to the error handler before it reaches the around middleware. An error returned by an around middleware
+//! itself is passed to the error handler and its response is sent as is.
+//!
+//! ```
+//! use routerify::{Router, Middleware, Next};
+//! use hyper::{Request, Body, Response};
+//! use std::time::Instant;
+//!
+//! async fn timing_middleware_handler(req: Request<Body>, next: Next<Body>) -> Result<Response<Body>, routerify::Error> {
+//!     let start = Instant::now();
+//!     let mut res = next.run(req).await?;
+//!
+//!     let elapsed = format!("{}", start.elapsed().as_micros());
+//!     res.headers_mut().insert("x-response-time-us", elapsed.parse().unwrap());
+//!     Ok(res)
+//! }
+//!
+//! # fn run() -> Router<Body, routerify::Error> {
+//! let router = Router::builder()
+//!      .middleware(Middleware::around(timing_middleware_handler))
+//!      .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+//!      .build()
+//!      .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! ### The built-in Middlewars
 //!
+//! The [`middleware`](./middleware/index.html) module provides some ready-made middlewares, some of them are gated behind cargo features:
+//!
+//! - [`compression`](./middleware/fn.compression.html) (feature `compression`): A post middleware which compresses the response bodies
+//!   based on the `Accept-Encoding` request header.
+//! - [`circuit_breaker`](./middleware/fn.circuit_breaker.html): An around middleware which responds with
+//!   `503 Service Unavailable` without running the route handler once too many requests fail, and probes before it recovers.
+//! - [`concurrency_limit`](./middleware/fn.concurrency_limit.html): An around middleware which limits the number of the
+//!   requests processed at the same time and responds with `503 Service Unavailable` once the limit is reached.
+//! - [`conditional_get`](./middleware/fn.conditional_get.html): A post middleware which adds the `ETag` headers and responds with
+//!   `304 Not Modified` if the request's `If-None-Match` or `If-Modified-Since` header matches.
+//! - [`auth::basic`](./middleware/auth/fn.basic.html) and [`auth::bearer`](./middleware/auth/fn.bearer.html): Around middlewares
+//!   which verify the `Authorization` header and store the authenticated user in the request context or reject the request with
+//!   `401 Unauthorized`.
+//! - [`auth::jwt`](./middleware/auth/fn.jwt.html) (feature `jwt`): An around middleware which verifies the `HS256` or `RS256`
+//!   signed JSON Web Tokens and stores the claims in the request context.
+//! - [`Cors`](./cors/struct.Cors.html): A pair of a pre middleware and a post middleware which respond to the `CORS` preflight
+//!   requests and add the `Access-Control-Allow-*` headers to the responses.
+//! - [`csrf`](./middleware/fn.csrf.html) (feature `cookies`): An around middleware which issues a token per [session](./session/index.html) and
+//!   rejects the state-changing requests without a matching token in a header or a form field with `403 Forbidden`.
+//! - [`ip_filter`](./middleware/fn.ip_filter.html): A pre middleware which admits or rejects the requests by the client address
+//!   against the allowed and the denied [IP ranges](./struct.IpRange.html) and responds with `403 Forbidden` otherwise.
+//! - [`method_override`](./middleware/fn.method_override.html): A pre middleware which replaces the method of a `POST` request
+//!   with the one in the `X-HTTP-Method-Override` header or in a query param.
+//! - [`rate_limit`](./middleware/fn.rate_limit.html): An around middleware which limits the request rate of every client by a token
+//!   bucket and responds with `429 Too Many Requests` once the limit is exceeded.
+//!
 //! Here is a list of some middlewares which are published in different crates:
 //!
-//! - [routerify-cors](https://github.com/routerify/routerify-cors): A post middleware which enables `CORS` to the routes.
 //! - [routerify-query](https://github.com/routerify/routerify-query): A pre middleware which parses the request query string.
 //!
 //! ## Data and State Sharing
@@ -695,6 +850,59 @@
 //! }
 //! ```
 //!
+//! The data of a scoped router shadows the data of the same type shared by the outer routers for the requests routed into that
+//! scope, e.g. each tenant router can carry its own config. The data of the other types is still looked up in the outer routers.
+//!
+//! The state of a single route can also be passed to its handler as the second argument by the
+//! [`get_with_state`](./struct.RouterBuilder.html#method.get_with_state) and the similar methods, so a plain
+//! `async fn(req, state)` function can be registered without reading the state from the request.
+//!
+//! ### Request Context
+//!
+//! The data shared by the [`data`](./struct.RouterBuilder.html#method.data) method is app state, it's set once while building
+//! the router and it's shared by all the requests. To pass a value which belongs to a single request e.g. the user authenticated
+//! by a pre middleware, set it on the request with the [`set_context`](./ext/trait.RequestExt.html#tymethod.set_context) method
+//! and read it with the [`context`](./ext/trait.RequestExt.html#tymethod.context) method. A request holds a single value per type.
+//!
+//! The values are kept separately from the request extensions, so they don't collide with the extensions added by other code.
+//! The post middlewares and the error handler with request info can read them, including the values set in the route handler, by
+//! the [`RequestInfo::context`](./struct.RequestInfo.html#method.context) method.
+//!
+//! ```
+//! use routerify::prelude::*;
+//! use routerify::{Middleware, Router};
+//! use hyper::{Body, Request, Response};
+//! use std::convert::Infallible;
+//!
+//! struct CurrentUser(String);
+//!
+//! async fn auth_middleware(mut req: Request<Body>) -> Result<Request<Body>, Infallible> {
+//!     if let Some(user) = req.headers().get("x-user").and_then(|val| val.to_str().ok()) {
+//!         let user = CurrentUser(user.to_owned());
+//!         req.set_context(user);
+//!     }
+//!     Ok(req)
+//! }
+//!
+//! async fn home_handler(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+//!     let name = match req.context::<CurrentUser>() {
+//!         Some(user) => user.0.as_str(),
+//!         None => "guest",
+//!     };
+//!     Ok(Response::new(Body::from(format!("Hello {}", name))))
+//! }
+//!
+//! # fn run() -> Router<Body, Infallible> {
+//! let router = Router::builder()
+//!     .middleware(Middleware::pre(auth_middleware))
+//!     .get("/", home_handler)
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+//!
 //! ## Error Handling
 //!
 //! Any route or middleware could go wrong and throws an error. The `Routerify` tries to add a default error handler in some cases. But, it also
@@ -728,6 +936,20 @@
 //! # run();
 //! ```
 //!
+//! If a pre middleware fails, the remaining pre middlewares and the route handler are skipped and the error handler's response
+//! is passed through the post middlewares. If a post middleware fails, the error handler's response is passed to the remaining
+//! post middlewares. So, the post middlewares e.g. a logger or a CORS middleware still see the error responses.
+//!
+//! The route handlers and the middlewares must return the router's error type. A handler which returns its own error type
+//! convertible into the router's one can be wrapped with [`handler::err_into`](./handler/fn.err_into.html).
+//!
+//! The errors of a specific type can also be converted into responses by the mappers added with the
+//! [`map_err`](./struct.RouterBuilder.html#method.map_err) method, they're tried before the error handler.
+//!
+//! The error handler can fail itself e.g. while rendering an error page, by returning a `Result` of a response instead of a
+//! response. A plain `500 Internal Server Error` response is sent if it returns an error, please refer to
+//! [`handler::ErrHandlerOutput`](./handler/trait.ErrHandlerOutput.html) for more info.
+//!
 //! ### Error Handling with Request Info
 //!
 //! Sometimes, it's needed to to generate response on error based on the request headers, method, uri etc. The `Routerify` also provides a method [`err_handler_with_info`](./struct.RouterBuilder.html#method.err_handler_with_info)
@@ -759,28 +981,94 @@
 //! # }
 //! # run();
 //! ```
+//!
+//! ## Tracing
+//!
+//! With the `tracing` feature enabled, every request is processed inside a `request` span with the `http.method` and `http.target`
+//! fields. The template of the matched route e.g. `/users/:id` is recorded as `http.route` and the response status as `http.status_code`.
+//! The pre middlewares, the around middlewares, the route handler and the post middlewares run inside the `pre_middleware`,
+//! `around_middleware`, `route` and `post_middleware` child spans respectively, with the `path` they're registered on. Any error
+//! passed to the error handler is recorded as an event with its source chain.
+//!
+//! The request span is at the `INFO` level and the child spans are at the `DEBUG` level. Nothing is recorded if no subscriber is
+//! installed, e.g. with the [`tracing-subscriber`](https://docs.rs/tracing-subscriber) crate:
+//!
+//! ```ignore
+//! use routerify::{Middleware, Router, RouterService};
+//! use hyper::{Body, Response, Server};
+//! use std::convert::Infallible;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
+//!
+//!     let router: Router<Body, Infallible> = Router::builder()
+//!         .middleware(Middleware::pre(|req| async move { Ok(req) }))
+//!         .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+//!         .build()
+//!         .unwrap();
+//!
+//!     let service = RouterService::new(router).unwrap();
+//!     Server::bind(&([127, 0, 0, 1], 3001).into()).serve(service).await.unwrap();
+//! }
+//! ```
+//!
+//! A `GET /users/1` request produces the following span tree:
+//!
+//! ```text
+//! request{http.method=GET http.target=/users/1 http.route="/users/:id" http.status_code=200}
+//! ├── pre_middleware{path="/*"}
+//! └── route{path="/users/:id"}
+//! ```
 
-pub use self::error::Error;
-pub use self::middleware::{Middleware, PostMiddleware, PreMiddleware};
-pub use self::route::Route;
-pub use self::router::{Router, RouterBuilder};
+pub use self::error::{BuildError, Error, ErrorKind};
+pub use self::middleware::{AroundMiddleware, Middleware, Next, PostMiddleware, PreMiddleware, PreOutcome};
+pub use self::response::IntoResponse;
+#[cfg(feature = "serde")]
+pub use self::response::Json;
+pub use self::route::{HeaderMatcher, MountServiceOptions, QueryMatcher, Route};
+pub use self::router::{DynamicRoutes, RouteHandle, RouteMatcher, Router, RouterBuilder};
+pub use self::service::Connection;
+pub use self::service::MapResponseBody;
 #[doc(hidden)]
 pub use self::service::RequestService;
 pub use self::service::RequestServiceBuilder;
 pub use self::service::RouterService;
-pub use self::types::{RequestInfo, RouteParams};
+#[cfg(feature = "tower")]
+pub use self::service::RouterTowerService;
+pub use self::service::{ShutdownHandle, ShutdownSignal};
+pub use self::types::{
+    Accept, IpRange, MediaRange, PeerAddr, PercentDecodeMode, QueryParams, RequestInfo, RequestMetrics, RouteParams,
+    SlashNormalization, TrailingSlash,
+};
+#[cfg(feature = "cookies")]
+pub use self::types::{CookieKeys, Cookies, SameSite, SetCookie};
 
+pub mod body;
 mod constants;
+pub mod cors;
 mod data_map;
 mod error;
 pub mod ext;
+pub mod handler;
 mod helpers;
-mod middleware;
+pub mod middleware;
+#[cfg(feature = "multipart")]
+pub mod multipart;
 pub mod prelude;
 mod regex_generator;
+pub mod response;
 mod route;
 mod router;
 mod service;
+#[cfg(feature = "cookies")]
+pub mod session;
+#[cfg(feature = "sse")]
+pub mod sse;
+#[cfg(feature = "test-util")]
+pub mod test;
+#[cfg(feature = "tracing")]
+mod trace;
 mod types;
 
 /// A Result type often returned from methods that can have routerify errors.
@@ -0,0 +1,142 @@
+use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
+use crate::Error;
+use futures::channel::oneshot;
+use hyper::{body::HttpBody, Request, Response};
+use regex::Regex;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+type Handler<B, E, ReqB> = Box<dyn Fn(Request<ReqB>, Next<B, ReqB>) -> HandlerReturn<B, E> + Send + Sync + 'static>;
+type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+
+type Downstream<B, ReqB> = (Request<ReqB>, oneshot::Sender<crate::Result<Response<B>>>);
+
+/// The around middleware type. Refer to [Around Middleware](./index.html#around-middleware) for more info.
+///
+/// An around middleware wraps the processing of the rest of the request, it receives the request and a [`Next`](./struct.Next.html)
+/// which runs the remaining pre middlewares, the route handler and the post middlewares and returns the response.
+///
+/// This `AroundMiddleware<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
+///
+/// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
+///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
+///   type.
+/// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
+pub struct AroundMiddleware<B, E, ReqB = hyper::Body> {
+    pub(crate) path: String,
+    pub(crate) regex: Regex,
+    // Make it an option so that when a router is used to scope in another router,
+    // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
+    pub(crate) handler: Option<Handler<B, E, ReqB>>,
+    // It's inherited from the scoped router, the errors of the other middlewares are handled by the root router's error handler.
+    pub(crate) err_handler: Option<Arc<ErrHandler<B>>>,
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > AroundMiddleware<B, E, ReqB>
+{
+    pub(crate) fn new_with_boxed_handler<P: Into<String>>(
+        path: P,
+        handler: Handler<B, E, ReqB>,
+    ) -> crate::Result<AroundMiddleware<B, E, ReqB>> {
+        let path = path.into();
+        let (re, _) = generate_exact_match_regex(path.as_str())?;
+
+        Ok(AroundMiddleware {
+            path,
+            regex: re,
+            handler: Some(handler),
+            err_handler: None,
+        })
+    }
+
+    /// Creates an around middleware with a handler at the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, AroundMiddleware};
+    /// use hyper::Body;
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::Around(AroundMiddleware::new("/abc", |req, next| async move { next.run(req).await }).unwrap()))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<AroundMiddleware<B, E, ReqB>>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>, Next<B, ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        let handler: Handler<B, E, ReqB> =
+            Box::new(move |req: Request<ReqB>, next: Next<B, ReqB>| Box::new(handler(req, next)));
+        AroundMiddleware::new_with_boxed_handler(path, handler)
+    }
+
+    /// Runs the handler and returns its future together with a receiver of the request which is passed to `Next::run`.
+    pub(crate) fn process(
+        &self,
+        req: Request<ReqB>,
+    ) -> (
+        impl Future<Output = crate::Result<Response<B>>> + Send + 'static,
+        oneshot::Receiver<Downstream<B, ReqB>>,
+    ) {
+        let handler = self
+            .handler
+            .as_ref()
+            .expect("A router can not be used after mounting into another router");
+
+        let (req_tx, req_rx) = oneshot::channel();
+        let fut = Pin::from(handler(req, Next { req_tx }));
+
+        let fut = async move { fut.await.map_err(|e| Error::HandleAroundMiddlewareRequest(e.into())) };
+        (fut, req_rx)
+    }
+}
+
+impl<B, E, ReqB> Debug for AroundMiddleware<B, E, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
+    }
+}
+
+/// Runs the rest of the request processing from an [around middleware](./struct.AroundMiddleware.html).
+pub struct Next<B, ReqB = hyper::Body> {
+    req_tx: oneshot::Sender<Downstream<B, ReqB>>,
+}
+
+impl<B, ReqB> Next<B, ReqB> {
+    /// Runs the remaining around middlewares, the pre middlewares, the route handler and the post middlewares with the
+    /// specified request and returns the response.
+    ///
+    /// The errors raised by them are already passed to the error handler, so an error is returned only if there is no error
+    /// handler, or if the `Next` is run after the around middleware returned e.g. from a spawned task.
+    pub async fn run(self, req: Request<ReqB>) -> crate::Result<Response<B>> {
+        let (res_tx, res_rx) = oneshot::channel();
+
+        self.req_tx
+            .send((req, res_tx))
+            .map_err(|_| Error::AroundMiddlewareNextUnavailable)?;
+
+        res_rx.await.map_err(|_| Error::AroundMiddlewareNextUnavailable)?
+    }
+}
+
+impl<B, ReqB> Debug for Next<B, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ is_canceled: {:?} }}", self.req_tx.is_canceled())
+    }
+}
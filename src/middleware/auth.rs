@@ -0,0 +1,304 @@
+//! The middlewares which authenticate the requests by the `Authorization` header.
+//!
+//! The credentials are passed to an async verifier which returns the authenticated user or the claims of the token, the value
+//! is stored in the request context, so the handlers can read it by the
+//! [`context`](../../ext/trait.RequestExt.html#tymethod.context) method. The request is rejected without running the rest of
+//! the middlewares and the route handler with:
+//!
+//! - `401 Unauthorized` and a `WWW-Authenticate` challenge if the header is missing, if it has a different scheme or if the
+//!   verifier returns an error.
+//! - `400 Bad Request` if the credentials are malformed e.g. an invalid base64 value.
+//!
+//! They are [around middlewares](../struct.AroundMiddleware.html), so they run before the pre middlewares.
+//!
+//! With the `jwt` feature, the [jwt](./fn.jwt.html) middleware verifies the JSON Web Tokens by itself and rejects the invalid
+//! ones with `401 Unauthorized`.
+
+use crate::ext::RequestExt;
+use crate::middleware::{Middleware, Next};
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use std::future::Future;
+use std::sync::Arc;
+
+#[cfg(feature = "jwt")]
+pub use self::jwt::{jwt, JwtError, JwtKey, JwtOptions};
+
+#[cfg(feature = "jwt")]
+mod jwt;
+
+/// The credentials of the `Basic` authentication scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BasicCredentials {
+    /// The user name.
+    pub username: String,
+
+    /// The password.
+    pub password: String,
+}
+
+/// Creates an around middleware which authenticates the requests matching the path by the `Basic` scheme.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use routerify::middleware::auth;
+/// use hyper::{Response, Body};
+/// use std::convert::Infallible;
+///
+/// struct User(String);
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(
+///         auth::basic("/admin/*", |credentials: auth::BasicCredentials| async move {
+///             if credentials.username == "admin" && credentials.password == "secret" {
+///                 Ok(User(credentials.username))
+///             } else {
+///                 Err("Invalid credentials")
+///             }
+///         })
+///         .unwrap(),
+///     )
+///     .get("/admin/dashboard", |req| async move {
+///         let user = req.context::<User>().unwrap();
+///         Ok(Response::new(Body::from(format!("Hello {}", user.0))))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn basic<P, E, ReqB, V, R, C, VE>(path: P, verifier: V) -> crate::Result<Middleware<Body, E, ReqB>>
+where
+    P: Into<String>,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+    V: Fn(BasicCredentials) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<C, VE>> + Send + 'static,
+    C: Send + Sync + 'static,
+{
+    let verifier = Arc::new(verifier);
+
+    Middleware::around_with_path(path, move |req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let verifier = verifier.clone();
+
+        async move {
+            let challenge = "Basic realm=\"Restricted\", charset=\"UTF-8\"";
+
+            let credentials = match credentials(&req, "Basic") {
+                Credentials::Found(credentials) => credentials,
+                Credentials::Missing => return Ok(reject(StatusCode::UNAUTHORIZED, challenge)),
+                Credentials::Malformed => return Ok(reject(StatusCode::BAD_REQUEST, challenge)),
+            };
+
+            let credentials = match parse_basic_credentials(credentials) {
+                Some(credentials) => credentials,
+                None => return Ok(reject(StatusCode::BAD_REQUEST, challenge)),
+            };
+
+            authenticate(req, next, verifier(credentials), challenge).await
+        }
+    })
+}
+
+/// Creates an around middleware which authenticates the requests matching the path by the `Bearer` scheme, the verifier
+/// receives the token.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::ext::RequestExt;
+/// use routerify::middleware::auth;
+/// use hyper::{Response, Body};
+/// use std::convert::Infallible;
+///
+/// struct Claims {
+///     subject: String,
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(
+///         auth::bearer("/api/*", |token: String| async move {
+///             match token.as_str() {
+///                 "token-of-alice" => Ok(Claims { subject: "alice".to_owned() }),
+///                 _ => Err("Unknown token"),
+///             }
+///         })
+///         .unwrap(),
+///     )
+///     .get("/api/me", |req| async move {
+///         let claims = req.context::<Claims>().unwrap();
+///         Ok(Response::new(Body::from(claims.subject.clone())))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn bearer<P, E, ReqB, V, R, C, VE>(path: P, verifier: V) -> crate::Result<Middleware<Body, E, ReqB>>
+where
+    P: Into<String>,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+    V: Fn(String) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<C, VE>> + Send + 'static,
+    C: Send + Sync + 'static,
+{
+    let verifier = Arc::new(verifier);
+
+    Middleware::around_with_path(path, move |req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let verifier = verifier.clone();
+
+        async move {
+            let token = match credentials(&req, "Bearer") {
+                Credentials::Found(token) if !token.is_empty() => token.to_owned(),
+                Credentials::Missing => return Ok(reject(StatusCode::UNAUTHORIZED, "Bearer")),
+                _ => return Ok(reject(StatusCode::BAD_REQUEST, "Bearer error=\"invalid_request\"")),
+            };
+
+            authenticate(req, next, verifier(token), "Bearer error=\"invalid_token\"").await
+        }
+    })
+}
+
+async fn authenticate<E, ReqB, R, C, VE>(
+    mut req: Request<ReqB>,
+    next: Next<Body, ReqB>,
+    verification: R,
+    challenge: &'static str,
+) -> Result<Response<Body>, E>
+where
+    R: Future<Output = Result<C, VE>>,
+    C: Send + Sync + 'static,
+{
+    let claims = match verification.await {
+        Ok(claims) => claims,
+        Err(_) => return Ok(reject(StatusCode::UNAUTHORIZED, challenge)),
+    };
+
+    req.set_context(claims);
+
+    // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+    Ok(next
+        .run(req)
+        .await
+        .unwrap_or_else(|_| reject(StatusCode::INTERNAL_SERVER_ERROR, "")))
+}
+
+enum Credentials<'a> {
+    Found(&'a str),
+    // The header is missing or it has a different scheme.
+    Missing,
+    Malformed,
+}
+
+fn credentials<'a, ReqB>(req: &'a Request<ReqB>, scheme: &str) -> Credentials<'a> {
+    let val = match req.headers().get(header::AUTHORIZATION) {
+        Some(val) => val,
+        None => return Credentials::Missing,
+    };
+
+    let val = match val.to_str() {
+        Ok(val) => val.trim(),
+        Err(_) => return Credentials::Malformed,
+    };
+
+    let (val_scheme, credentials) = val.split_at(val.find(' ').unwrap_or(val.len()));
+
+    if val_scheme.eq_ignore_ascii_case(scheme) {
+        Credentials::Found(credentials.trim())
+    } else {
+        Credentials::Missing
+    }
+}
+
+fn parse_basic_credentials(encoded: &str) -> Option<BasicCredentials> {
+    let decoded = String::from_utf8(decode_base64(encoded)?).ok()?;
+    let (username, password) = decoded.split_at(decoded.find(':')?);
+
+    Some(BasicCredentials {
+        username: username.to_owned(),
+        password: password[1..].to_owned(),
+    })
+}
+
+/// Decodes the standard base64 alphabet, the padding is optional.
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=');
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buf = 0_u32;
+    let mut bits = 0;
+
+    for byte in encoded.bytes() {
+        let val = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        buf = (buf << 6) | u32::from(val);
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buf >> bits) as u8);
+        }
+    }
+
+    // A single leftover character can't encode a whole byte.
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+
+    Some(decoded)
+}
+
+fn reject(status: StatusCode, challenge: &'static str) -> Response<Body> {
+    let mut resp = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *resp.status_mut() = status;
+
+    if !challenge.is_empty() {
+        resp.headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static(challenge));
+    }
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_credentials() {
+        assert_eq!(
+            parse_basic_credentials("YWxhZGRpbjpvcGVuc2VzYW1l"),
+            Some(BasicCredentials {
+                username: "aladdin".to_owned(),
+                password: "opensesame".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse_basic_credentials("dXNlcjpwYTpzcw=="),
+            Some(BasicCredentials {
+                username: "user".to_owned(),
+                password: "pa:ss".to_owned(),
+            })
+        );
+        // No colon.
+        assert_eq!(parse_basic_credentials("dXNlcg=="), None);
+        assert_eq!(parse_basic_credentials("not base64!"), None);
+        assert_eq!(parse_basic_credentials("YWJjZ"), None);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::middleware::{Middleware, Next};
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Options for the [circuit_breaker](./fn.circuit_breaker.html) middleware.
+///
+/// A request fails if it's responded with a `5xx` status, or if it takes longer than the [`slow_call`](#method.slow_call)
+/// duration when it's set.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerOptions {
+    failure_rate: f64,
+    open_duration: Duration,
+    window_size: usize,
+    min_requests: usize,
+    slow_call: Option<Duration>,
+    half_open_requests: u32,
+}
+
+impl CircuitBreakerOptions {
+    /// Creates the options with the rate of the failed requests e.g. `0.5` which trips the breaker, and the time the breaker
+    /// stays open before it lets the probing requests through.
+    ///
+    /// The rate is measured over the last `20` requests once there are at least `10` of them, and a single successful probe
+    /// closes the breaker.
+    pub fn new(failure_rate: f64, open_duration: Duration) -> CircuitBreakerOptions {
+        CircuitBreakerOptions {
+            failure_rate,
+            open_duration,
+            window_size: 20,
+            min_requests: 10,
+            slow_call: None,
+            half_open_requests: 1,
+        }
+    }
+
+    /// Sets the number of the last requests the failure rate is measured over.
+    pub fn window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size.max(1);
+        self
+    }
+
+    /// Sets the minimum number of the requests in the window before the breaker can trip.
+    pub fn min_requests(mut self, min_requests: usize) -> Self {
+        self.min_requests = min_requests;
+        self
+    }
+
+    /// Counts the requests which take longer than the duration as failed, even if they succeed.
+    pub fn slow_call(mut self, slow_call: Duration) -> Self {
+        self.slow_call = Some(slow_call);
+        self
+    }
+
+    /// Sets the number of the probing requests which must succeed to close the breaker, they're let through one at a time
+    /// after the breaker has been open for the `open_duration`.
+    pub fn half_open_requests(mut self, half_open_requests: u32) -> Self {
+        self.half_open_requests = half_open_requests.max(1);
+        self
+    }
+}
+
+/// Creates an around middleware which stops sending the requests to the rest of the middlewares and the route handler once
+/// too many of them fail, so a failing upstream gets time to recover and the clients get a quick answer instead of waiting
+/// for a timeout.
+///
+/// The breaker starts closed and it opens when the [failure rate](./struct.CircuitBreakerOptions.html) reaches the
+/// threshold. While it's open, the requests are rejected with `503 Service Unavailable` and a `Retry-After` header. After the
+/// `open_duration`, it lets a probing request through: the breaker closes if the probes succeed and opens again if any of
+/// them fails.
+///
+/// Every middleware keeps its own breaker, so it's usually added to a scoped router e.g. the routes which proxy to an
+/// upstream service.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, CircuitBreakerOptions};
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let payments = Router::builder()
+///     // Open for 30 seconds once half of the requests fail or take longer than 5 seconds.
+///     .middleware(middleware::circuit_breaker(
+///         CircuitBreakerOptions::new(0.5, Duration::from_secs(30)).slow_call(Duration::from_secs(5)),
+///     ))
+///     .post("/charges", |_| async move { Ok(Response::new(Body::from("Charged"))) })
+///     .build()
+///     .unwrap();
+///
+/// let router = Router::builder()
+///     .scope("/payments", payments)
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn circuit_breaker<E, ReqB>(options: CircuitBreakerOptions) -> Middleware<Body, E, ReqB>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let slow_call = options.slow_call;
+    let breaker = Arc::new(CircuitBreaker::new(options));
+
+    Middleware::around(move |req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let started_at = Instant::now();
+        let guard = breaker
+            .acquire(started_at)
+            .map(|probe| OutcomeGuard::new(breaker.clone(), probe));
+
+        async move {
+            let mut guard = match guard {
+                Ok(guard) => guard,
+                Err(wait) => return Ok(service_unavailable(wait)),
+            };
+
+            // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+            let resp = next.run(req).await.unwrap_or_else(|_| {
+                let mut resp = Response::new(Body::empty());
+                *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                resp
+            });
+
+            let slow = slow_call.is_some_and(|slow_call| started_at.elapsed() > slow_call);
+            guard.failed = resp.status().is_server_error() || slow;
+
+            Ok(resp)
+        }
+    })
+}
+
+fn service_unavailable(wait: Duration) -> Response<Body> {
+    // The `Retry-After` header takes whole seconds, so it's rounded up.
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+
+    let mut resp = Response::new(Body::from("Service Unavailable"));
+    *resp.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    resp.headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    resp
+}
+
+// Records the outcome of a request when it's dropped, a request cancelled e.g. by a client disconnect counts as failed.
+struct OutcomeGuard {
+    breaker: Arc<CircuitBreaker>,
+    probe: bool,
+    failed: bool,
+}
+
+impl OutcomeGuard {
+    fn new(breaker: Arc<CircuitBreaker>, probe: bool) -> OutcomeGuard {
+        OutcomeGuard {
+            breaker,
+            probe,
+            failed: true,
+        }
+    }
+}
+
+impl Drop for OutcomeGuard {
+    fn drop(&mut self) {
+        self.breaker.record(self.probe, self.failed, Instant::now());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Closed,
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, succeeded: u32 },
+}
+
+struct State {
+    phase: Phase,
+    // The outcomes of the last requests while the breaker is closed, `true` for a failed one.
+    outcomes: VecDeque<bool>,
+}
+
+struct CircuitBreaker {
+    options: CircuitBreakerOptions,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    fn new(options: CircuitBreakerOptions) -> CircuitBreaker {
+        CircuitBreaker {
+            options,
+            state: Mutex::new(State {
+                phase: Phase::Closed,
+                outcomes: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Lets a request through and returns whether it's a probe, it returns the time until the next probe if the breaker is open.
+    fn acquire(&self, now: Instant) -> Result<bool, Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        match state.phase {
+            Phase::Closed => Ok(false),
+            Phase::Open { until } if now < until => Err(until - now),
+            Phase::Open { .. } => {
+                state.phase = Phase::HalfOpen {
+                    in_flight: 1,
+                    succeeded: 0,
+                };
+                Ok(true)
+            }
+            Phase::HalfOpen { in_flight, succeeded } => {
+                if in_flight + succeeded >= self.options.half_open_requests {
+                    return Err(Duration::from_secs(1));
+                }
+
+                state.phase = Phase::HalfOpen {
+                    in_flight: in_flight + 1,
+                    succeeded,
+                };
+                Ok(true)
+            }
+        }
+    }
+
+    fn record(&self, probe: bool, failed: bool, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        match (state.phase, probe) {
+            (Phase::HalfOpen { .. }, true) if failed => self.open(&mut state, now),
+            (Phase::HalfOpen { in_flight, succeeded }, true) => {
+                if succeeded + 1 >= self.options.half_open_requests {
+                    state.phase = Phase::Closed;
+                } else {
+                    state.phase = Phase::HalfOpen {
+                        in_flight: in_flight.saturating_sub(1),
+                        succeeded: succeeded + 1,
+                    };
+                }
+            }
+            (Phase::Closed, false) => {
+                state.outcomes.push_back(failed);
+                if state.outcomes.len() > self.options.window_size {
+                    state.outcomes.pop_front();
+                }
+
+                let total = state.outcomes.len();
+                let failures = state.outcomes.iter().filter(|failed| **failed).count();
+                if total >= self.options.min_requests.max(1)
+                    && failures as f64 >= self.options.failure_rate * total as f64
+                {
+                    self.open(&mut state, now);
+                }
+            }
+            // The request was let through before the breaker changed its state.
+            _ => {}
+        }
+    }
+
+    fn open(&self, state: &mut State, now: Instant) {
+        state.phase = Phase::Open {
+            until: now + self.options.open_duration,
+        };
+        state.outcomes.clear();
+    }
+
+    #[cfg(test)]
+    fn phase(&self) -> Phase {
+        self.state.lock().unwrap().phase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker_trips_and_recovers() {
+        let breaker = CircuitBreaker::new(
+            CircuitBreakerOptions::new(0.5, Duration::from_secs(10))
+                .window_size(4)
+                .min_requests(4)
+                .half_open_requests(2),
+        );
+        let start = Instant::now();
+
+        for failed in [true, false, true] {
+            assert_eq!(breaker.acquire(start), Ok(false));
+            breaker.record(false, failed, start);
+        }
+        assert_eq!(breaker.phase(), Phase::Closed);
+
+        breaker.record(false, false, start);
+        breaker.record(false, true, start);
+        // The window keeps the last 4 outcomes, 2 of them failed.
+        assert!(matches!(breaker.phase(), Phase::Open { .. }));
+        assert_eq!(
+            breaker.acquire(start + Duration::from_secs(4)),
+            Err(Duration::from_secs(6))
+        );
+
+        // A failed probe opens it again.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(breaker.acquire(later), Ok(true));
+        assert_eq!(breaker.acquire(later), Ok(true));
+        assert!(breaker.acquire(later).is_err());
+        breaker.record(true, true, later);
+        assert!(breaker.acquire(later).is_err());
+
+        // The probes close it once they all succeed.
+        let later = later + Duration::from_secs(10);
+        assert_eq!(breaker.acquire(later), Ok(true));
+        breaker.record(true, false, later);
+        assert_eq!(breaker.acquire(later), Ok(true));
+        breaker.record(true, false, later);
+        assert_eq!(breaker.phase(), Phase::Closed);
+        assert_eq!(breaker.acquire(later), Ok(false));
+    }
+}
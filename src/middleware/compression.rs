@@ -0,0 +1,504 @@
+use crate::middleware::Middleware;
+use crate::types::RequestInfo;
+use brotli::CompressorWriter;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use futures::Stream;
+use hyper::body::{Bytes, HttpBody};
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Response, StatusCode};
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Options for the [compression](./fn.compression.html) middleware.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CompressionOptions {
+    /// The responses whose body size is known and is smaller than this value (in bytes) are sent uncompressed.
+    pub min_size: u64,
+
+    /// Enables the `gzip` encoding.
+    pub gzip: bool,
+
+    /// Enables the `deflate` encoding.
+    pub deflate: bool,
+
+    /// Enables the `br` (brotli) encoding.
+    pub brotli: bool,
+
+    /// The media types of the responses to compress, an entry can be an exact media type e.g. `application/json` or a
+    /// wildcard e.g. `text/*`. The common textual types are compressed if it's `None`.
+    pub content_types: Option<Vec<String>>,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> CompressionOptions {
+        CompressionOptions {
+            min_size: 1024,
+            gzip: true,
+            deflate: true,
+            brotli: true,
+            content_types: None,
+        }
+    }
+}
+
+impl CompressionOptions {
+    /// Sends the responses whose body size is known and is smaller than `min_size` bytes uncompressed, it's `1024` by
+    /// default.
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Enables or disables the `gzip` encoding, it's enabled by default.
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Enables or disables the `deflate` encoding, it's enabled by default.
+    pub fn deflate(mut self, deflate: bool) -> Self {
+        self.deflate = deflate;
+        self
+    }
+
+    /// Enables or disables the `br` (brotli) encoding, it's enabled by default.
+    pub fn brotli(mut self, brotli: bool) -> Self {
+        self.brotli = brotli;
+        self
+    }
+
+    /// Compresses only the responses of the media types, an entry can be an exact media type e.g. `application/json` or a
+    /// wildcard e.g. `text/*`.
+    pub fn content_types<I, T>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.content_types = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Creates a post middleware which compresses the response bodies with `gzip`, `deflate` or `br` encoding based on the
+/// request's `Accept-Encoding` header.
+///
+/// The responses which are already encoded, which are smaller than the [`min_size`](./struct.CompressionOptions.html#method.min_size)
+/// or whose content type isn't one of the [`content_types`](./struct.CompressionOptions.html#method.content_types) are left
+/// untouched. The compressed body is streamed, so the `Content-Length`
+/// header is removed from the compressed responses. The `Vary: Accept-Encoding` header is added to the responses which are
+/// compressed or left uncompressed for the request's `Accept-Encoding` header, so the caches don't serve a compressed
+/// response to a client which doesn't accept it.
+///
+/// It requires the `compression` feature to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, CompressionOptions};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(middleware::compression(CompressionOptions::default()))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn compression<E>(options: CompressionOptions) -> Middleware<Body, E>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::post_with_info(move |res, req_info| {
+        let options = options.clone();
+        async move { Ok(compress_response(res, &req_info, &options)) }
+    })
+}
+
+fn compress_response(mut res: Response<Body>, req_info: &RequestInfo, options: &CompressionOptions) -> Response<Body> {
+    if !should_compress(&res, options) {
+        return res;
+    }
+
+    if !has_compressible_content_type(&res, options) {
+        return res;
+    }
+
+    res.headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+    let encoding = match req_info
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| negotiate_encoding(val, options))
+    {
+        Some(encoding) => encoding,
+        None => return res,
+    };
+
+    let (mut parts, body) = res.into_parts();
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+
+    let body = Body::wrap_stream(CompressedBody {
+        inner: body,
+        encoder: Some(Encoder::new(encoding)),
+    });
+
+    Response::from_parts(parts, body)
+}
+
+fn should_compress(res: &Response<Body>, options: &CompressionOptions) -> bool {
+    if res.status() == StatusCode::NO_CONTENT || res.status() == StatusCode::NOT_MODIFIED {
+        return false;
+    }
+
+    let headers = res.headers();
+
+    if headers.contains_key(header::CONTENT_ENCODING) {
+        return false;
+    }
+
+    let no_transform = headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|val| val.to_str().ok())
+        .map(|val| val.to_ascii_lowercase().contains("no-transform"))
+        .unwrap_or(false);
+    if no_transform {
+        return false;
+    }
+
+    let size = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<u64>().ok())
+        .or_else(|| HttpBody::size_hint(res.body()).exact());
+
+    match size {
+        Some(size) => size >= options.min_size,
+        None => true,
+    }
+}
+
+fn has_compressible_content_type(res: &Response<Body>, options: &CompressionOptions) -> bool {
+    res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .map(|content_type| match options.content_types {
+            Some(ref content_types) => matches_content_types(content_type, content_types),
+            None => is_compressible_content_type(content_type),
+        })
+        .unwrap_or(false)
+}
+
+fn is_compressible_content_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+
+    if mime == "text/event-stream" {
+        return false;
+    }
+
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/json"
+                | "application/javascript"
+                | "application/xml"
+                | "application/wasm"
+                | "application/x-javascript"
+                | "image/svg+xml"
+        )
+}
+
+fn matches_content_types(content_type: &str, content_types: &[String]) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+
+    content_types.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        match allowed.strip_suffix('*') {
+            Some(prefix) => mime.starts_with(prefix),
+            None => mime == allowed,
+        }
+    })
+}
+
+fn negotiate_encoding(accept_encoding: &str, options: &CompressionOptions) -> Option<Encoding> {
+    let items = accept_encoding
+        .split(',')
+        .map(|item| {
+            let mut segments = item.split(';');
+            let coding = segments.next().unwrap_or("").trim().to_ascii_lowercase();
+
+            let quality = segments
+                .filter_map(|param| {
+                    let param = param.trim();
+                    param.strip_prefix("q=").or_else(|| param.strip_prefix("Q="))
+                })
+                .next()
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            (coding, quality)
+        })
+        .collect::<Vec<_>>();
+
+    // The `*` matches only the codings which aren't listed explicitly, so it can't re-enable a refused one.
+    let listed = items
+        .iter()
+        .flat_map(|(coding, _)| codings(coding))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut selected: Option<(Encoding, f32)> = None;
+
+    for (coding, quality) in items.iter() {
+        let quality = *quality;
+        if quality <= 0.0 {
+            continue;
+        }
+
+        let candidates: &[Encoding] = match coding.as_str() {
+            "*" => &[Encoding::Brotli, Encoding::Gzip, Encoding::Deflate],
+            coding => codings(coding),
+        };
+
+        for &encoding in candidates {
+            if coding == "*" && listed.contains(&encoding) {
+                continue;
+            }
+
+            let enabled = match encoding {
+                Encoding::Brotli => options.brotli,
+                Encoding::Gzip => options.gzip,
+                Encoding::Deflate => options.deflate,
+            };
+
+            if !enabled {
+                continue;
+            }
+
+            // Prefer the higher quality value, and the order `br`, `gzip`, `deflate` for the equal ones.
+            let better = match selected {
+                Some((selected_encoding, selected_quality)) => {
+                    quality > selected_quality
+                        || (quality == selected_quality && rank(encoding) < rank(selected_encoding))
+                }
+                None => true,
+            };
+
+            if better {
+                selected = Some((encoding, quality));
+            }
+        }
+    }
+
+    selected.map(|(encoding, _)| encoding)
+}
+
+fn codings(coding: &str) -> &'static [Encoding] {
+    match coding {
+        "br" => &[Encoding::Brotli],
+        "gzip" | "x-gzip" => &[Encoding::Gzip],
+        "deflate" => &[Encoding::Deflate],
+        _ => &[],
+    }
+}
+
+fn rank(encoding: Encoding) -> u8 {
+    match encoding {
+        Encoding::Brotli => 0,
+        Encoding::Gzip => 1,
+        Encoding::Deflate => 2,
+    }
+}
+
+enum Encoder {
+    Brotli(Box<CompressorWriter<Vec<u8>>>),
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Encoder {
+        match encoding {
+            Encoding::Brotli => Encoder::Brotli(Box::new(CompressorWriter::new(Vec::new(), 4096, 5, 22))),
+            Encoding::Gzip => Encoder::Gzip(GzEncoder::new(Vec::new(), Compression::default())),
+            Encoding::Deflate => Encoder::Deflate(ZlibEncoder::new(Vec::new(), Compression::default())),
+        }
+    }
+
+    // The encoder is flushed after every chunk, so a streamed response e.g. the server-sent events isn't held back until the
+    // internal buffer of the encoder fills up.
+    fn encode(&mut self, chunk: &[u8]) -> io::Result<Bytes> {
+        let buf = match self {
+            Encoder::Brotli(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Deflate(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+        };
+
+        Ok(Bytes::from(std::mem::take(buf)))
+    }
+
+    fn finish(self) -> io::Result<Bytes> {
+        let buf = match self {
+            Encoder::Brotli(encoder) => encoder.into_inner(),
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            Encoder::Deflate(encoder) => encoder.finish()?,
+        };
+
+        Ok(Bytes::from(buf))
+    }
+}
+
+struct CompressedBody {
+    inner: Body,
+    encoder: Option<Encoder>,
+}
+
+impl Stream for CompressedBody {
+    type Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.encoder.is_none() {
+                return Poll::Ready(None);
+            }
+
+            match Pin::new(&mut self.inner).poll_data(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let encoded = self.encoder.as_mut().unwrap().encode(&chunk);
+                    match encoded {
+                        // An empty chunk produces nothing, so keep reading.
+                        Ok(encoded) if encoded.is_empty() => continue,
+                        Ok(encoded) => return Poll::Ready(Some(Ok(encoded))),
+                        Err(err) => return Poll::Ready(Some(Err(err.into()))),
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => {
+                    let encoded = self.encoder.take().unwrap().finish();
+                    return Poll::Ready(Some(encoded.map_err(|err| err.into())));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_encoding() {
+        let options = CompressionOptions::default();
+
+        assert_eq!(
+            negotiate_encoding("gzip, deflate, br", &options),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            negotiate_encoding("gzip;q=1.0, br;q=0.5", &options),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(negotiate_encoding("deflate", &options), Some(Encoding::Deflate));
+        assert_eq!(negotiate_encoding("*", &options), Some(Encoding::Brotli));
+        assert_eq!(negotiate_encoding("gzip;q=0, identity", &options), None);
+        // The `*` doesn't re-enable the refused codings.
+        assert_eq!(negotiate_encoding("br;q=0, *", &options), Some(Encoding::Gzip));
+        assert_eq!(negotiate_encoding("br;q=0, gzip;q=0, deflate;q=0, *", &options), None);
+        assert_eq!(negotiate_encoding("gzip;q=0.5, *", &options), Some(Encoding::Brotli));
+        assert_eq!(negotiate_encoding("", &options), None);
+
+        let options = CompressionOptions::default().brotli(false);
+        assert_eq!(negotiate_encoding("br, gzip", &options), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn test_encoder_flushes_every_chunk() {
+        use flate2::write::{GzDecoder, ZlibDecoder};
+
+        fn decode<W: Write>(mut decoder: W, encoded: &[u8]) -> W {
+            decoder.write_all(encoded).unwrap();
+            decoder.flush().unwrap();
+            decoder
+        }
+
+        for encoding in [Encoding::Brotli, Encoding::Gzip, Encoding::Deflate] {
+            let mut encoder = Encoder::new(encoding);
+            // The first chunk is decodable before the body ends.
+            let encoded = encoder.encode(b"data: hello\n\n").unwrap();
+
+            let decoded = match encoding {
+                Encoding::Brotli => decode(brotli::DecompressorWriter::new(Vec::new(), 4096), &encoded)
+                    .get_ref()
+                    .clone(),
+                Encoding::Gzip => decode(GzDecoder::new(Vec::new()), &encoded).get_ref().clone(),
+                Encoding::Deflate => decode(ZlibDecoder::new(Vec::new()), &encoded).get_ref().clone(),
+            };
+            assert_eq!(decoded, b"data: hello\n\n", "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_is_compressible_content_type() {
+        assert!(is_compressible_content_type("text/html; charset=utf-8"));
+        assert!(is_compressible_content_type("application/json"));
+        assert!(is_compressible_content_type("application/vnd.api+json"));
+        assert!(!is_compressible_content_type("image/png"));
+        assert!(!is_compressible_content_type("text/event-stream"));
+    }
+
+    #[test]
+    fn test_matches_content_types() {
+        let content_types = vec!["application/json".to_owned(), "text/*".to_owned()];
+        assert!(matches_content_types("application/json; charset=utf-8", &content_types));
+        assert!(matches_content_types("Text/CSV", &content_types));
+        assert!(!matches_content_types("application/xml", &content_types));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::middleware::{Middleware, Next};
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Options for the [concurrency_limit](./fn.concurrency_limit.html) middleware.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitOptions {
+    max_in_flight: usize,
+    queue_timeout: Option<Duration>,
+}
+
+impl ConcurrencyLimitOptions {
+    /// Creates the options with the maximum number of the requests processed at the same time.
+    ///
+    /// The requests exceeding the limit are rejected right away, unless a [`queue_timeout`](#method.queue_timeout) is set.
+    pub fn new(max_in_flight: usize) -> ConcurrencyLimitOptions {
+        ConcurrencyLimitOptions {
+            max_in_flight,
+            queue_timeout: None,
+        }
+    }
+
+    /// Sets the maximum time a request waits for a slot when the limit is reached, it's rejected once the time elapses. The
+    /// waiting requests get the slots in their arrival order.
+    pub fn queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = Some(queue_timeout);
+        self
+    }
+}
+
+/// Creates an around middleware which limits the number of the requests processed at the same time, the requests exceeding
+/// the limit are rejected with `503 Service Unavailable` without running the rest of the middlewares and the route handler.
+///
+/// It protects the app and its backends from the overload, as the router doesn't limit the concurrency by itself. A slot is
+/// held until the response is ready, a streamed response body isn't counted. The limit is shared by all the connections of
+/// the router, it can be added to a scoped router to limit only the routes of the scope e.g. the expensive ones.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, ConcurrencyLimitOptions};
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     // At most 100 requests at the same time, the others wait up to 2 seconds.
+///     .middleware(middleware::concurrency_limit(
+///         ConcurrencyLimitOptions::new(100).queue_timeout(Duration::from_secs(2)),
+///     ))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn concurrency_limit<E, ReqB>(options: ConcurrencyLimitOptions) -> Middleware<Body, E, ReqB>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(options.max_in_flight));
+    let queue_timeout = options.queue_timeout;
+
+    Middleware::around(move |req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let semaphore = semaphore.clone();
+
+        async move {
+            let permit = match (semaphore.try_acquire(), queue_timeout) {
+                (Ok(permit), _) => permit,
+                (Err(_), Some(queue_timeout)) => match tokio::time::timeout(queue_timeout, semaphore.acquire()).await {
+                    Ok(permit) => permit,
+                    Err(_) => return Ok(service_unavailable()),
+                },
+                (Err(_), None) => return Ok(service_unavailable()),
+            };
+
+            // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+            let resp = next.run(req).await.unwrap_or_else(|_| {
+                let mut resp = Response::new(Body::empty());
+                *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                resp
+            });
+
+            drop(permit);
+            Ok(resp)
+        }
+    })
+}
+
+fn service_unavailable() -> Response<Body> {
+    let mut resp = Response::new(Body::from("Service Unavailable"));
+    *resp.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    resp.headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+    resp
+}
@@ -0,0 +1,232 @@
+use crate::middleware::Middleware;
+use crate::types::RequestInfo;
+use futures::stream;
+use hyper::body::{self, Bytes, HttpBody};
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Body, Method, Response, StatusCode};
+use std::sync::Arc;
+
+/// Options for the [conditional_get](./fn.conditional_get.html) middleware.
+#[derive(Debug, Clone)]
+pub struct ConditionalGetOptions {
+    /// The responses whose body is larger than this value (in bytes) or whose size is unknown e.g. a streaming body don't get
+    /// an `ETag`, as the body has to be buffered to compute it.
+    pub max_size: u64,
+
+    /// Enables the `If-Modified-Since` validation for the responses which have a `Last-Modified` header.
+    pub last_modified: bool,
+}
+
+impl Default for ConditionalGetOptions {
+    fn default() -> ConditionalGetOptions {
+        ConditionalGetOptions {
+            max_size: 256 * 1024,
+            last_modified: true,
+        }
+    }
+}
+
+/// Creates a post middleware which adds a weak `ETag` header to the responses of the `GET` and `HEAD` requests and responds
+/// with `304 Not Modified` and an empty body if the request's `If-None-Match` header matches it.
+///
+/// The `ETag` is computed over the buffered response body, so it's skipped for the bodies larger than the
+/// [`max_size`](./struct.ConditionalGetOptions.html#structfield.max_size) and for the streaming bodies. An `ETag` set by the
+/// handler is kept as it is. The responses which already carry a `Last-Modified` header are validated against the
+/// `If-Modified-Since` header too, unless the request has an `If-None-Match` header. The non-`2xx` responses are left untouched.
+///
+/// It should be added before the [`compression`](./fn.compression.html) middleware, as a compressed body is streamed.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, ConditionalGetOptions};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .middleware(middleware::conditional_get(ConditionalGetOptions::default()))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn conditional_get<E>(options: ConditionalGetOptions) -> Middleware<Body, E>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::post_with_info(move |res, req_info| {
+        let options = options.clone();
+        async move { Ok(validate_response(res, &req_info, &options).await) }
+    })
+}
+
+async fn validate_response(
+    res: Response<Body>,
+    req_info: &RequestInfo,
+    options: &ConditionalGetOptions,
+) -> Response<Body> {
+    let is_get = req_info.method() == Method::GET || req_info.method() == Method::HEAD;
+    if !is_get || !res.status().is_success() {
+        return res;
+    }
+
+    let res = if res.headers().contains_key(header::ETAG) {
+        res
+    } else {
+        match add_etag(res, options).await {
+            Ok(res) => res,
+            // The body is already consumed, so the error is passed on to the connection.
+            Err(res) => return res,
+        }
+    };
+
+    if is_not_modified(req_info.headers(), res.headers(), options) {
+        return not_modified(res);
+    }
+
+    res
+}
+
+async fn add_etag(res: Response<Body>, options: &ConditionalGetOptions) -> Result<Response<Body>, Response<Body>> {
+    let size = HttpBody::size_hint(res.body()).upper();
+    if size.map(|size| size > options.max_size).unwrap_or(true) {
+        return Ok(res);
+    }
+
+    let (mut parts, body) = res.into_parts();
+
+    let bytes = match body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let body = Body::wrap_stream(stream::once(async move { Err::<Bytes, _>(err) }));
+            return Err(Response::from_parts(parts, body));
+        }
+    };
+
+    let etag = format!("W/\"{:x}-{:016x}\"", bytes.len(), fnv1a(&bytes));
+    if let Ok(val) = HeaderValue::from_str(&etag) {
+        parts.headers.insert(header::ETAG, val);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn is_not_modified(req_headers: &HeaderMap, res_headers: &HeaderMap, options: &ConditionalGetOptions) -> bool {
+    // The `If-Modified-Since` header is ignored if the `If-None-Match` header is present.
+    if let Some(if_none_match) = req_headers.get(header::IF_NONE_MATCH) {
+        let etag = match res_headers.get(header::ETAG).and_then(|val| val.to_str().ok()) {
+            Some(etag) => etag,
+            None => return false,
+        };
+
+        return if_none_match
+            .to_str()
+            .map(|val| etag_matches(val, etag))
+            .unwrap_or(false);
+    }
+
+    if !options.last_modified {
+        return false;
+    }
+
+    let parse_date = |headers: &HeaderMap, name| {
+        headers
+            .get(name)
+            .and_then(|val: &HeaderValue| val.to_str().ok())
+            .and_then(|val| httpdate::parse_http_date(val).ok())
+    };
+
+    match (
+        parse_date(res_headers, header::LAST_MODIFIED),
+        parse_date(req_headers, header::IF_MODIFIED_SINCE),
+    ) {
+        (Some(last_modified), Some(if_modified_since)) => last_modified <= if_modified_since,
+        _ => false,
+    }
+}
+
+/// Matches the `If-None-Match` header value against the `ETag` by the weak comparison, it's a list of the entity tags or `*`.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque_tag = |tag: &str| {
+        let tag = tag.trim();
+        tag.strip_prefix("W/").unwrap_or(tag).to_owned()
+    };
+
+    let etag = opaque_tag(etag);
+
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque_tag(tag) == etag)
+}
+
+fn not_modified(res: Response<Body>) -> Response<Body> {
+    let (mut parts, _) = res.into_parts();
+
+    parts.status = StatusCode::NOT_MODIFIED;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::CONTENT_TYPE);
+
+    Response::from_parts(parts, Body::empty())
+}
+
+// The 64-bit FNV-1a hash, it's stable across the builds unlike the std hasher, so the clients can keep the `ETag` values.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("W/\"5-abc\"", "W/\"5-abc\""));
+        assert!(etag_matches("\"5-abc\"", "W/\"5-abc\""));
+        assert!(etag_matches("\"1-def\", W/\"5-abc\"", "W/\"5-abc\""));
+        assert!(etag_matches("*", "W/\"5-abc\""));
+        assert!(!etag_matches("W/\"5-abd\"", "W/\"5-abc\""));
+        assert!(!etag_matches("", "W/\"5-abc\""));
+    }
+
+    #[test]
+    fn test_is_not_modified_by_date() {
+        let options = ConditionalGetOptions::default();
+
+        let mut res_headers = HeaderMap::new();
+        res_headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert!(is_not_modified(&req_headers, &res_headers, &options));
+
+        req_headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Tue, 20 Oct 2015 07:28:00 GMT"),
+        );
+        assert!(!is_not_modified(&req_headers, &res_headers, &options));
+
+        let options = ConditionalGetOptions {
+            last_modified: false,
+            ..ConditionalGetOptions::default()
+        };
+        req_headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert!(!is_not_modified(&req_headers, &res_headers, &options));
+    }
+}
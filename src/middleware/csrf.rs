@@ -0,0 +1,173 @@
+use crate::body::{media_type, read_body};
+use crate::ext::RequestExt;
+use crate::middleware::{Middleware, Next};
+use crate::session::Session;
+use crate::types::{constant_time_eq, random_token, QueryParams};
+use crate::Error;
+use hyper::header::HeaderName;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use std::sync::Arc;
+
+// The session key which keeps the token.
+const SESSION_KEY: &str = "_csrf_token";
+
+/// Options for the [csrf](./fn.csrf.html) middleware.
+#[derive(Debug, Clone)]
+pub struct CsrfOptions {
+    /// The request header which carries the token, it's `X-CSRF-Token` by default.
+    pub header: HeaderName,
+
+    /// The field of an `application/x-www-form-urlencoded` body which carries the token, it's `_csrf` by default. The body
+    /// isn't read if it's `None` or if the header is present.
+    pub form_field: Option<String>,
+
+    /// The maximum size of the form body in bytes which is buffered to find the token, it's `64 KiB` by default.
+    pub max_form_size: u64,
+}
+
+impl Default for CsrfOptions {
+    fn default() -> CsrfOptions {
+        CsrfOptions {
+            header: HeaderName::from_static("x-csrf-token"),
+            form_field: Some("_csrf".to_owned()),
+            max_form_size: 64 * 1024,
+        }
+    }
+}
+
+/// The CSRF token of the session, it's stored in the request context by the [csrf](./fn.csrf.html) middleware, so a route
+/// handler can embed it into a form or a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(pub String);
+
+impl CsrfToken {
+    /// Returns the token.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+/// Creates an around middleware which protects the routes against the cross-site request forgery by a token which is issued
+/// per session.
+///
+/// The token is kept in the [session](../session/index.html), so the [session](../session/fn.session.html) middleware must be
+/// added before this one, otherwise the requests are responded with `500 Internal Server Error`. It's stored in the request
+/// context as a [`CsrfToken`](./struct.CsrfToken.html), a request with any method other than `GET`, `HEAD`, `OPTIONS` and
+/// `TRACE` must send it back in the `X-CSRF-Token` header or in the `_csrf` form field, otherwise it's rejected with
+/// `403 Forbidden` without running the rest of the middlewares and the route handler.
+///
+/// The token is issued on the first request of a session, so it starts a session for every client. It can be added to a
+/// scoped router to protect only the routes which serve the browsers, and the API routes which authenticate by a header
+/// don't need it.
+///
+/// It requires the `cookies` feature to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::middleware::{self, CsrfOptions, CsrfToken};
+/// use routerify::session::{self, MemoryStore, SessionOptions};
+/// use routerify::prelude::*;
+/// use routerify::{CookieKeys, Router};
+/// use hyper::Body;
+///
+/// # fn run() -> routerify::Result<Router<Body, routerify::Error>> {
+/// let keys = CookieKeys::new("a secret key of at least 32 bytes!!")?;
+///
+/// let router = Router::builder()
+///     .middleware(session::session(SessionOptions::new(MemoryStore::new(), keys)))
+///     .middleware(middleware::csrf(CsrfOptions::default()))
+///     .get("/profile", |req| async move {
+///         let token = req.context::<CsrfToken>().unwrap();
+///         Ok(format!(
+///             r#"<form method="post"><input type="hidden" name="_csrf" value="{}"><input name="bio"></form>"#,
+///             token.as_str()
+///         ))
+///     })
+///     .post("/profile", |_| async move { Ok("Updated") })
+///     .build()
+///     .unwrap();
+/// # Ok(router)
+/// # }
+/// # run().unwrap();
+/// ```
+pub fn csrf<E>(options: CsrfOptions) -> Middleware<Body, E>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::around(move |mut req: Request<Body>, next: Next<Body>| {
+        let options = options.clone();
+        let session = req.session().cloned();
+
+        async move {
+            let session = match session {
+                Some(session) => session,
+                None => return Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR)),
+            };
+            let token = session_token(&session);
+
+            if !is_safe_method(req.method()) {
+                let valid = match submitted_token(&mut req, &options).await {
+                    Ok(submitted) => submitted.is_some_and(|val| constant_time_eq(val.as_bytes(), token.as_bytes())),
+                    Err(Error::RequestBodyTooLarge(_)) => return Ok(error_response(StatusCode::PAYLOAD_TOO_LARGE)),
+                    Err(_) => return Ok(error_response(StatusCode::BAD_REQUEST)),
+                };
+
+                if !valid {
+                    return Ok(error_response(StatusCode::FORBIDDEN));
+                }
+            }
+
+            req.set_context(CsrfToken(token));
+
+            // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+            Ok(next
+                .run(req)
+                .await
+                .unwrap_or_else(|_| error_response(StatusCode::INTERNAL_SERVER_ERROR)))
+        }
+    })
+}
+
+fn session_token(session: &Session) -> String {
+    match session.get(SESSION_KEY) {
+        Some(token) => token,
+        None => {
+            let token = random_token();
+            session.insert(SESSION_KEY, token.as_str());
+            token
+        }
+    }
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+}
+
+// Finds the token in the header or in the form body, the body is put back into the request for the route handler.
+async fn submitted_token(req: &mut Request<Body>, options: &CsrfOptions) -> crate::Result<Option<String>> {
+    if let Some(val) = req.headers().get(&options.header) {
+        return Ok(val.to_str().ok().map(str::to_owned));
+    }
+
+    let form_field = match options.form_field {
+        Some(ref form_field) if media_type(req) == "application/x-www-form-urlencoded" => form_field,
+        _ => return Ok(None),
+    };
+
+    let bytes = read_body(req, Some(options.max_form_size)).await?;
+    let token = QueryParams::parse(Some(&String::from_utf8_lossy(&bytes)))
+        .get(form_field)
+        .map(str::to_owned);
+    *req.body_mut() = Body::from(bytes);
+
+    Ok(token)
+}
+
+fn error_response(status: StatusCode) -> Response<Body> {
+    let mut resp = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *resp.status_mut() = status;
+    resp
+}
@@ -0,0 +1,119 @@
+use crate::ext::RequestExt;
+use crate::middleware::{Middleware, PreOutcome};
+use crate::types::{resolve_client_ip, IpRange};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// Options for the [ip_filter](./fn.ip_filter.html) middleware.
+#[derive(Debug, Clone, Default)]
+pub struct IpFilterOptions {
+    allow: Vec<IpRange>,
+    deny: Vec<IpRange>,
+    trusted_proxies: Vec<IpRange>,
+}
+
+impl IpFilterOptions {
+    /// Creates the options which admit every client.
+    pub fn new() -> IpFilterOptions {
+        IpFilterOptions::default()
+    }
+
+    /// Admits the clients in the range, once a range is allowed the clients outside of all the allowed ranges are rejected.
+    pub fn allow(mut self, range: IpRange) -> Self {
+        self.allow.push(range);
+        self
+    }
+
+    /// Rejects the clients in the range, it takes precedence over the allowed ranges.
+    pub fn deny(mut self, range: IpRange) -> Self {
+        self.deny.push(range);
+        self
+    }
+
+    /// Trusts the `Forwarded`, `X-Forwarded-For` and `X-Real-IP` headers of the requests sent by the proxies in the range, so
+    /// the client address is taken from them instead of the connection. The router's
+    /// [trusted proxies](../struct.RouterBuilder.html#method.trusted_proxy) are used if no range is trusted here.
+    pub fn trusted_proxy(mut self, range: IpRange) -> Self {
+        self.trusted_proxies.push(range);
+        self
+    }
+
+    fn admits(&self, ip: Option<IpAddr>) -> bool {
+        match ip {
+            Some(ip) => {
+                !self.deny.iter().any(|range| range.contains(&ip))
+                    && (self.allow.is_empty() || self.allow.iter().any(|range| range.contains(&ip)))
+            }
+            None => self.allow.is_empty(),
+        }
+    }
+}
+
+/// Creates a pre middleware which admits or rejects the requests by the client address, the rejected requests are responded
+/// with `403 Forbidden` without running the rest of the middlewares and the route handler.
+///
+/// A client is rejected if its address is in a [denied](./struct.IpFilterOptions.html#method.deny) range, or if any range is
+/// [allowed](./struct.IpFilterOptions.html#method.allow) and its address is in none of them. It's resolved like the
+/// [`RequestExt::client_ip`](../ext/trait.RequestExt.html#tymethod.client_ip) method, but the
+/// [trusted proxies](./struct.IpFilterOptions.html#method.trusted_proxy) of the options take precedence over the router's.
+/// A client connected over a Unix domain socket has no address, so it's only admitted if no range is allowed.
+///
+/// It can be added to a scoped router to restrict only the routes of the scope e.g. the admin routes.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{IpRange, Router};
+/// use routerify::middleware::{self, IpFilterOptions};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> routerify::Result<Router<Body, Infallible>> {
+/// let admin = Router::builder()
+///     .middleware(middleware::ip_filter(
+///         IpFilterOptions::new()
+///             .allow("10.0.0.0/8".parse::<IpRange>()?)
+///             .deny("10.0.13.0/24".parse::<IpRange>()?)
+///             // The load balancer.
+///             .trusted_proxy("10.0.0.2".parse::<IpRange>()?),
+///     ))
+///     .get("/stats", |_| async move { Ok(Response::new(Body::from("Stats"))) })
+///     .build()
+///     .unwrap();
+///
+/// let router = Router::builder()
+///     .scope("/admin", admin)
+///     .build()
+///     .unwrap();
+/// # Ok(router)
+/// # }
+/// # run().unwrap();
+/// ```
+pub fn ip_filter<E, ReqB>(options: IpFilterOptions) -> Middleware<Body, E, ReqB>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::pre_with_response(move |req: Request<ReqB>| {
+        let ip = if options.trusted_proxies.is_empty() {
+            req.client_ip()
+        } else {
+            req.peer_addr()
+                .as_socket_addr()
+                .map(|addr| resolve_client_ip(addr.ip(), req.headers(), &options.trusted_proxies))
+        };
+
+        let outcome = if options.admits(ip) {
+            PreOutcome::Continue(req)
+        } else {
+            let mut resp = Response::new(Body::from("Forbidden"));
+            *resp.status_mut() = StatusCode::FORBIDDEN;
+            PreOutcome::Respond(resp)
+        };
+
+        async move { Ok(outcome) }
+    })
+}
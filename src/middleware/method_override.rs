@@ -0,0 +1,129 @@
+use crate::ext::RequestExt;
+use crate::middleware::Middleware;
+use hyper::header::HeaderName;
+use hyper::{body::HttpBody, Method, Request};
+use std::sync::Arc;
+
+/// Options for the [method_override](./fn.method_override.html) middleware.
+#[derive(Debug, Clone)]
+pub struct MethodOverrideOptions {
+    /// The request header which carries the method, it's `X-HTTP-Method-Override` by default. The header is ignored if it's
+    /// `None`.
+    pub header: Option<HeaderName>,
+
+    /// The query param which carries the method e.g. `_method`, it's disabled by default. The header takes precedence over
+    /// the query param if both are present.
+    pub query_param: Option<String>,
+
+    /// The methods which a `POST` request can be overridden with, they are `PUT`, `PATCH` and `DELETE` by default.
+    pub allowed_methods: Vec<Method>,
+}
+
+impl Default for MethodOverrideOptions {
+    fn default() -> MethodOverrideOptions {
+        MethodOverrideOptions {
+            header: Some(HeaderName::from_static("x-http-method-override")),
+            query_param: None,
+            allowed_methods: vec![Method::PUT, Method::PATCH, Method::DELETE],
+        }
+    }
+}
+
+/// The method a request was received with before the [method_override](./fn.method_override.html) middleware replaced it,
+/// it's stored in the request extensions.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::OriginalMethod;
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .delete("/users/:id", |req| async move {
+///         if let Some(OriginalMethod(method)) = req.extensions().get::<OriginalMethod>() {
+///             println!("A {} request is handled as DELETE", method);
+///         }
+///         Ok(Response::new(Body::from("Deleted")))
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalMethod(pub Method);
+
+/// Creates a pre middleware which replaces the method of a `POST` request with the one in the `X-HTTP-Method-Override`
+/// header or in a query param, so a HTML form can reach e.g. a `DELETE` route.
+///
+/// Only the [allowed methods](./struct.MethodOverrideOptions.html#structfield.allowed_methods) are accepted, any other
+/// value is ignored and the request is routed as a `POST` request. The routes are matched after the pre middlewares, so it
+/// should be added before the other pre middlewares which depend on the method. The original method is stored as an
+/// [`OriginalMethod`](./struct.OriginalMethod.html) in the request extensions, the [`RequestInfo`](../struct.RequestInfo.html)
+/// keeps the original method too.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, MethodOverrideOptions};
+/// use hyper::{Response, Body};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     // A form posted to `/users/1?_method=DELETE` is handled by the `DELETE` route.
+///     .middleware(middleware::method_override(MethodOverrideOptions {
+///         query_param: Some("_method".to_owned()),
+///         ..MethodOverrideOptions::default()
+///     }))
+///     .delete("/users/:id", |_| async move { Ok(Response::new(Body::from("Deleted"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn method_override<B, E, ReqB>(options: MethodOverrideOptions) -> Middleware<B, E, ReqB>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::pre(move |mut req| {
+        if let Some(method) = override_method(&req, &options) {
+            let original_method = std::mem::replace(req.method_mut(), method);
+            req.extensions_mut().insert(OriginalMethod(original_method));
+        }
+
+        async move { Ok(req) }
+    })
+}
+
+fn override_method<ReqB>(req: &Request<ReqB>, options: &MethodOverrideOptions) -> Option<Method> {
+    if req.method() != Method::POST {
+        return None;
+    }
+
+    let from_header = options
+        .header
+        .as_ref()
+        .and_then(|header| req.headers().get(header))
+        .and_then(|val| val.to_str().ok());
+
+    let from_query = || options.query_param.as_ref().and_then(|key| req.query(key));
+
+    let method = from_header.or_else(from_query)?.trim().to_ascii_uppercase();
+
+    options
+        .allowed_methods
+        .iter()
+        .find(|allowed| allowed.as_str() == method)
+        .cloned()
+}
@@ -1,32 +1,65 @@
+//! The middleware types and some ready-made middlewares. Please refer to the [Middleware](../index.html#middleware) section for more info.
+
 use crate::types::RequestInfo;
 use hyper::{body::HttpBody, Request, Response};
 use std::future::Future;
 
+pub use self::around::{AroundMiddleware, Next};
+pub use self::circuit_breaker::{circuit_breaker, CircuitBreakerOptions};
+#[cfg(feature = "compression")]
+pub use self::compression::{compression, CompressionOptions};
+pub use self::concurrency_limit::{concurrency_limit, ConcurrencyLimitOptions};
+pub use self::conditional_get::{conditional_get, ConditionalGetOptions};
+#[cfg(feature = "cookies")]
+pub use self::csrf::{csrf, CsrfOptions, CsrfToken};
+pub use self::ip_filter::{ip_filter, IpFilterOptions};
+pub use self::method_override::{method_override, MethodOverrideOptions, OriginalMethod};
 pub use self::post::PostMiddleware;
-pub use self::pre::PreMiddleware;
+pub use self::pre::{PreMiddleware, PreOutcome};
+pub use self::rate_limit::{rate_limit, RateLimitOptions, RateLimitStore};
 
+mod around;
+pub mod auth;
+mod circuit_breaker;
+#[cfg(feature = "compression")]
+mod compression;
+mod concurrency_limit;
+mod conditional_get;
+#[cfg(feature = "cookies")]
+mod csrf;
+mod ip_filter;
+mod method_override;
 mod post;
 mod pre;
+mod rate_limit;
 
 /// Enum type for all the middleware types. Please refer to the [Middleware](./index.html#middleware) for more info.
 ///
-/// This `Middleware<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Middleware<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
 #[derive(Debug)]
-pub enum Middleware<B, E> {
+pub enum Middleware<B, E, ReqB = hyper::Body> {
     /// Variant for the pre middleware. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
-    Pre(PreMiddleware<E>),
+    Pre(PreMiddleware<E, ReqB, B>),
 
     /// Variant for the post middleware. Refer to [Post Middleware](./index.html#post-middleware) for more info.
     Post(PostMiddleware<B, E>),
+
+    /// Variant for the around middleware. Refer to [Around Middleware](./index.html#around-middleware) for more info.
+    Around(AroundMiddleware<B, E, ReqB>),
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    Middleware<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Middleware<B, E, ReqB>
 {
     /// Creates a pre middleware with a handler at the `/*` path.
     ///
@@ -46,10 +79,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn pre<H, R>(handler: H) -> Middleware<B, E>
+    pub fn pre<H, R>(handler: H) -> Middleware<B, E, ReqB>
     where
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqB>, E>> + Send + 'static,
     {
         Middleware::pre_with_path("/*", handler).unwrap()
     }
@@ -72,9 +105,9 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn post<H, R>(handler: H) -> Middleware<B, E>
+    pub fn post<H, R>(handler: H) -> Middleware<B, E, ReqB>
     where
-        H: FnMut(Response<B>) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Middleware::post_with_path("/*", handler).unwrap()
@@ -107,9 +140,9 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_info<H, R>(handler: H) -> Middleware<B, E>
+    pub fn post_with_info<H, R>(handler: H) -> Middleware<B, E, ReqB>
     where
-        H: FnMut(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Middleware::post_with_info_with_path("/*", handler).unwrap()
@@ -133,15 +166,89 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn pre_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqB>>
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqB>, E>> + Send + 'static,
     {
         Ok(Middleware::Pre(PreMiddleware::new(path, handler)?))
     }
 
+    /// Creates a pre middleware with a handler at the `/*` path, which can respond to the request by itself e.g. to reject an
+    /// unauthorized request. The response skips the remaining pre middlewares and the route handler, but it's still passed
+    /// through the post middlewares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, PreOutcome};
+    /// use hyper::{Request, Response, Body, StatusCode};
+    /// use std::convert::Infallible;
+    ///
+    /// async fn maintenance(req: Request<Body>) -> Result<PreOutcome<Body>, Infallible> {
+    ///     if req.uri().path().starts_with("/admin") {
+    ///         let res = Response::builder()
+    ///             .status(StatusCode::SERVICE_UNAVAILABLE)
+    ///             .body(Body::from("Under maintenance"))
+    ///             .unwrap();
+    ///         return Ok(PreOutcome::Respond(res));
+    ///     }
+    ///
+    ///     Ok(PreOutcome::Continue(req))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::pre_with_response(maintenance))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn pre_with_response<H, R>(handler: H) -> Middleware<B, E, ReqB>
+    where
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<PreOutcome<B, ReqB>, E>> + Send + 'static,
+    {
+        Middleware::pre_with_response_with_path("/*", handler).unwrap()
+    }
+
+    /// Creates a pre middleware with a handler at the specified path, which can respond to the request by itself.
+    ///
+    /// Please refer to the [`pre_with_response`](#method.pre_with_response) method for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware, PreOutcome};
+    /// use hyper::{Response, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      .middleware(
+    ///          Middleware::pre_with_response_with_path("/legacy/*", |_| async move {
+    ///              Ok(PreOutcome::Respond(Response::new(Body::from("Gone"))))
+    ///          })
+    ///          .unwrap(),
+    ///      )
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn pre_with_response_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqB>>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<PreOutcome<B, ReqB>, E>> + Send + 'static,
+    {
+        Ok(Middleware::Pre(PreMiddleware::new_with_response(path, handler)?))
+    }
+
     /// Creates a post middleware with a handler at the specified path.
     ///
     /// # Examples
@@ -160,10 +267,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn post_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqB>>
     where
         P: Into<String>,
-        H: FnMut(Response<B>) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Ok(Middleware::Post(PostMiddleware::new(path, handler)?))
@@ -196,12 +303,68 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E>>
+    pub fn post_with_info_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqB>>
     where
         P: Into<String>,
-        H: FnMut(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         Ok(Middleware::Post(PostMiddleware::new_with_info(path, handler)?))
     }
+    /// Creates an around middleware with a handler at the `/*` path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    /// use std::time::Instant;
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::around(|req, next| async move {
+    ///          let start = Instant::now();
+    ///          let res = next.run(req).await;
+    ///          println!("Took {:?}", start.elapsed());
+    ///          res
+    ///      }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn around<H, R>(handler: H) -> Middleware<B, E, ReqB>
+    where
+        H: Fn(Request<ReqB>, Next<B, ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        Middleware::around_with_path("/*", handler).unwrap()
+    }
+
+    /// Creates an around middleware with a handler at the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::Body;
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///      .middleware(Middleware::around_with_path("/my-path/*", |req, next| async move { next.run(req).await }).unwrap())
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn around_with_path<P, H, R>(path: P, handler: H) -> crate::Result<Middleware<B, E, ReqB>>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>, Next<B, ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+    {
+        Ok(Middleware::Around(AroundMiddleware::new(path, handler)?))
+    }
 }
@@ -1,4 +1,5 @@
 use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
 use crate::types::RequestInfo;
 use crate::Error;
 use hyper::{body::HttpBody, Response};
@@ -6,12 +7,13 @@ use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-type HandlerWithoutInfo<B, E> = Box<dyn FnMut(Response<B>) -> HandlerWithoutInfoReturn<B, E> + Send + Sync + 'static>;
+type HandlerWithoutInfo<B, E> = Box<dyn Fn(Response<B>) -> HandlerWithoutInfoReturn<B, E> + Send + Sync + 'static>;
 type HandlerWithoutInfoReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
 type HandlerWithInfo<B, E> =
-    Box<dyn FnMut(Response<B>, RequestInfo) -> HandlerWithInfoReturn<B, E> + Send + Sync + 'static>;
+    Box<dyn Fn(Response<B>, RequestInfo) -> HandlerWithInfoReturn<B, E> + Send + Sync + 'static>;
 type HandlerWithInfoReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
 
 /// The post middleware type. Refer to [Post Middleware](./index.html#post-middleware) for more info.
@@ -28,6 +30,8 @@ pub struct PostMiddleware<B, E> {
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
     pub(crate) handler: Option<Handler<B, E>>,
+    // It's inherited from the scoped router, the errors of the other middlewares are handled by the root router's error handler.
+    pub(crate) err_handler: Option<Arc<ErrHandler<B>>>,
 }
 
 pub(crate) enum Handler<B, E> {
@@ -49,6 +53,7 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
             path,
             regex: re,
             handler: Some(handler),
+            err_handler: None,
         })
     }
 
@@ -70,10 +75,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn new<P, H, R>(path: P, mut handler: H) -> crate::Result<PostMiddleware<B, E>>
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<B, E>>
     where
         P: Into<String>,
-        H: FnMut(Response<B>) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         let handler: HandlerWithoutInfo<B, E> = Box::new(move |res: Response<B>| Box::new(handler(res)));
@@ -107,10 +112,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn new_with_info<P, H, R>(path: P, mut handler: H) -> crate::Result<PostMiddleware<B, E>>
+    pub fn new_with_info<P, H, R>(path: P, handler: H) -> crate::Result<PostMiddleware<B, E>>
     where
         P: Into<String>,
-        H: FnMut(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
+        H: Fn(Response<B>, RequestInfo) -> R + Send + Sync + 'static,
         R: Future<Output = Result<Response<B>, E>> + Send + 'static,
     {
         let handler: HandlerWithInfo<B, E> =
@@ -129,25 +134,19 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         }
     }
 
-    pub(crate) async fn process(
-        &mut self,
-        res: Response<B>,
-        req_info: Option<RequestInfo>,
-    ) -> crate::Result<Response<B>> {
+    pub(crate) async fn process(&self, res: Response<B>, req_info: Option<RequestInfo>) -> crate::Result<Response<B>> {
         let handler = self
             .handler
-            .as_mut()
+            .as_ref()
             .expect("A router can not be used after mounting into another router");
 
         match handler {
-            Handler::WithoutInfo(ref mut handler) => Pin::from(handler(res))
+            Handler::WithoutInfo(ref handler) => Pin::from(handler(res))
                 .await
                 .map_err(|e| Error::HandlePostMiddlewareWithoutInfoRequest(e.into())),
-            Handler::WithInfo(ref mut handler) => {
-                Pin::from(handler(res, req_info.expect("No RequestInfo is provided")))
-                    .await
-                    .map_err(|e| Error::HandlePostMiddlewareWithInfoRequest(e.into()))
-            }
+            Handler::WithInfo(ref handler) => Pin::from(handler(res, req_info.expect("No RequestInfo is provided")))
+                .await
+                .map_err(|e| Error::HandlePostMiddlewareWithInfoRequest(e.into())),
         }
     }
 }
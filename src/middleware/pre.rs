@@ -1,32 +1,54 @@
 use crate::regex_generator::generate_exact_match_regex;
+use crate::router::ErrHandler;
 use crate::Error;
-use hyper::Request;
+use futures::TryFutureExt;
+use hyper::{body::HttpBody, Request, Response};
 use regex::Regex;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-type Handler<E> = Box<dyn FnMut(Request<hyper::Body>) -> HandlerReturn<E> + Send + Sync + 'static>;
-type HandlerReturn<E> = Box<dyn Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static>;
+type Handler<E, ReqB, B> = Box<dyn Fn(Request<ReqB>) -> HandlerReturn<E, ReqB, B> + Send + Sync + 'static>;
+type HandlerReturn<E, ReqB, B> = Box<dyn Future<Output = Result<PreOutcome<B, ReqB>, E>> + Send + 'static>;
+
+/// The outcome of a pre middleware created by the [`Middleware::pre_with_response`](./enum.Middleware.html#method.pre_with_response)
+/// method.
+#[derive(Debug)]
+pub enum PreOutcome<B, ReqB = hyper::Body> {
+    /// Passes the request to the remaining pre middlewares and the route handler.
+    Continue(Request<ReqB>),
+
+    /// Skips the remaining pre middlewares and the route handler, the response goes straight to the post middlewares.
+    Respond(Response<B>),
+}
 
 /// The pre middleware type. Refer to [Pre Middleware](./index.html#pre-middleware) for more info.
 ///
-/// This `PreMiddleware<E>` type accepts a single type parameter: `E`.
+/// This `PreMiddleware<E, ReqB, B>` type accepts three type parameters: `E`, `ReqB` and `B`.
 ///
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
-pub struct PreMiddleware<E> {
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
+/// * The `B` represents the body type of the response a pre middleware can respond with, it must be the response body type of
+///   the router and it defaults to [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
+pub struct PreMiddleware<E, ReqB = hyper::Body, B = hyper::Body> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<E>>,
+    pub(crate) handler: Option<Handler<E, ReqB, B>>,
+    // The error handler inherited from the scoped router.
+    pub(crate) err_handler: Option<Arc<ErrHandler<B>>>,
 }
 
-impl<E: std::error::Error + Send + Sync + Unpin + 'static> PreMiddleware<E> {
+impl<E: std::error::Error + Send + Sync + Unpin + 'static, ReqB: HttpBody + Send + 'static, B: Send + 'static>
+    PreMiddleware<E, ReqB, B>
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
-        handler: Handler<E>,
-    ) -> crate::Result<PreMiddleware<E>> {
+        handler: Handler<E, ReqB, B>,
+    ) -> crate::Result<PreMiddleware<E, ReqB, B>> {
         let path = path.into();
         let (re, _) = generate_exact_match_regex(path.as_str())?;
 
@@ -34,6 +56,7 @@ impl<E: std::error::Error + Send + Sync + Unpin + 'static> PreMiddleware<E> {
             path,
             regex: re,
             handler: Some(handler),
+            err_handler: None,
         })
     }
 
@@ -55,20 +78,31 @@ impl<E: std::error::Error + Send + Sync + Unpin + 'static> PreMiddleware<E> {
     /// # }
     /// # run();
     /// ```
-    pub fn new<P, H, R>(path: P, mut handler: H) -> crate::Result<PreMiddleware<E>>
+    pub fn new<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E, ReqB, B>>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<Request<ReqB>, E>> + Send + 'static,
+    {
+        let handler: Handler<E, ReqB, B> =
+            Box::new(move |req: Request<ReqB>| Box::new(handler(req).map_ok(PreOutcome::Continue)));
+        PreMiddleware::new_with_boxed_handler(path, handler)
+    }
+
+    pub(crate) fn new_with_response<P, H, R>(path: P, handler: H) -> crate::Result<PreMiddleware<E, ReqB, B>>
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Request<hyper::Body>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<PreOutcome<B, ReqB>, E>> + Send + 'static,
     {
-        let handler: Handler<E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
+        let handler: Handler<E, ReqB, B> = Box::new(move |req: Request<ReqB>| Box::new(handler(req)));
         PreMiddleware::new_with_boxed_handler(path, handler)
     }
 
-    pub(crate) async fn process(&mut self, req: Request<hyper::Body>) -> crate::Result<Request<hyper::Body>> {
+    pub(crate) async fn process(&self, req: Request<ReqB>) -> crate::Result<PreOutcome<B, ReqB>> {
         let handler = self
             .handler
-            .as_mut()
+            .as_ref()
             .expect("A router can not be used after mounting into another router");
 
         Pin::from(handler(req))
@@ -77,7 +111,7 @@ impl<E: std::error::Error + Send + Sync + Unpin + 'static> PreMiddleware<E> {
     }
 }
 
-impl<E> Debug for PreMiddleware<E> {
+impl<E, ReqB, B> Debug for PreMiddleware<E, ReqB, B> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{{ path: {:?}, regex: {:?} }}", self.path, self.regex)
     }
@@ -0,0 +1,386 @@
+use crate::ext::RequestExt;
+use crate::middleware::{Middleware, Next};
+use futures::future::BoxFuture;
+use hyper::header::{self, HeaderValue};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use lru::LruCache;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// The in-memory buckets are split into the shards by the hash of the key, so the requests of the different clients rarely
+// wait for the same lock.
+const SHARDS: usize = 16;
+
+type KeyExtractor<ReqB> = Arc<dyn Fn(&Request<ReqB>) -> Option<String> + Send + Sync + 'static>;
+
+/// An external storage of the token buckets for the [rate_limit](./fn.rate_limit.html) middleware e.g. a cache server, so
+/// the limits are shared by all the instances of the app. A store backend can wrap its own errors in an
+/// [`Error::Store`](../enum.Error.html#variant.Store) error.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::middleware::RateLimitStore;
+/// use futures::future::BoxFuture;
+/// use std::time::Duration;
+///
+/// struct RedisStore {
+///     // A connection pool.
+/// }
+///
+/// impl RateLimitStore for RedisStore {
+///     fn acquire<'a>(
+///         &'a self,
+///         key: &'a str,
+///         capacity: u32,
+///         refill_interval: Duration,
+///     ) -> BoxFuture<'a, routerify::Result<Option<Duration>>> {
+///         // Run a script which refills and takes a token from the bucket atomically.
+///         Box::pin(async move { Ok(None) })
+///     }
+/// }
+/// ```
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Takes a token from the bucket of the key, which holds at most `capacity` tokens and gets a token back after every
+    /// `refill_interval`. It returns the time until the next token is available if the bucket is empty.
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        capacity: u32,
+        refill_interval: Duration,
+    ) -> BoxFuture<'a, crate::Result<Option<Duration>>>;
+}
+
+/// Options for the [rate_limit](./fn.rate_limit.html) middleware.
+///
+/// Every client gets a bucket of `capacity` tokens, a request takes a token from its bucket and a token is added back after
+/// every `refill_interval`, so a client can send a burst of `capacity` requests and then a request per `refill_interval`.
+pub struct RateLimitOptions<ReqB = hyper::Body> {
+    capacity: u32,
+    refill_interval: Duration,
+    max_keys: usize,
+    idle_timeout: Duration,
+    key: KeyExtractor<ReqB>,
+    store: Option<Arc<dyn RateLimitStore>>,
+}
+
+impl<ReqB: 'static> RateLimitOptions<ReqB> {
+    /// Creates the options with the bucket capacity and the time it takes to add a token back to a bucket.
+    ///
+    /// The clients are identified by their [IP address](../ext/trait.RequestExt.html#tymethod.client_ip) and the buckets are kept in memory, at most `10000` buckets are kept and
+    /// the buckets which aren't used for `10` minutes are removed.
+    pub fn new(capacity: u32, refill_interval: Duration) -> RateLimitOptions<ReqB> {
+        RateLimitOptions {
+            capacity,
+            refill_interval,
+            max_keys: 10_000,
+            idle_timeout: Duration::from_secs(10 * 60),
+            key: Arc::new(|req: &Request<ReqB>| {
+                Some(match req.client_ip() {
+                    Some(ip) => ip.to_string(),
+                    None => req.peer_addr().to_string(),
+                })
+            }),
+            store: None,
+        }
+    }
+
+    /// Sets the maximum number of buckets kept in memory, they're split evenly among the shards of the store. If a new client
+    /// arrives when the limit of its shard is reached, the least recently used bucket of the shard is removed to make room
+    /// for it, so a flood of new keys can't lock out the other clients.
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    /// Sets the time after which an unused bucket is removed.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets the function which identifies the client of a request e.g. by an API key header instead of the IP address. The
+    /// requests for which it returns `None` aren't limited.
+    pub fn key<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&Request<ReqB>) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = Arc::new(key);
+        self
+    }
+
+    /// Keeps the buckets in an external store instead of the memory, the [`max_keys`](#method.max_keys) and the
+    /// [`idle_timeout`](#method.idle_timeout) options don't apply to it. If the store fails, the request is responded with
+    /// `500 Internal Server Error`.
+    pub fn store<S: RateLimitStore>(mut self, store: S) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+}
+
+impl<ReqB> Clone for RateLimitOptions<ReqB> {
+    fn clone(&self) -> Self {
+        RateLimitOptions {
+            capacity: self.capacity,
+            refill_interval: self.refill_interval,
+            max_keys: self.max_keys,
+            idle_timeout: self.idle_timeout,
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<ReqB> Debug for RateLimitOptions<ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ capacity: {:?}, refill_interval: {:?}, max_keys: {:?}, idle_timeout: {:?}, store: {:?} }}",
+            self.capacity,
+            self.refill_interval,
+            self.max_keys,
+            self.idle_timeout,
+            self.store.is_some()
+        )
+    }
+}
+
+/// Creates an around middleware which limits the request rate of every client by a token bucket, the requests exceeding the
+/// limit are rejected with `429 Too Many Requests` and a `Retry-After` header without running the rest of the middlewares and
+/// the route handler.
+///
+/// The buckets are shared by all the connections of the router.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use routerify::middleware::{self, RateLimitOptions};
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     // A burst of 10 requests and then a request per second for every API key.
+///     .middleware(middleware::rate_limit(
+///         RateLimitOptions::new(10, Duration::from_secs(1))
+///             .key(|req| req.headers().get("x-api-key").and_then(|val| val.to_str().ok()).map(str::to_owned)),
+///     ))
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn rate_limit<E, ReqB>(options: RateLimitOptions<ReqB>) -> Middleware<Body, E, ReqB>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let key = options.key.clone();
+    let (capacity, refill_interval) = (options.capacity, options.refill_interval);
+    let store = options.store.clone();
+    let limiter = Arc::new(RateLimiter::new(options));
+
+    Middleware::around(move |req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let key = key(&req);
+        let store = store.clone();
+        let wait = match (&key, &store) {
+            (Some(key), None) => limiter.acquire(key.clone(), Instant::now()).err(),
+            _ => None,
+        };
+
+        async move {
+            let wait = match (key, store) {
+                (Some(key), Some(store)) => match store.acquire(&key, capacity, refill_interval).await {
+                    Ok(wait) => wait,
+                    Err(_) => return Ok(internal_server_error()),
+                },
+                _ => wait,
+            };
+
+            if let Some(wait) = wait {
+                return Ok(too_many_requests(wait));
+            }
+
+            // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+            Ok(next.run(req).await.unwrap_or_else(|_| internal_server_error()))
+        }
+    })
+}
+
+fn internal_server_error() -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    resp
+}
+
+fn too_many_requests(wait: Duration) -> Response<Body> {
+    // The `Retry-After` header takes whole seconds, so it's rounded up.
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+
+    let mut resp = Response::new(Body::from("Too Many Requests"));
+    *resp.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    resp.headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    resp
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+pub(crate) struct RateLimiter {
+    capacity: f64,
+    refill_interval: Duration,
+    max_keys_per_shard: usize,
+    idle_timeout: Duration,
+    hasher: RandomState,
+    // The buckets of a shard are ordered by their last use, so the idle ones are at the least recently used end.
+    shards: Box<[Mutex<LruCache<String, Bucket>>]>,
+}
+
+impl RateLimiter {
+    fn new<ReqB>(options: RateLimitOptions<ReqB>) -> RateLimiter {
+        RateLimiter::with_shards(options, SHARDS)
+    }
+
+    fn with_shards<ReqB>(options: RateLimitOptions<ReqB>, shards: usize) -> RateLimiter {
+        RateLimiter {
+            capacity: f64::from(options.capacity),
+            refill_interval: options.refill_interval,
+            max_keys_per_shard: options.max_keys.div_ceil(shards).max(1),
+            idle_timeout: options.idle_timeout,
+            hasher: RandomState::new(),
+            shards: (0..shards).map(|_| Mutex::new(LruCache::unbounded())).collect(),
+        }
+    }
+
+    /// Takes a token from the bucket of the key, it returns the time until the next token is available if the bucket is empty.
+    ///
+    /// If the shard of a new key is full, the least recently used bucket of the shard is removed.
+    pub(crate) fn acquire(&self, key: String, now: Instant) -> Result<(), Duration> {
+        let shard = &self.shards[self.hasher.hash_one(&key) as usize % self.shards.len()];
+        let mut buckets = shard.lock().unwrap_or_else(|err| err.into_inner());
+
+        // The idle buckets are removed from the least recently used end, a bucket is removed only once so it takes constant
+        // time on average.
+        while let Some((_, bucket)) = buckets.peek_lru() {
+            if now.saturating_duration_since(bucket.updated_at) < self.idle_timeout {
+                break;
+            }
+            buckets.pop_lru();
+        }
+
+        if !buckets.contains(&key) && buckets.len() >= self.max_keys_per_shard {
+            buckets.pop_lru();
+        }
+
+        let capacity = self.capacity;
+        let bucket = buckets.get_or_insert_mut(key, || Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        let refilled = if self.refill_interval == Duration::from_secs(0) {
+            capacity
+        } else {
+            elapsed.as_secs_f64() / self.refill_interval.as_secs_f64()
+        };
+        bucket.tokens = (bucket.tokens + refilled).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.refill_interval.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_refills() {
+        let limiter = RateLimiter::new(RateLimitOptions::<Body>::new(3, Duration::from_secs(2)));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire("a".to_owned(), start), Ok(()));
+        }
+        assert_eq!(limiter.acquire("a".to_owned(), start), Err(Duration::from_secs(2)));
+        // Other keys have their own buckets.
+        assert_eq!(limiter.acquire("b".to_owned(), start), Ok(()));
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.acquire("a".to_owned(), later), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_secs(2);
+        assert_eq!(limiter.acquire("a".to_owned(), later), Ok(()));
+        assert!(limiter.acquire("a".to_owned(), later).is_err());
+
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire("a".to_owned(), later), Ok(()));
+        }
+        assert!(limiter.acquire("a".to_owned(), later).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_evicts_idle_buckets() {
+        let limiter = RateLimiter::with_shards(
+            RateLimitOptions::<Body>::new(1, Duration::from_secs(1))
+                .max_keys(2)
+                .idle_timeout(Duration::from_secs(10)),
+            1,
+        );
+        let start = Instant::now();
+
+        assert!(limiter.acquire("a".to_owned(), start).is_ok());
+        assert!(limiter.acquire("b".to_owned(), start + Duration::from_secs(1)).is_ok());
+        assert!(limiter
+            .acquire("b".to_owned(), start + Duration::from_millis(1500))
+            .is_err());
+        assert_eq!(limiter.len(), 2);
+
+        // Both buckets are idle by now, so they're removed before `c` gets its own.
+        assert!(limiter.acquire("c".to_owned(), start + Duration::from_secs(20)).is_ok());
+        assert_eq!(limiter.len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limiter_admits_new_keys_when_full() {
+        let limiter = RateLimiter::with_shards(
+            RateLimitOptions::<Body>::new(1, Duration::from_secs(1))
+                .max_keys(2)
+                .idle_timeout(Duration::from_secs(10)),
+            1,
+        );
+        let start = Instant::now();
+
+        assert!(limiter.acquire("a".to_owned(), start).is_ok());
+        assert!(limiter.acquire("b".to_owned(), start).is_ok());
+        assert!(limiter.acquire("b".to_owned(), start).is_err());
+
+        // The shard is full of active buckets, the least recently used one of `a` is removed to make room for `c`.
+        assert!(limiter.acquire("c".to_owned(), start).is_ok());
+        assert_eq!(limiter.len(), 2);
+        assert!(limiter.acquire("b".to_owned(), start).is_err());
+        assert!(limiter.acquire("c".to_owned(), start).is_err());
+    }
+}
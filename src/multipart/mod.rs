@@ -0,0 +1,547 @@
+//! A streaming parser of the `multipart/form-data` request bodies e.g. the file uploads.
+//!
+//! The fields are read one by one, and the content of a field is streamed in chunks, so a large file doesn't have to be
+//! buffered in the memory. The size of every field and the total size of the body are limited by the
+//! [`MultipartOptions`](./struct.MultipartOptions.html).
+//!
+//! It requires the `multipart` feature to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::multipart::Multipart;
+//! use routerify::Router;
+//! use hyper::{Body, StatusCode};
+//!
+//! # fn run() -> Router<Body, routerify::Error> {
+//! let router = Router::builder()
+//!     .post("/upload", |mut req| async move {
+//!         let mut multipart = Multipart::new(&mut req)?;
+//!         let mut uploaded = Vec::new();
+//!
+//!         while let Some(field) = multipart.next_field().await? {
+//!             match field.file_name().map(|name| name.replace('/', "_")) {
+//!                 Some(file_name) => {
+//!                     field.save_to(std::env::temp_dir().join(&file_name)).await?;
+//!                     uploaded.push(file_name);
+//!                 }
+//!                 None => {
+//!                     let name = field.name().unwrap_or_default().to_owned();
+//!                     println!("{}: {}", name, field.text().await?);
+//!                 }
+//!             }
+//!         }
+//!
+//!         Ok((StatusCode::CREATED, format!("Uploaded: {}", uploaded.join(", "))))
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::body::{check_content_length, media_type};
+use crate::Error;
+use futures::future::poll_fn;
+use futures::ready;
+use futures::stream::Stream;
+use hyper::body::{Bytes, HttpBody};
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue};
+use hyper::{Body, Request};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWriteExt;
+
+// The maximum size of the headers of a part, a larger header block is most likely not a valid multipart body.
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+
+/// Options for the [Multipart::with_options](./struct.Multipart.html#method.with_options) function.
+#[derive(Debug, Clone)]
+pub struct MultipartOptions {
+    /// The maximum size of the content of a field in bytes, a larger field fails with an
+    /// [`Error::RequestBodyTooLarge`](../enum.Error.html#variant.RequestBodyTooLarge) error. There's no limit if it's `None`.
+    pub max_field_size: Option<u64>,
+
+    /// The maximum size of the whole body in bytes, a larger body fails with an
+    /// [`Error::RequestBodyTooLarge`](../enum.Error.html#variant.RequestBodyTooLarge) error. There's no limit if it's `None`.
+    pub max_total_size: Option<u64>,
+}
+
+impl Default for MultipartOptions {
+    fn default() -> MultipartOptions {
+        MultipartOptions {
+            max_field_size: Some(16 * 1024 * 1024),
+            max_total_size: Some(64 * 1024 * 1024),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // Before the first boundary.
+    Preamble,
+    // After a boundary, which is followed by either a line break or `--` for the last one.
+    BoundaryTail,
+    Headers,
+    Body,
+    Done,
+}
+
+/// A parser of a `multipart/form-data` request body, which yields the [fields](./struct.Field.html) one by one.
+///
+/// The body is taken out of the request and replaced with an empty one, so the request can still be used to read e.g.
+/// the route params. The errors of a malformed body are [`Error::ParseMultipart`](../enum.Error.html#variant.ParseMultipart)
+/// errors, the default error handler responds to them with `400 Bad Request`.
+#[derive(Debug)]
+pub struct Multipart {
+    body: Body,
+    // The delimiter of the parts i.e. `\r\n--` followed by the boundary, the first one isn't preceded by a line break.
+    delimiter: Vec<u8>,
+    buf: Vec<u8>,
+    eof: bool,
+    state: State,
+    read: u64,
+    options: MultipartOptions,
+}
+
+impl Multipart {
+    /// Creates a parser of the request body with the [default options](./struct.MultipartOptions.html), a field larger than
+    /// `16 MiB` or a body larger than `64 MiB` is rejected.
+    ///
+    /// It fails with an [`Error::UnsupportedContentType`](../enum.Error.html#variant.UnsupportedContentType) error if the
+    /// `Content-Type` of the request is not `multipart/form-data`.
+    pub fn new(req: &mut Request<Body>) -> crate::Result<Multipart> {
+        Multipart::with_options(req, MultipartOptions::default())
+    }
+
+    /// Same as [new](#method.new), but with the specified options.
+    pub fn with_options(req: &mut Request<Body>, options: MultipartOptions) -> crate::Result<Multipart> {
+        let media_type = media_type(req);
+        if media_type != "multipart/form-data" {
+            return Err(Error::UnsupportedContentType(media_type));
+        }
+
+        let boundary = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| find_param(val, "boundary"))
+            .filter(|boundary| !boundary.is_empty())
+            .ok_or_else(|| Error::ParseMultipart("the boundary is missing".to_owned()))?;
+
+        if let Some(max_total_size) = options.max_total_size {
+            check_content_length(req, max_total_size)?;
+        }
+
+        Ok(Multipart {
+            body: std::mem::replace(req.body_mut(), Body::empty()),
+            delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            buf: Vec::new(),
+            eof: false,
+            state: State::Preamble,
+            read: 0,
+            options,
+        })
+    }
+
+    /// Returns the next field or `None` if there are no more fields. The unread content of the previous field is skipped.
+    pub async fn next_field(&mut self) -> crate::Result<Option<Field<'_>>> {
+        let headers = match poll_fn(|cx| self.poll_next_headers(cx)).await? {
+            Some(headers) => headers,
+            None => return Ok(None),
+        };
+
+        let disposition = headers
+            .get(header::CONTENT_DISPOSITION)
+            .and_then(|val| val.to_str().ok())
+            .unwrap_or_default();
+
+        Ok(Some(Field {
+            name: find_param(disposition, "name"),
+            file_name: find_param(disposition, "filename"),
+            headers,
+            size: 0,
+            multipart: self,
+        }))
+    }
+
+    fn poll_next_headers(&mut self, cx: &mut Context<'_>) -> Poll<crate::Result<Option<HeaderMap>>> {
+        loop {
+            match self.state {
+                State::Preamble => {
+                    let dash_boundary = &self.delimiter[2..];
+                    if let Some(idx) = find(&self.buf, dash_boundary) {
+                        self.buf.drain(..idx + dash_boundary.len());
+                        self.state = State::BoundaryTail;
+                        continue;
+                    }
+
+                    // Only the tail of the preamble can be the beginning of the boundary.
+                    let keep = dash_boundary.len() - 1;
+                    if self.buf.len() > keep {
+                        self.buf.drain(..self.buf.len() - keep);
+                    }
+                }
+                State::BoundaryTail => {
+                    if self.buf.starts_with(b"--") {
+                        self.state = State::Done;
+                        continue;
+                    } else if self.buf.starts_with(b"\r\n") {
+                        self.buf.drain(..2);
+                        self.state = State::Headers;
+                        continue;
+                    } else if self.buf.len() >= 2 {
+                        return Poll::Ready(Err(Error::ParseMultipart(
+                            "the boundary isn't followed by a line break".to_owned(),
+                        )));
+                    }
+                }
+                State::Headers => {
+                    if self.buf.starts_with(b"\r\n") {
+                        self.buf.drain(..2);
+                        self.state = State::Body;
+                        return Poll::Ready(Ok(Some(HeaderMap::new())));
+                    }
+
+                    if let Some(idx) = find(&self.buf, b"\r\n\r\n") {
+                        let headers = parse_headers(&self.buf[..idx])?;
+                        self.buf.drain(..idx + 4);
+                        self.state = State::Body;
+                        return Poll::Ready(Ok(Some(headers)));
+                    }
+
+                    if self.buf.len() > MAX_HEADERS_SIZE {
+                        return Poll::Ready(Err(Error::ParseMultipart("the part headers are too large".to_owned())));
+                    }
+                }
+                State::Body => {
+                    // Skips the unread content of the previous field.
+                    while ready!(self.poll_chunk(cx))?.is_some() {}
+                    continue;
+                }
+                State::Done => return Poll::Ready(Ok(None)),
+            }
+
+            ready!(self.poll_fill(cx))?;
+        }
+    }
+
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<crate::Result<Option<Bytes>>> {
+        loop {
+            if self.state != State::Body {
+                return Poll::Ready(Ok(None));
+            }
+
+            if let Some(idx) = find(&self.buf, &self.delimiter) {
+                if idx == 0 {
+                    self.buf.drain(..self.delimiter.len());
+                    self.state = State::BoundaryTail;
+                    return Poll::Ready(Ok(None));
+                }
+
+                return Poll::Ready(Ok(Some(self.take(idx))));
+            }
+
+            // The tail of the buffer can be the beginning of the delimiter, the rest is the field content for sure.
+            let safe = self.buf.len().saturating_sub(self.delimiter.len() - 1);
+            if safe > 0 {
+                return Poll::Ready(Ok(Some(self.take(safe))));
+            }
+
+            ready!(self.poll_fill(cx))?;
+        }
+    }
+
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<crate::Result<()>> {
+        if self.eof {
+            return Poll::Ready(Err(Error::ParseMultipart("unexpected end of the body".to_owned())));
+        }
+
+        match ready!(Pin::new(&mut self.body).poll_data(cx)) {
+            Some(Ok(chunk)) => {
+                self.read += chunk.len() as u64;
+                if let Some(max_total_size) = self.options.max_total_size {
+                    if self.read > max_total_size {
+                        return Poll::Ready(Err(Error::RequestBodyTooLarge(max_total_size)));
+                    }
+                }
+
+                self.buf.extend_from_slice(&chunk);
+            }
+            Some(Err(err)) => return Poll::Ready(Err(Error::ReadRequestBody(err))),
+            None => self.eof = true,
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn take(&mut self, len: usize) -> Bytes {
+        let rest = self.buf.split_off(len);
+        Bytes::from(std::mem::replace(&mut self.buf, rest))
+    }
+}
+
+/// A field of a `multipart/form-data` body, whose content is a [`Stream`](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html)
+/// of chunks.
+///
+/// The content can also be read at once by the [`bytes`](#method.bytes) and the [`text`](#method.text) methods, or written
+/// into a file by the [`save_to`](#method.save_to) method.
+#[derive(Debug)]
+pub struct Field<'a> {
+    multipart: &'a mut Multipart,
+    headers: HeaderMap,
+    name: Option<String>,
+    file_name: Option<String>,
+    size: u64,
+}
+
+impl Field<'_> {
+    /// Returns the name of the field from the `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the file name from the `Content-Disposition` header, it's `None` if the field is not a file.
+    ///
+    /// **Note:** The file name is sent by the client, it must be sanitized before it's used as a path.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Returns the value of the `Content-Type` header of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(header::CONTENT_TYPE).and_then(|val| val.to_str().ok())
+    }
+
+    /// Returns the headers of the field.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the next chunk of the content or `None` if the whole content is read.
+    pub async fn chunk(&mut self) -> crate::Result<Option<Bytes>> {
+        poll_fn(|cx| self.poll_chunk(cx)).await
+    }
+
+    /// Reads the whole content.
+    pub async fn bytes(mut self) -> crate::Result<Bytes> {
+        let mut buf = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(Bytes::from(buf))
+    }
+
+    /// Reads the whole content as a UTF-8 text.
+    pub async fn text(self) -> crate::Result<String> {
+        let name = self.name.clone().unwrap_or_default();
+        let bytes = self.bytes().await?;
+
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::ParseMultipart(format!("the field '{}' is not a valid UTF-8 text", name)))
+    }
+
+    /// Writes the content into the file at the specified path and returns its size, the file is created or truncated. The
+    /// file is removed if the content couldn't be read e.g. it's too large.
+    ///
+    /// It fails with an [`Error::SaveMultipartField`](../enum.Error.html#variant.SaveMultipartField) error if the file
+    /// couldn't be written.
+    pub async fn save_to<P: AsRef<Path>>(mut self, path: P) -> crate::Result<u64> {
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::create(path).await.map_err(Error::SaveMultipartField)?;
+
+        let result = async {
+            let mut size = 0;
+            while let Some(chunk) = self.chunk().await? {
+                file.write_all(&chunk).await.map_err(Error::SaveMultipartField)?;
+                size += chunk.len() as u64;
+            }
+            file.flush().await.map_err(Error::SaveMultipartField)?;
+
+            Ok(size)
+        }
+        .await;
+
+        if result.is_err() {
+            drop(file);
+            let _ = tokio::fs::remove_file(path).await;
+        }
+
+        result
+    }
+
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<crate::Result<Option<Bytes>>> {
+        let chunk = ready!(self.multipart.poll_chunk(cx))?;
+
+        if let Some(ref chunk) = chunk {
+            self.size += chunk.len() as u64;
+            if let Some(max_field_size) = self.multipart.options.max_field_size {
+                if self.size > max_field_size {
+                    return Poll::Ready(Err(Error::RequestBodyTooLarge(max_field_size)));
+                }
+            }
+        }
+
+        Poll::Ready(Ok(chunk))
+    }
+}
+
+impl Stream for Field<'_> {
+    type Item = crate::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_chunk(cx).map(Result::transpose)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn parse_headers(block: &[u8]) -> crate::Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+
+    for line in block.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let colon = line
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| Error::ParseMultipart("a part header has no value".to_owned()))?;
+
+        let name = HeaderName::from_bytes(&line[..colon])
+            .map_err(|_| Error::ParseMultipart("invalid part header name".to_owned()))?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+            .map_err(|_| Error::ParseMultipart("invalid part header value".to_owned()))?;
+
+        headers.append(name, value);
+    }
+
+    Ok(headers)
+}
+
+// Finds a param of a header value like `form-data; name="avatar"; filename="me.png"`, the value can be a quoted string.
+fn find_param(val: &str, param: &str) -> Option<String> {
+    let mut rest = val.split_once(';')?.1;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_ascii_whitespace());
+        if rest.is_empty() {
+            return None;
+        }
+
+        let key_end = rest.find(['=', ';']).unwrap_or(rest.len());
+        let key = rest[..key_end].trim();
+        rest = &rest[key_end..];
+
+        let value = match rest.strip_prefix('=').map(str::trim_start) {
+            Some(token) if token.starts_with('"') => {
+                let quoted = &token[1..];
+                let mut value = String::new();
+                let mut chars = quoted.char_indices();
+                rest = "";
+
+                while let Some((idx, c)) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            rest = &quoted[idx + 1..];
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+
+                value
+            }
+            Some(token) => {
+                let end = token.find(';').unwrap_or(token.len());
+                rest = &token[end..];
+                token[..end].trim().to_owned()
+            }
+            None => String::new(),
+        };
+
+        if key.eq_ignore_ascii_case(param) {
+            return Some(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(chunks: Vec<&'static str>) -> Request<Body> {
+        let chunks = chunks.into_iter().map(Ok::<_, std::io::Error>);
+        Request::builder()
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=\"XyZ\"")
+            .body(Body::wrap_stream(futures::stream::iter(chunks)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find_param() {
+        let val = r#"form-data; name="a;b"; filename="say \"hi\".txt"; size=3"#;
+        assert_eq!(find_param(val, "name").as_deref(), Some("a;b"));
+        assert_eq!(find_param(val, "filename").as_deref(), Some("say \"hi\".txt"));
+        assert_eq!(find_param(val, "SIZE").as_deref(), Some("3"));
+        assert_eq!(find_param(val, "type"), None);
+        assert_eq!(find_param("form-data", "name"), None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_multipart() {
+        let mut req = request(vec![
+            "preamble\r\n--X",
+            "yZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nHello\r\n--XyZ\r\n",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nline 1\r\n",
+            "line 2\r\n--X",
+            "yZ--\r\nepilogue",
+        ]);
+        let mut multipart = Multipart::new(&mut req).unwrap();
+
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("title"));
+        assert_eq!(field.file_name(), None);
+        assert_eq!(field.text().await.unwrap(), "Hello");
+
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.file_name(), Some("a.txt"));
+        assert_eq!(field.content_type(), Some("text/plain"));
+        assert_eq!(field.text().await.unwrap(), "line 1\r\nline 2");
+
+        assert!(multipart.next_field().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_limits_and_errors() {
+        let body = vec!["--XyZ\r\n\r\n0123456789\r\n--XyZ\r\n\r\nabc\r\n--XyZ--"];
+        let options = MultipartOptions {
+            max_field_size: Some(5),
+            max_total_size: None,
+        };
+
+        // The skipped content isn't limited by the field size.
+        let mut multipart = Multipart::with_options(&mut request(body.clone()), options.clone()).unwrap();
+        multipart.next_field().await.unwrap().unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.bytes().await.unwrap(), "abc");
+
+        let mut multipart = Multipart::with_options(&mut request(body), options).unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert!(matches!(field.bytes().await, Err(Error::RequestBodyTooLarge(5))));
+
+        let mut multipart = Multipart::new(&mut request(vec!["--XyZ\r\n\r\ntruncated"])).unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert!(matches!(field.bytes().await, Err(Error::ParseMultipart(_))));
+
+        let mut req = Request::builder()
+            .header(header::CONTENT_TYPE, "multipart/form-data")
+            .body(Body::empty())
+            .unwrap();
+        assert!(matches!(Multipart::new(&mut req), Err(Error::ParseMultipart(_))));
+    }
+}
@@ -1 +1,3 @@
 pub use crate::ext::RequestExt;
+#[cfg(feature = "cookies")]
+pub use crate::ext::ResponseExt;
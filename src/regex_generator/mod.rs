@@ -1,9 +1,189 @@
 use crate::Error;
-use lazy_static::lazy_static;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use regex::Regex;
+use std::ops::Range;
 
-lazy_static! {
-    static ref PATH_PARAMS_RE: Regex = Regex::new(r"(?s)(?::([^/]+))|(?:\*)").unwrap();
+const PARAM_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+const PARAM_SEGMENT_ENCODE_SET: &AsciiSet = &PARAM_ENCODE_SET.add(b'/');
+
+/// A `:name` param or a `*` wildcard in a route path, the name of a wildcard is `*` unless it's named e.g. `*filepath`.
+#[derive(Debug, PartialEq, Eq)]
+struct PathParam<'a> {
+    range: Range<usize>,
+    name: &'a str,
+    wildcard: bool,
+    // The regex in the parentheses after the name e.g. `\d+` of `:id(\d+)`.
+    constraint: Option<&'a str>,
+    // It's marked by a trailing `?` e.g. `:lang?` or `:id(\d+)?`.
+    optional: bool,
+}
+
+/// Finds the params of a route path, a param name runs until the next `/` or the `(` of its constraint.
+fn path_params(path: &str) -> Vec<PathParam<'_>> {
+    let bytes = path.as_bytes();
+    let mut params = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'*' => {
+                let name_len = bytes[pos + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                    .count();
+                let end = pos + 1 + name_len;
+
+                params.push(PathParam {
+                    range: pos..end,
+                    name: if name_len == 0 { "*" } else { &path[pos + 1..end] },
+                    wildcard: true,
+                    constraint: None,
+                    optional: false,
+                });
+                pos = end;
+            }
+            b':' => {
+                let name_start = pos + 1;
+                let name_end = path[name_start..]
+                    .find(['/', '('])
+                    .map_or(path.len(), |idx| name_start + idx);
+
+                if name_end == name_start {
+                    pos += 1;
+                    continue;
+                }
+
+                let constraint_end = if bytes.get(name_end) == Some(&b'(') {
+                    constraint_end(&path[name_end..]).map(|len| name_end + len)
+                } else {
+                    None
+                };
+
+                params.push(match constraint_end {
+                    Some(end) => {
+                        let optional = bytes.get(end) == Some(&b'?');
+                        PathParam {
+                            range: pos..end + optional as usize,
+                            name: &path[name_start..name_end],
+                            wildcard: false,
+                            constraint: Some(&path[name_end + 1..end - 1]),
+                            optional,
+                        }
+                    }
+                    None if bytes[name_end - 1] == b'?' && name_end - 1 > name_start => PathParam {
+                        range: pos..name_end,
+                        name: &path[name_start..name_end - 1],
+                        wildcard: false,
+                        constraint: None,
+                        optional: true,
+                    },
+                    // An unbalanced `(` is kept in the name as before the constraints were supported.
+                    None => {
+                        let end = path[name_start..].find('/').map_or(path.len(), |idx| name_start + idx);
+                        PathParam {
+                            range: pos..end,
+                            name: &path[name_start..end],
+                            wildcard: false,
+                            constraint: None,
+                            optional: false,
+                        }
+                    }
+                });
+                pos = params.last().unwrap().range.end;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    params
+}
+
+/// Returns the length of the parenthesized constraint at the start of `s` including the parentheses, the escaped chars and
+/// the chars in a class e.g. `[(]` are skipped.
+fn constraint_end(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_class = false;
+    let mut chars = s.char_indices();
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class => depth += 1,
+            ')' if !in_class => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Turns the capture groups of a constraint into the non-capturing ones, so the param values are still the only captures
+/// of the route regex.
+fn non_capturing(constraint: &str) -> String {
+    let mut regex_str = String::with_capacity(constraint.len() + 8);
+    let mut in_class = false;
+    let mut chars = constraint.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                regex_str.push(ch);
+                regex_str.extend(chars.next());
+                continue;
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class => {
+                let rest = chars.clone().collect::<String>();
+                if !rest.starts_with('?') {
+                    regex_str += "(?:";
+                    continue;
+                }
+
+                // A named group e.g. `(?P<name>...)`, unlike the look-around like assertions `(?<=...)`.
+                let name_start = if rest.starts_with("?P<") {
+                    Some(3)
+                } else if rest.starts_with("?<") && !rest.starts_with("?<=") && !rest.starts_with("?<!") {
+                    Some(2)
+                } else {
+                    None
+                };
+
+                if let Some(name_end) = name_start.and_then(|start| rest[start..].find('>').map(|idx| start + idx)) {
+                    regex_str += "(?:";
+                    for _ in 0..rest[..=name_end].chars().count() {
+                        chars.next();
+                    }
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        regex_str.push(ch);
+    }
+
+    regex_str
 }
 
 fn generate_common_regex_str(path: &str) -> (String, Vec<String>) {
@@ -12,21 +192,37 @@ fn generate_common_regex_str(path: &str) -> (String, Vec<String>) {
 
     let mut pos: usize = 0;
 
-    for caps in PATH_PARAMS_RE.captures_iter(path) {
-        let whole = caps.get(0).unwrap();
+    for param in path_params(path) {
+        let path_s = &path[pos..param.range.start];
 
-        let path_s = &path[pos..whole.start()];
-        regex_str += &regex::escape(path_s);
+        // The trailing slash appended by the router isn't captured by a named wildcard at the end of the path.
+        let tail = param.wildcard && param.name != "*" && &path[param.range.end..] == "/";
 
-        if whole.as_str() == "*" {
-            regex_str += r"(.*)";
-            param_names.push("*".to_owned());
-        } else {
-            regex_str += r"([^/]+)";
-            param_names.push(caps.get(1).unwrap().as_str().to_owned());
+        let param_re = match param.constraint {
+            _ if tail => r"(.*?)/?".to_owned(),
+            _ if param.wildcard => r"(.*)".to_owned(),
+            Some(constraint) => format!("({})", non_capturing(constraint)),
+            None => r"([^/]+)".to_owned(),
+        };
+
+        // The slash before an optional param is optional too, so `/articles/:lang?/:slug` matches `/articles/foo`.
+        match path_s.strip_suffix('/') {
+            Some(path_s) if param.optional => {
+                regex_str += &regex::escape(path_s);
+                regex_str += &format!("(?:/{})?", param_re);
+            }
+            _ if param.optional => {
+                regex_str += &regex::escape(path_s);
+                regex_str += &format!("{}?", param_re);
+            }
+            _ => {
+                regex_str += &regex::escape(path_s);
+                regex_str += &param_re;
+            }
         }
+        param_names.push(param.name.to_owned());
 
-        pos = whole.end();
+        pos = if tail { path.len() } else { param.range.end };
     }
 
     let left_over_path_s = &path[pos..];
@@ -42,6 +238,43 @@ pub(crate) fn generate_exact_match_regex(path: &str) -> crate::Result<(Regex, Ve
     Ok((re, params))
 }
 
+/// Fills the params of the path with the percent-encoded values, a wildcard value may span several segments. A missing optional
+/// param is left out with the slash before it.
+pub(crate) fn fill_path_params<'a, F>(path: &str, mut value_of: F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Option<&'a str>,
+{
+    let mut filled = String::with_capacity(path.len());
+    let mut pos: usize = 0;
+
+    for param in path_params(path) {
+        filled += &path[pos..param.range.start];
+
+        let encode_set = if param.wildcard {
+            PARAM_ENCODE_SET
+        } else {
+            PARAM_SEGMENT_ENCODE_SET
+        };
+        let value = match value_of(param.name) {
+            Some(value) => value,
+            None if param.optional => {
+                if filled.ends_with('/') && pos < param.range.start {
+                    filled.pop();
+                }
+                pos = param.range.end;
+                continue;
+            }
+            None => return Err(param.name.to_owned()),
+        };
+        filled.extend(utf8_percent_encode(value, encode_set));
+
+        pos = param.range.end;
+    }
+
+    filled += &path[pos..];
+    Ok(filled)
+}
+
 #[allow(dead_code)]
 pub(crate) fn generate_prefix_match_regex(path: &str) -> crate::Result<(Regex, Vec<String>)> {
     let (common_regex_str, params) = generate_common_regex_str(path);
@@ -125,4 +358,104 @@ mod tests {
         let r = generate_common_regex_str(path);
         assert_eq!(r, (r"/users/(.*)(.*)".to_owned(), vec!["*".to_owned(), "*".to_owned()]));
     }
+
+    #[test]
+    fn test_generate_common_regex_str_constraints() {
+        let path = r"/users/:id(\d+)";
+        let r = generate_common_regex_str(path);
+        assert_eq!(r, (r"/users/(\d+)".to_owned(), vec!["id".to_owned()]));
+
+        let path = r"/files/:name([a-z-]+).:ext(txt|md)/*";
+        let r = generate_common_regex_str(path);
+        assert_eq!(
+            r,
+            (
+                r"/files/([a-z-]+)\.(txt|md)/(.*)".to_owned(),
+                vec!["name".to_owned(), "ext".to_owned(), "*".to_owned()]
+            )
+        );
+
+        let path = r"/v:ver((?P<major>\d+)(\.\d+)?)/:op([()]+)";
+        let r = generate_common_regex_str(path);
+        assert_eq!(
+            r,
+            (
+                r"/v((?:\d+)(?:\.\d+)?)/([()]+)".to_owned(),
+                vec!["ver".to_owned(), "op".to_owned()]
+            )
+        );
+
+        let path = r"/users/:id(\d+";
+        let r = generate_common_regex_str(path);
+        assert_eq!(r, (r"/users/([^/]+)".to_owned(), vec![r"id(\d+".to_owned()]));
+    }
+
+    #[test]
+    fn test_generate_common_regex_str_optional() {
+        let path = "/articles/:lang?/:slug";
+        let r = generate_common_regex_str(path);
+        assert_eq!(
+            r,
+            (
+                r"/articles(?:/([^/]+))?/([^/]+)".to_owned(),
+                vec!["lang".to_owned(), "slug".to_owned()]
+            )
+        );
+
+        let path = r"/items/:id(\d+)?/";
+        let r = generate_common_regex_str(path);
+        assert_eq!(r, (r"/items(?:/(\d+))?/".to_owned(), vec!["id".to_owned()]));
+
+        let path = "/v:ver?/:?";
+        let r = generate_common_regex_str(path);
+        assert_eq!(
+            r,
+            (r"/v([^/]+)?/([^/]+)".to_owned(), vec!["ver".to_owned(), "?".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_generate_common_regex_str_named_wildcard() {
+        let path = "/static/*filepath/";
+        let r = generate_common_regex_str(path);
+        assert_eq!(r, (r"/static/(.*?)/?".to_owned(), vec!["filepath".to_owned()]));
+
+        let path = "/repos/*repo_path/issues/:id/";
+        let r = generate_common_regex_str(path);
+        assert_eq!(
+            r,
+            (
+                r"/repos/(.*)/issues/([^/]+)/".to_owned(),
+                vec!["repo_path".to_owned(), "id".to_owned()]
+            )
+        );
+
+        let path = "/files/*.txt";
+        let r = generate_common_regex_str(path);
+        assert_eq!(r, (r"/files/(.*)\.txt".to_owned(), vec!["*".to_owned()]));
+    }
+
+    #[test]
+    fn test_fill_path_params() {
+        let params = [("id", "a b/c"), ("*", "docs/a?b")];
+        let value_of = |name: &str| params.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+
+        let r = fill_path_params("/users/:id/files/*", value_of);
+        assert_eq!(r, Ok("/users/a%20b%2Fc/files/docs/a%3Fb".to_owned()));
+
+        let r = fill_path_params("/users/:id/posts/:post_id", value_of);
+        assert_eq!(r, Err("post_id".to_owned()));
+
+        let r = fill_path_params(r"/users/:id([^/]+)/files/*", value_of);
+        assert_eq!(r, Ok("/users/a%20b%2Fc/files/docs/a%3Fb".to_owned()));
+
+        let r = fill_path_params("/users/:id/:lang?/posts/:post_id?", value_of);
+        assert_eq!(r, Ok("/users/a%20b%2Fc/posts".to_owned()));
+
+        let r = fill_path_params("/users/:id/files/*filepath", |name| value_of(name).or(value_of("*")));
+        assert_eq!(r, Ok("/users/a%20b%2Fc/files/docs/a%3Fb".to_owned()));
+
+        let r = fill_path_params("/about", value_of);
+        assert_eq!(r, Ok("/about".to_owned()));
+    }
 }
@@ -0,0 +1,191 @@
+use hyper::header::{self, HeaderValue};
+use hyper::{Response, StatusCode};
+
+/// Converts a value returned by a route handler into a response, so a handler can return e.g. a `String` or a
+/// `(StatusCode, &'static str)` tuple instead of building a `Response<B>` every time.
+///
+/// It's implemented for:
+///
+/// * `Response<B>` which is returned as it is.
+/// * `&'static str` and `String` which become a `200 OK` response with a `text/plain; charset=utf-8` body.
+/// * `StatusCode` which becomes a response with the status and an empty body.
+/// * `(StatusCode, T)` where `T: IntoResponse<B>` which overrides the status of the `T` response.
+/// * [`Json<T>`](./struct.Json.html) if the `serde` feature is enabled.
+///
+/// A handler which never returns `Ok` needs a type annotation e.g. `Err::<Response<Body>, _>(err)`, as the response type
+/// can't be inferred from the handler then.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::Router;
+/// use hyper::{Body, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/", |_| async move { Ok("Home page") })
+///     .get("/about", |_| async move { Ok(format!("Version {}", 1)) })
+///     .post("/users", |_| async move { Ok((StatusCode::CREATED, "Created")) })
+///     .delete("/users/:id", |_| async move { Ok(StatusCode::NO_CONTENT) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+///
+/// The trait can be implemented for the application's own types:
+///
+/// ```
+/// use routerify::{IntoResponse, Router};
+/// use hyper::{Body, Response, StatusCode};
+/// # use std::convert::Infallible;
+///
+/// struct Html(String);
+///
+/// impl IntoResponse<Body> for Html {
+///     fn into_response(self) -> Response<Body> {
+///         Response::builder()
+///             .header("content-type", "text/html; charset=utf-8")
+///             .body(Body::from(self.0))
+///             .unwrap()
+///     }
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/", |_| async move { Ok(Html("<h1>Home page</h1>".to_owned())) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub trait IntoResponse<B> {
+    /// Creates the response.
+    fn into_response(self) -> Response<B>;
+}
+
+impl<B> IntoResponse<B> for Response<B> {
+    fn into_response(self) -> Response<B> {
+        self
+    }
+}
+
+impl<B: From<&'static str>> IntoResponse<B> for &'static str {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "text/plain; charset=utf-8")
+    }
+}
+
+impl<B: From<String>> IntoResponse<B> for String {
+    fn into_response(self) -> Response<B> {
+        with_content_type(B::from(self), "text/plain; charset=utf-8")
+    }
+}
+
+impl<B: Default> IntoResponse<B> for StatusCode {
+    fn into_response(self) -> Response<B> {
+        let mut resp = Response::new(B::default());
+        *resp.status_mut() = self;
+        resp
+    }
+}
+
+impl<B, T: IntoResponse<B>> IntoResponse<B> for (StatusCode, T) {
+    fn into_response(self) -> Response<B> {
+        let mut resp = self.1.into_response();
+        *resp.status_mut() = self.0;
+        resp
+    }
+}
+
+/// A wrapper which serializes the value into an `application/json` response body.
+///
+/// A value which can't be serialized e.g. a map with non-string keys results in a `500 Internal Server Error` response.
+///
+/// This type is available only if the `serde` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Json, Router};
+/// use hyper::{Body, StatusCode};
+/// use serde::Serialize;
+/// # use std::convert::Infallible;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let router = Router::builder()
+///     .get("/users/me", |_| async move { Ok(Json(User { id: 1, name: "alice".to_owned() })) })
+///     .post("/users", |_| async move { Ok((StatusCode::CREATED, Json(User { id: 2, name: "bob".to_owned() }))) })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<B: From<Vec<u8>>, T: serde::Serialize> IntoResponse<B> for Json<T> {
+    fn into_response(self) -> Response<B> {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => with_content_type(B::from(body), "application/json"),
+            Err(_) => {
+                let mut resp = Response::new(B::from(Vec::new()));
+                *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                resp
+            }
+        }
+    }
+}
+
+fn with_content_type<B>(body: B, content_type: &'static str) -> Response<B> {
+    let mut resp = Response::new(body);
+    resp.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Body;
+
+    #[test]
+    fn test_into_response() {
+        let resp: Response<Body> = "Hello".into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+
+        let resp: Response<Body> = (StatusCode::CREATED, "Hello".to_owned()).into_response();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+
+        let resp: Response<Body> = StatusCode::NO_CONTENT.into_response();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(resp.headers().get(header::CONTENT_TYPE).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_into_response() {
+        let resp: Response<Body> = Json(vec![1, 2, 3]).into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(vec![1], 1);
+        let resp: Response<Body> = Json(map).into_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
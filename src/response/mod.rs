@@ -0,0 +1,150 @@
+//! Helpers to create the common responses with the `hyper::Body` type without going through `Response::builder()`.
+//!
+//! They set the `Content-Type` and the `Content-Length` headers and return an [`Error`](../enum.Error.html) instead of
+//! panicking if the response couldn't be created.
+//!
+//! A handler can also return any type which implements the [`IntoResponse`](./trait.IntoResponse.html) trait e.g. a `String`
+//! or a `(StatusCode, &'static str)` tuple.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::{response, Router};
+//! use hyper::{Body, StatusCode};
+//!
+//! # fn run() -> Router<Body, routerify::Error> {
+//! let router = Router::builder()
+//!     .get("/", |_| async move { response::text(StatusCode::OK, "Home page") })
+//!     .get("/old", |_| async move { response::redirect(StatusCode::MOVED_PERMANENTLY, "/") })
+//!     .delete("/users/:id", |_| async move { response::empty(StatusCode::NO_CONTENT) })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use crate::Error;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Response, StatusCode};
+
+pub use self::into_response::IntoResponse;
+#[cfg(feature = "serde")]
+pub use self::into_response::Json;
+pub use self::negotiated::{negotiate, Negotiated};
+
+mod into_response;
+mod negotiated;
+
+/// Creates a response with a `text/plain; charset=utf-8` body.
+pub fn text<T: Into<String>>(status: StatusCode, text: T) -> crate::Result<Response<Body>> {
+    with_body(status, "text/plain; charset=utf-8", text.into().into_bytes())
+}
+
+/// Creates a response with an `application/json` body serialized from the specified value.
+///
+/// This function is available only if the `serde` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{response, Router};
+/// use hyper::{Body, StatusCode};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .get("/users/me", |_| async move {
+///         let user = User { id: 1, name: "alice".to_owned() };
+///         response::json(StatusCode::OK, &user)
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[cfg(feature = "serde")]
+pub fn json<T: serde::Serialize + ?Sized>(status: StatusCode, value: &T) -> crate::Result<Response<Body>> {
+    let body = serde_json::to_vec(value).map_err(Error::SerializeJson)?;
+    with_body(status, "application/json", body)
+}
+
+/// Creates a redirect response to the specified location, the status must be a `3xx` one.
+pub fn redirect<L: AsRef<str>>(status: StatusCode, location: L) -> crate::Result<Response<Body>> {
+    if !status.is_redirection() {
+        return Err(Error::InvalidRedirectStatus(status));
+    }
+
+    Response::builder()
+        .status(status)
+        .header(header::LOCATION, location.as_ref())
+        .header(header::CONTENT_LENGTH, 0)
+        .body(Body::empty())
+        .map_err(Error::CreateResponse)
+}
+
+/// Creates a response without a body.
+pub fn empty(status: StatusCode) -> crate::Result<Response<Body>> {
+    let mut builder = Response::builder().status(status);
+
+    // These responses must not have a `Content-Length` header.
+    if !status.is_informational() && status != StatusCode::NO_CONTENT && status != StatusCode::NOT_MODIFIED {
+        builder = builder.header(header::CONTENT_LENGTH, 0);
+    }
+
+    builder.body(Body::empty()).map_err(Error::CreateResponse)
+}
+
+fn with_body(status: StatusCode, content_type: &'static str, body: Vec<u8>) -> crate::Result<Response<Body>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, HeaderValue::from_static(content_type))
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+        .map_err(Error::CreateResponse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_helpers() {
+        let resp = text(StatusCode::OK, "Hello").unwrap();
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert_eq!(resp.headers()[header::CONTENT_LENGTH], "5");
+
+        let resp = redirect(StatusCode::FOUND, "/login").unwrap();
+        assert_eq!(resp.status(), StatusCode::FOUND);
+        assert_eq!(resp.headers()[header::LOCATION], "/login");
+
+        assert!(redirect(StatusCode::OK, "/login").is_err());
+        assert!(redirect(StatusCode::FOUND, "/login\n").is_err());
+
+        assert_eq!(
+            empty(StatusCode::ACCEPTED).unwrap().headers()[header::CONTENT_LENGTH],
+            "0"
+        );
+        assert!(empty(StatusCode::NO_CONTENT)
+            .unwrap()
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_response() {
+        let resp = json(StatusCode::CREATED, &vec![1, 2, 3]).unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(resp.headers()[header::CONTENT_LENGTH], "7");
+    }
+}
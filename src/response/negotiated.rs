@@ -0,0 +1,152 @@
+use crate::ext::RequestExt;
+use crate::Error;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Request, Response, StatusCode};
+
+/// Selects the media type the client prefers the most among the ones the server can produce, based on the `Accept` request
+/// headers. The server's order breaks the ties.
+///
+/// It returns `None` if the client accepts none of them. It's a shorthand for
+/// [`req.accepts().negotiate(available)`](../struct.Accept.html#method.negotiate).
+///
+/// **Note:** The response should have the `Vary: Accept` header if its content depends on the selected media type, the
+/// [`Negotiated`](./struct.Negotiated.html) responder adds it.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{response, Router};
+/// use hyper::{Body, Response, StatusCode};
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .get("/", |req| async move {
+///         let mut resp = match response::negotiate(&req, &["text/html", "text/plain"]) {
+///             Some("text/html") => Response::builder()
+///                 .header("content-type", "text/html; charset=utf-8")
+///                 .body(Body::from("<h1>Home page</h1>"))
+///                 .unwrap(),
+///             _ => response::text(StatusCode::OK, "Home page")?,
+///         };
+///         resp.headers_mut().insert("vary", "accept".parse().unwrap());
+///
+///         Ok(resp)
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+pub fn negotiate<'a, B>(req: &Request<B>, available: &[&'a str]) -> Option<&'a str> {
+    req.accepts().negotiate(available)
+}
+
+/// A responder which holds the variants of a response in the different media types and selects the one the client prefers
+/// the most, based on the `Accept` request headers.
+///
+/// The variants are tried in the order they are added if the client prefers them equally e.g. it accepts anything. The
+/// response has the `Vary: Accept` header, so the caches store the variants separately. If the client accepts none of the
+/// variants, an [`Error::NotAcceptable`](../enum.Error.html#variant.NotAcceptable) error is returned which the default error
+/// handler turns into `406 Not Acceptable`.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::response::Negotiated;
+/// use routerify::Router;
+/// use hyper::{Body, StatusCode};
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .get("/users/me", |req| async move {
+///         Negotiated::new(StatusCode::OK)
+///             .html("<p>alice</p>")
+///             .text("alice")
+///             .respond(&req)
+///     })
+///     .build()
+///     .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug)]
+pub struct Negotiated {
+    status: StatusCode,
+    variants: Vec<(&'static str, crate::Result<Vec<u8>>)>,
+}
+
+impl Negotiated {
+    /// Creates a responder without any variants, the selected variant is responded with the specified status.
+    pub fn new(status: StatusCode) -> Negotiated {
+        Negotiated {
+            status,
+            variants: Vec::new(),
+        }
+    }
+
+    /// Adds an `application/json` variant serialized from the specified value.
+    ///
+    /// If the value couldn't be serialized, the [`respond`](#method.respond) method returns the error if the variant is
+    /// selected.
+    ///
+    /// This method is available only if the `serde` feature is enabled.
+    #[cfg(feature = "serde")]
+    pub fn json<T: serde::Serialize + ?Sized>(mut self, value: &T) -> Negotiated {
+        let body = serde_json::to_vec(value).map_err(Error::SerializeJson);
+        self.variants.push(("application/json", body));
+        self
+    }
+
+    /// Adds a `text/html; charset=utf-8` variant.
+    pub fn html<T: Into<String>>(mut self, html: T) -> Negotiated {
+        self.variants
+            .push(("text/html; charset=utf-8", Ok(html.into().into_bytes())));
+        self
+    }
+
+    /// Adds a `text/plain; charset=utf-8` variant.
+    pub fn text<T: Into<String>>(mut self, text: T) -> Negotiated {
+        self.variants
+            .push(("text/plain; charset=utf-8", Ok(text.into().into_bytes())));
+        self
+    }
+
+    /// Selects the variant the client prefers the most and creates the response.
+    pub fn respond<B>(self, req: &Request<B>) -> crate::Result<Response<Body>> {
+        let available = self
+            .variants
+            .iter()
+            .map(|(content_type, _)| *content_type)
+            .collect::<Vec<_>>();
+
+        let content_type = match negotiate(req, &available) {
+            Some(content_type) => content_type,
+            None => {
+                return Err(Error::NotAcceptable(
+                    available.iter().map(|content_type| essence(content_type)).collect(),
+                ))
+            }
+        };
+
+        let body = self
+            .variants
+            .into_iter()
+            .find(|(variant, _)| *variant == content_type)
+            .map(|(_, body)| body)
+            .expect("The negotiated variant must exist")?;
+
+        Response::builder()
+            .status(self.status)
+            .header(header::CONTENT_TYPE, HeaderValue::from_static(content_type))
+            .header(header::CONTENT_LENGTH, body.len())
+            .header(header::VARY, HeaderValue::from_static("accept"))
+            .body(Body::from(body))
+            .map_err(Error::CreateResponse)
+    }
+}
+
+fn essence(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or_default().to_owned()
+}
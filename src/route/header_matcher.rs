@@ -0,0 +1,116 @@
+use crate::Error;
+use hyper::header::{HeaderMap, HeaderName};
+use regex::Regex;
+use std::fmt::{self, Debug, Formatter};
+
+/// Represents a header criteria of a route, a request is routed to the route only if it has the header with a matching value.
+///
+/// A matcher can be created from a `(name, value)` tuple for an exact value match, or by the [`regex`](#method.regex) method.
+/// Please refer to the [`add_with_header`](./struct.RouterBuilder.html#method.add_with_header) method for an example.
+#[derive(Debug, Clone)]
+pub struct HeaderMatcher {
+    name: String,
+    value: String,
+    is_regex: bool,
+}
+
+impl HeaderMatcher {
+    /// Creates a matcher which matches the header value exactly.
+    pub fn exact<N: Into<String>, V: Into<String>>(name: N, value: V) -> HeaderMatcher {
+        HeaderMatcher {
+            name: name.into(),
+            value: value.into(),
+            is_regex: false,
+        }
+    }
+
+    /// Creates a matcher which matches the header value against a regex pattern. The pattern must match the whole value.
+    pub fn regex<N: Into<String>, P: Into<String>>(name: N, pattern: P) -> HeaderMatcher {
+        HeaderMatcher {
+            name: name.into(),
+            value: pattern.into(),
+            is_regex: true,
+        }
+    }
+
+    pub(crate) fn compile(self) -> crate::Result<CompiledHeaderMatcher> {
+        let name = HeaderName::from_bytes(self.name.as_bytes())
+            .map_err(|_| Error::InvalidHeaderMatcher(format!("Invalid header name: {}", self.name)))?;
+
+        let regex = if self.is_regex {
+            let re = Regex::new(format!("^(?:{})$", self.value).as_str())
+                .map_err(|e| Error::InvalidHeaderMatcher(format!("Invalid value pattern for {}: {}", self.name, e)))?;
+            Some(re)
+        } else {
+            None
+        };
+
+        Ok(CompiledHeaderMatcher {
+            name,
+            value: self.value,
+            regex,
+        })
+    }
+}
+
+impl<N: Into<String>, V: Into<String>> From<(N, V)> for HeaderMatcher {
+    fn from((name, value): (N, V)) -> Self {
+        HeaderMatcher::exact(name, value)
+    }
+}
+
+pub(crate) struct CompiledHeaderMatcher {
+    name: HeaderName,
+    value: String,
+    regex: Option<Regex>,
+}
+
+impl CompiledHeaderMatcher {
+    /// Any of the values of a repeated header can match.
+    pub(crate) fn is_match(&self, headers: &HeaderMap) -> bool {
+        headers.get_all(&self.name).iter().any(|val| match self.regex {
+            Some(ref re) => val.to_str().map(|val| re.is_match(val)).unwrap_or(false),
+            None => val.as_bytes() == self.value.as_bytes(),
+        })
+    }
+}
+
+impl Debug for CompiledHeaderMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.regex {
+            Some(ref re) => write!(f, "{{ name: {:?}, regex: {:?} }}", self.name, re),
+            None => write!(f, "{{ name: {:?}, value: {:?} }}", self.name, self.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_header_matcher() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-event", HeaderValue::from_static("push"));
+
+        let matcher = HeaderMatcher::from(("X-GitHub-Event", "push")).compile().unwrap();
+        assert!(matcher.is_match(&headers));
+
+        let matcher = HeaderMatcher::exact("X-GitHub-Event", "pull_request")
+            .compile()
+            .unwrap();
+        assert!(!matcher.is_match(&headers));
+
+        let matcher = HeaderMatcher::regex("X-GitHub-Event", "push|pull_request")
+            .compile()
+            .unwrap();
+        assert!(matcher.is_match(&headers));
+
+        let matcher = HeaderMatcher::regex("X-GitHub-Event", "pu").compile().unwrap();
+        assert!(!matcher.is_match(&headers));
+
+        assert!(HeaderMatcher::exact("X Event", "push").compile().is_err());
+        assert!(HeaderMatcher::regex("X-Event", "(push").compile().is_err());
+    }
+}
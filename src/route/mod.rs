@@ -1,27 +1,63 @@
-use crate::helpers;
+use crate::body;
+use crate::constants;
+use crate::ext::RequestExt;
+use crate::helpers::{self, RawPath};
 use crate::regex_generator::generate_exact_match_regex;
+use crate::response::IntoResponse;
+use crate::router::ErrHandler;
 use crate::types::{RequestMeta, RouteParams};
 use crate::Error;
-use hyper::{body::HttpBody, Method, Request, Response};
+use futures::TryFutureExt;
+use hyper::{
+    body::HttpBody,
+    header::{self, HeaderMap, HeaderValue},
+    Method, Request, Response,
+};
 use regex::Regex;
+use std::any::Any;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
-type Handler<B, E> = Box<dyn FnMut(Request<hyper::Body>) -> HandlerReturn<B, E> + Send + Sync + 'static>;
-type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+pub(crate) use self::header_matcher::CompiledHeaderMatcher;
+pub use self::header_matcher::HeaderMatcher;
+pub(crate) use self::mount::service_handler;
+pub use self::mount::MountServiceOptions;
+pub(crate) use self::query_matcher::CompiledQueryMatcher;
+pub use self::query_matcher::QueryMatcher;
+
+mod header_matcher;
+mod mount;
+mod query_matcher;
+
+type HandlerFn<B, E, ReqB> = Box<dyn Fn(Request<ReqB>) -> HandlerFnReturn<B, E> + Send + Sync + 'static>;
+type HandlerFnReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Send + 'static>;
+
+type ServiceFn<B, ReqB> = Box<dyn Fn(Request<ReqB>) -> ServiceFnReturn<B> + Send + Sync + 'static>;
+type ServiceFnReturn<B> =
+    Box<dyn Future<Output = Result<Response<B>, Box<dyn std::error::Error + Send + Sync + 'static>>> + Send + 'static>;
+
+pub(crate) enum Handler<B, E, ReqB> {
+    Handler(HandlerFn<B, E, ReqB>),
+    // A mounted service, its errors don't have to be converted into the router's error type.
+    Service(ServiceFn<B, ReqB>),
+}
 
 /// Represents a single route.
 ///
 /// A route consists of a path, http method type(s) and a handler. It shouldn't be created directly, use [RouterBuilder](./struct.RouterBuilder.html) methods
 /// to create a route.
 ///
-/// This `Route<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Route<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -43,100 +79,385 @@ type HandlerReturn<B, E> = Box<dyn Future<Output = Result<Response<B>, E>> + Sen
 /// # }
 /// # run();
 /// ```
-pub struct Route<B, E> {
+pub struct Route<B, E, ReqB = hyper::Body> {
     pub(crate) path: String,
     pub(crate) regex: Regex,
     route_params: Vec<String>,
     // Make it an option so that when a router is used to scope in another router,
     // It can be extracted out by 'opt.take()' without taking the whole router's ownership.
-    pub(crate) handler: Option<Handler<B, E>>,
+    pub(crate) handler: Option<Handler<B, E, ReqB>>,
     pub(crate) methods: Vec<Method>,
+    // The route matches every method, including the non-standard ones, if it's set.
+    pub(crate) all_methods: bool,
+    pub(crate) header_matcher: Option<CompiledHeaderMatcher>,
+    pub(crate) query_matcher: Option<CompiledQueryMatcher>,
+    // The lowercased media type of the request body the route consumes e.g. `application/json` or `text/*`.
+    pub(crate) content_type: Option<String>,
+    pub(crate) priority: i32,
+    // The path without the trailing slash appended by the router, it's shared with the requests handled by the route.
+    template: Arc<str>,
+    // The default 404 and OPTIONS routes are added by the router, they're reported as unmatched in the request metrics.
+    pub(crate) builtin: bool,
+    // The segments of the path if it consists of the static and the `:param` segments only, otherwise the route is matched by the regex.
+    pub(crate) segments: Option<Vec<Segment>>,
+    // It handles the errors of the route handler instead of the root router's error handler, it's either added to the route
+    // or inherited from the scoped router.
+    pub(crate) err_handler: Option<Arc<ErrHandler<B>>>,
+    // The accumulated path of the scopes the route is mounted on, it's empty for the routes of the root router.
+    pub(crate) mount_prefix: Arc<str>,
+    // The name to generate the URL of the route by `Router::url_for`.
+    pub(crate) name: Option<String>,
+    // Whether the route is added with a trailing slash, it's `None` if the path ends with a wildcard which matches both forms.
+    pub(crate) trailing_slash: Option<bool>,
+    // It's added by `RouterBuilder::not_found`, it handles the requests not matched by any other route of its router.
+    pub(crate) fallback: bool,
+    // The maximum time the handler can take, it overrides the root router's timeout.
+    pub(crate) timeout: Option<Duration>,
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Route<B, E> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Static(String),
+    Param,
+    // It's always the last segment.
+    Wildcard,
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Route<B, E, ReqB>
+{
     pub(crate) fn new_with_boxed_handler<P: Into<String>>(
         path: P,
         methods: Vec<Method>,
-        handler: Handler<B, E>,
-    ) -> crate::Result<Route<B, E>> {
+        handler: Handler<B, E, ReqB>,
+    ) -> crate::Result<Route<B, E, ReqB>> {
         let path = path.into();
         let (re, params) = generate_exact_match_regex(path.as_str())?;
+        let segments = parse_segments(path.as_str());
+        let template = match path.strip_suffix('/') {
+            Some(stripped) if !stripped.is_empty() => Arc::from(stripped),
+            _ => Arc::from(path.as_str()),
+        };
 
         Ok(Route {
+            template,
             path,
             regex: re,
             route_params: params,
             handler: Some(handler),
             methods,
+            all_methods: false,
+            header_matcher: None,
+            query_matcher: None,
+            content_type: None,
+            priority: 0,
+            builtin: false,
+            segments,
+            err_handler: None,
+            mount_prefix: Arc::from(""),
+            name: None,
+            fallback: false,
+            trailing_slash: None,
+            timeout: None,
         })
     }
 
-    pub(crate) fn new<P, H, R>(path: P, methods: Vec<Method>, mut handler: H) -> crate::Result<Route<B, E>>
+    pub(crate) fn new<P, H, R, T>(path: P, methods: Vec<Method>, handler: H) -> crate::Result<Route<B, E, ReqB>>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let handler: HandlerFn<B, E, ReqB> =
+            Box::new(move |req: Request<ReqB>| Box::new(handler(req).map_ok(IntoResponse::into_response)));
+        Route::new_with_boxed_handler(path, methods, Handler::Handler(handler))
+    }
+
+    pub(crate) fn new_with_service<P, S>(
+        path: P,
+        service: S,
+        options: MountServiceOptions,
+    ) -> crate::Result<Route<B, E, ReqB>>
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        S: hyper::service::Service<Request<ReqB>, Response = Response<B>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
     {
-        let handler: Handler<B, E> = Box::new(move |req: Request<hyper::Body>| Box::new(handler(req)));
-        Route::new_with_boxed_handler(path, methods, handler)
+        let handler = Handler::Service(service_handler(service, options));
+        let mut route = Route::new_with_boxed_handler(path, Vec::new(), handler)?;
+        // The service handles every method itself.
+        route.all_methods = true;
+        Ok(route)
+    }
+
+    /// Returns the path without the trailing slash appended by the router, e.g. to label the requests by the route.
+    pub(crate) fn template(&self) -> &Arc<str> {
+        &self.template
+    }
+
+    /// Whether it's a `not_found` route or the `/*` route for all methods, which handles the requests not matched by any other
+    /// route e.g. the default 404 route.
+    pub(crate) fn is_fallback(&self) -> bool {
+        self.fallback || (self.path == "/*" && self.methods.as_slice() == &constants::ALL_POSSIBLE_HTTP_METHODS[..])
+    }
+
+    /// Whether it's tried after all the other routes, including the dynamic ones.
+    pub(crate) fn is_catch_all(&self) -> bool {
+        self.path == "/*" || self.fallback
     }
 
     pub(crate) fn is_match_method(&self, method: &Method) -> bool {
-        self.methods.contains(method)
+        self.all_methods || self.methods.contains(method)
+    }
+
+    pub(crate) fn is_match_header(&self, headers: &HeaderMap) -> bool {
+        self.header_matcher
+            .as_ref()
+            .map(|matcher| matcher.is_match(headers))
+            .unwrap_or(true)
+    }
+
+    pub(crate) fn is_match_content_type(&self, req: &Request<ReqB>) -> bool {
+        let content_type = match self.content_type {
+            Some(ref content_type) => content_type,
+            None => return true,
+        };
+
+        let media_type = body::media_type(req);
+        match content_type.strip_suffix('*') {
+            Some(prefix) => media_type.starts_with(prefix) && media_type.len() > prefix.len(),
+            None => media_type == *content_type,
+        }
+    }
+
+    // The query string is only parsed if the route has a query matcher.
+    pub(crate) fn is_match_query(&self, req: &Request<ReqB>) -> bool {
+        self.query_matcher
+            .as_ref()
+            .map(|matcher| matcher.is_match(req.query_params()))
+            .unwrap_or(true)
     }
 
     pub(crate) async fn process(
-        &mut self,
+        &self,
         target_path: &str,
-        mut req: Request<hyper::Body>,
+        raw_path: RawPath<'_>,
+        mut req: Request<ReqB>,
+        default_timeout: Option<Duration>,
     ) -> crate::Result<Response<B>> {
-        self.push_req_meta(target_path, &mut req)?;
+        self.push_req_meta(target_path, raw_path, &mut req)?;
 
         let handler = self
             .handler
-            .as_mut()
+            .as_ref()
             .expect("A router can not be used after mounting into another router");
 
-        Pin::from(handler(req))
-            .await
-            .map_err(|e| Error::HandleRequest(e.into(), target_path.into()))
+        // A HEAD request is only matched by a route without HEAD if it's a GET route answering it.
+        let strip_body = req.method() == Method::HEAD && !self.is_match_method(&Method::HEAD);
+
+        let fut = async move {
+            match handler {
+                Handler::Handler(handler) => Pin::from(handler(req))
+                    .await
+                    .map_err(|e| Error::HandleRequest(e.into(), target_path.into())),
+                Handler::Service(handler) => Pin::from(handler(req))
+                    .await
+                    .map_err(|e| Error::HandleRequest(e, target_path.into())),
+            }
+        };
+
+        let res = match self.timeout.or(default_timeout) {
+            // The handler future is dropped once the timeout elapses.
+            Some(timeout) => tokio::time::timeout(timeout, fut)
+                .await
+                .unwrap_or(Err(Error::HandlerTimeout(timeout))),
+            None => fut.await,
+        }?;
+
+        if strip_body {
+            Ok(strip_response_body(res))
+        } else {
+            Ok(res)
+        }
     }
 
-    fn push_req_meta(&self, target_path: &str, req: &mut Request<hyper::Body>) -> crate::Result<()> {
-        self.update_req_meta(req, self.generate_req_meta(target_path)?);
+    fn push_req_meta(&self, target_path: &str, raw_path: RawPath<'_>, req: &mut Request<ReqB>) -> crate::Result<()> {
+        self.update_req_meta(req, self.generate_req_meta(target_path, raw_path)?);
         Ok(())
     }
 
-    fn update_req_meta(&self, req: &mut Request<hyper::Body>, req_meta: RequestMeta) {
+    fn update_req_meta(&self, req: &mut Request<ReqB>, req_meta: RequestMeta) {
         helpers::update_req_meta_in_extensions(req.extensions_mut(), req_meta);
     }
 
-    fn generate_req_meta(&self, target_path: &str) -> crate::Result<RequestMeta> {
+    fn generate_req_meta(&self, target_path: &str, raw_path: RawPath<'_>) -> crate::Result<RequestMeta> {
         let route_params_list = &self.route_params;
         let ln = route_params_list.len();
 
         let mut route_params = RouteParams::with_capacity(ln);
 
         if ln > 0 {
-            if let Some(caps) = self.regex.captures(target_path) {
-                for idx in 0..ln {
-                    if let Some(g) = caps.get(idx + 1) {
-                        route_params.set(route_params_list[idx].clone(), g.as_str());
+            // The byte ranges of the param values in the target path, they're mapped back to the raw request path.
+            let mut param_ranges = Vec::with_capacity(ln);
+
+            if let Some(ref segments) = self.segments {
+                // No need to run the regex as the params are always whole segments and the wildcard takes the rest of the path.
+                if let Some(target_segments) = helpers::path_segments(target_path) {
+                    let mut start = 1;
+
+                    for (idx, segment) in segments.iter().enumerate() {
+                        if *segment == Segment::Wildcard {
+                            param_ranges.push(Some((start, target_path.len())));
+                            break;
+                        }
+
+                        let value = match target_segments.get(idx) {
+                            Some(value) => value,
+                            None => break,
+                        };
+
+                        if *segment == Segment::Param {
+                            param_ranges.push(Some((start, start + value.len())));
+                        }
+                        start += value.len() + 1;
                     }
                 }
+            } else if let Some(caps) = self.regex.captures(target_path) {
+                param_ranges.extend((1..=ln).map(|idx| caps.get(idx).map(|g| (g.start(), g.end()))));
+            }
+
+            let offsets = raw_path.offsets();
+
+            for (param_name, range) in route_params_list.iter().zip(param_ranges) {
+                if let Some((start, end)) = range {
+                    let (value, raw) = raw_path.param_value(&offsets, &target_path[start..end], start, end)?;
+                    route_params.append_with_raw(param_name.clone(), value, raw);
+                }
             }
         }
 
-        Ok(RequestMeta::with_route_params(route_params))
+        // The requests handled by the default 404 and OPTIONS routes are not considered routed.
+        let matched_route = if self.builtin {
+            None
+        } else {
+            Some(self.template.clone())
+        };
+
+        let mut req_meta = RequestMeta::with_route(route_params, matched_route);
+
+        if !self.mount_prefix.is_empty() {
+            let matched = matched_mount_prefix(&self.mount_prefix, target_path, &raw_path);
+            req_meta.set_mount_prefix(self.mount_prefix.clone(), matched);
+        }
+
+        Ok(req_meta)
+    }
+}
+
+/// The fallback routes are tried after all the other routes, the ones of the innermost scopes first.
+pub(crate) fn fallback_priority(path: &str) -> i32 {
+    i32::MIN.saturating_add(path.matches('/').count() as i32)
+}
+
+/// Whether a route path is added with a trailing slash, before the router appends it. It's `None` if the path ends with a
+/// wildcard e.g. `/assets/*` or `/assets/*filepath`.
+pub(crate) fn trailing_slash_of(path: &str) -> Option<bool> {
+    match path.rsplit('/').next() {
+        Some(last_segment) if last_segment.starts_with('*') => None,
+        _ => Some(path.ends_with('/')),
     }
 }
 
-impl<B, E> Debug for Route<B, E> {
+/// Drops the body of the response to a HEAD request, its size is kept in the `Content-Length` header if it's known.
+fn strip_response_body<B: HttpBody + 'static>(mut res: Response<B>) -> Response<B> {
+    if !res.headers().contains_key(header::CONTENT_LENGTH) {
+        if let Some(size) = res.body().size_hint().exact() {
+            res.headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(size));
+        }
+    }
+
+    // The body can only be replaced if its type is known, otherwise it's left to hyper which doesn't send it to the client.
+    let any_res: &mut dyn Any = &mut res;
+    if let Some(res) = any_res.downcast_mut::<Response<hyper::Body>>() {
+        *res.body_mut() = hyper::Body::empty();
+    }
+
+    res
+}
+
+/// Returns the part of the request path matched by the mount prefix, the prefix and the request path have the same number of
+/// leading segments as the params of the prefix are whole segments.
+fn matched_mount_prefix(mount_prefix: &str, target_path: &str, raw_path: &RawPath<'_>) -> String {
+    let segments = mount_prefix.matches('/').count();
+    let end = target_path
+        .match_indices('/')
+        .nth(segments)
+        .map(|(idx, _)| idx)
+        .unwrap_or_else(|| target_path.len());
+
+    let raw_end = raw_path.offsets().get(end).copied().unwrap_or(raw_path.path.len());
+    raw_path.path[..raw_end].trim_end_matches('/').to_owned()
+}
+
+fn parse_segments(path: &str) -> Option<Vec<Segment>> {
+    // A trailing wildcard e.g. `/assets/*` matches the rest of the path, the other wildcards are matched by the regex.
+    let (path, wildcard) = match path.strip_suffix('*') {
+        Some(prefix) if prefix.ends_with('/') => (prefix, true),
+        _ => (path, false),
+    };
+
+    if path.contains('*') {
+        return None;
+    }
+
+    let mut segments = helpers::path_segments(path)?
+        .into_iter()
+        .map(|segment| {
+            if !segment.contains(':') {
+                Some(Segment::Static(segment.to_owned()))
+            } else if segment.len() > 1 && segment.starts_with(':') && !segment.contains('(') && !segment.ends_with('?')
+            {
+                // A param with a constraint e.g. `:id(\d+)` or an optional one e.g. `:lang?` is matched by the regex.
+                Some(Segment::Param)
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    if wildcard {
+        segments.push(Segment::Wildcard);
+    }
+
+    Some(segments)
+}
+
+impl<B, E, ReqB> Debug for Route<B, E, ReqB> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let methods = if self.all_methods {
+            "*".to_owned()
+        } else {
+            format!("{:?}", self.methods)
+        };
+
         write!(
             f,
-            "{{ path: {:?}, regex: {:?}, route_params: {:?}, methods: {:?} }}",
-            self.path, self.regex, self.route_params, self.methods
+            "{{ path: {:?}, regex: {:?}, route_params: {:?}, methods: {}, header_matcher: {:?}, query_matcher: {:?}, content_type: {:?}, priority: {:?}, timeout: {:?} }}",
+            self.path,
+            self.regex,
+            self.route_params,
+            methods,
+            self.header_matcher,
+            self.query_matcher,
+            self.content_type,
+            self.priority,
+            self.timeout
         )
     }
 }
@@ -0,0 +1,68 @@
+use super::ServiceFn;
+use crate::ext::RequestExt;
+use futures::future;
+use hyper::{service::Service, Request, Response, Uri};
+use std::sync::Mutex;
+
+/// Options for the services mounted by the [`mount_service_with_options`](./struct.RouterBuilder.html#method.mount_service_with_options)
+/// method.
+#[derive(Debug, Clone)]
+pub struct MountServiceOptions {
+    /// Strips the path prefix from the request URI before passing the request to the service, e.g. a service mounted at
+    /// `/metrics` receives `/metrics/jobs?page=2` as `/jobs?page=2` and `/metrics` as `/`.
+    pub strip_prefix: bool,
+}
+
+impl Default for MountServiceOptions {
+    fn default() -> MountServiceOptions {
+        MountServiceOptions { strip_prefix: true }
+    }
+}
+
+pub(crate) fn service_handler<B, ReqB, S>(service: S, options: MountServiceOptions) -> ServiceFn<B, ReqB>
+where
+    ReqB: Send + 'static,
+    S: Service<Request<ReqB>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    // The service is cloned for every request, so it only has to be `Send`.
+    let service = Mutex::new(service);
+
+    Box::new(move |mut req: Request<ReqB>| {
+        let mut service = service.lock().unwrap_or_else(|err| err.into_inner()).clone();
+
+        if options.strip_prefix {
+            if let Some(uri) = strip_prefix(&req) {
+                *req.uri_mut() = uri;
+            }
+        }
+
+        Box::new(async move {
+            future::poll_fn(|cx| service.poll_ready(cx)).await.map_err(Into::into)?;
+            service.call(req).await.map_err(Into::into)
+        })
+    })
+}
+
+/// Replaces the request path by the part matched by the trailing `*` of the mount path.
+fn strip_prefix<ReqB>(req: &Request<ReqB>) -> Option<Uri> {
+    let path = req.uri().path();
+    let rest = req.raw_param("*")?;
+
+    // The trailing slash appended by the router isn't a part of the request path.
+    let rest = if path.ends_with(rest) {
+        rest
+    } else {
+        rest.strip_suffix('/').unwrap_or(rest)
+    };
+
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("/{}?{}", rest, query),
+        None => format!("/{}", rest),
+    };
+
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
@@ -0,0 +1,132 @@
+use crate::types::QueryParams;
+use crate::Error;
+use regex::Regex;
+use std::fmt::{self, Debug, Formatter};
+
+/// Represents a query string criteria of a route, a request is routed to the route only if its query string has the key with a
+/// matching value.
+///
+/// A matcher can be created from a `(key, value)` tuple for an exact value match, by the [`present`](#method.present) method
+/// for any value or by the [`regex`](#method.regex) method. Please refer to the [`add_with_query`](./struct.RouterBuilder.html#method.add_with_query)
+/// method for an example.
+#[derive(Debug, Clone)]
+pub struct QueryMatcher {
+    key: String,
+    value: Option<String>,
+    is_regex: bool,
+}
+
+impl QueryMatcher {
+    /// Creates a matcher which matches the decoded value of the key exactly.
+    pub fn exact<K: Into<String>, V: Into<String>>(key: K, value: V) -> QueryMatcher {
+        QueryMatcher {
+            key: key.into(),
+            value: Some(value.into()),
+            is_regex: false,
+        }
+    }
+
+    /// Creates a matcher which matches if the key is present with any value, including an empty one.
+    pub fn present<K: Into<String>>(key: K) -> QueryMatcher {
+        QueryMatcher {
+            key: key.into(),
+            value: None,
+            is_regex: false,
+        }
+    }
+
+    /// Creates a matcher which matches the decoded value of the key against a regex pattern. The pattern must match the whole value.
+    pub fn regex<K: Into<String>, P: Into<String>>(key: K, pattern: P) -> QueryMatcher {
+        QueryMatcher {
+            key: key.into(),
+            value: Some(pattern.into()),
+            is_regex: true,
+        }
+    }
+
+    pub(crate) fn compile(self) -> crate::Result<CompiledQueryMatcher> {
+        let regex = match self.value {
+            Some(ref pattern) if self.is_regex => {
+                let re = Regex::new(format!("^(?:{})$", pattern).as_str()).map_err(|e| {
+                    Error::InvalidQueryMatcher(format!("Invalid value pattern for {}: {}", self.key, e))
+                })?;
+                Some(re)
+            }
+            _ => None,
+        };
+
+        Ok(CompiledQueryMatcher {
+            key: self.key,
+            value: self.value,
+            regex,
+        })
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> From<(K, V)> for QueryMatcher {
+    fn from((key, value): (K, V)) -> Self {
+        QueryMatcher::exact(key, value)
+    }
+}
+
+pub(crate) struct CompiledQueryMatcher {
+    key: String,
+    value: Option<String>,
+    regex: Option<Regex>,
+}
+
+impl CompiledQueryMatcher {
+    /// Any of the values of a repeated key can match.
+    pub(crate) fn is_match(&self, query_params: &QueryParams) -> bool {
+        query_params
+            .get_all(&self.key)
+            .any(|val| match (&self.regex, &self.value) {
+                (Some(ref re), _) => re.is_match(val),
+                (None, Some(ref value)) => val == value,
+                (None, None) => true,
+            })
+    }
+}
+
+impl Debug for CompiledQueryMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.regex, &self.value) {
+            (Some(ref re), _) => write!(f, "{{ key: {:?}, regex: {:?} }}", self.key, re),
+            (None, Some(ref value)) => write!(f, "{{ key: {:?}, value: {:?} }}", self.key, value),
+            (None, None) => write!(f, "{{ key: {:?} }}", self.key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_matcher() {
+        let query_params = QueryParams::parse(Some("format=csv&tag=a&tag=b%20c&empty="));
+
+        let matcher = QueryMatcher::from(("format", "csv")).compile().unwrap();
+        assert!(matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::exact("format", "json").compile().unwrap();
+        assert!(!matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::exact("tag", "b c").compile().unwrap();
+        assert!(matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::present("empty").compile().unwrap();
+        assert!(matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::present("missing").compile().unwrap();
+        assert!(!matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::regex("format", "csv|tsv").compile().unwrap();
+        assert!(matcher.is_match(&query_params));
+
+        let matcher = QueryMatcher::regex("format", "cs").compile().unwrap();
+        assert!(!matcher.is_match(&query_params));
+
+        assert!(QueryMatcher::regex("format", "(csv").compile().is_err());
+    }
+}
@@ -1,23 +1,34 @@
+use crate::body::ScopedBodyLimit;
 use crate::constants;
+use crate::cors::Cors;
 use crate::data_map::{DataMap, ScopedDataMap};
-use crate::middleware::{Middleware, PostMiddleware, PreMiddleware};
-use crate::route::Route;
-use crate::router::Router;
-use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo};
-use crate::types::RequestInfo;
-use hyper::{body::HttpBody, Method, Request, Response};
-use std::collections::HashMap;
+use crate::error::BuildError;
+use crate::handler::ErrHandlerOutput;
+use crate::helpers;
+use crate::middleware::{AroundMiddleware, Middleware, PostMiddleware, PreMiddleware};
+use crate::response::IntoResponse;
+use crate::route::{self, HeaderMatcher, MountServiceOptions, QueryMatcher, Route};
+use crate::router::{DynamicRoutes, HostPattern, HostRouter, RouteMatcher, Router};
+use crate::router::{ErrHandler, ErrHandlerWithInfo, ErrHandlerWithoutInfo, ErrMapper, RequestCompleteHook};
+use crate::types::{IpRange, PercentDecodeMode, RequestInfo, RequestMetrics, SlashNormalization, TrailingSlash};
+use futures::FutureExt;
+use hyper::{body::HttpBody, Body, Method, Request, Response};
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::panic::Location;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Builder for the [Router](./struct.Router.html) type.
 ///
-/// This `RouterBuilder<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterBuilder<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -50,63 +61,163 @@ use std::sync::Arc;
 /// # }
 /// # run();
 /// ```
-pub struct RouterBuilder<B, E> {
-    inner: crate::Result<BuilderInner<B, E>>,
+pub struct RouterBuilder<B, E, ReqB = hyper::Body> {
+    inner: BuilderInner<B, E, ReqB>,
+    // The problems with the registrations are collected and reported together by the `build` method.
+    errors: Vec<BuildError>,
 }
 
-struct BuilderInner<B, E> {
-    pre_middlewares: Vec<PreMiddleware<E>>,
-    routes: Vec<Route<B, E>>,
+struct BuilderInner<B, E, ReqB> {
+    pre_middlewares: Vec<PreMiddleware<E, ReqB, B>>,
+    around_middlewares: Vec<AroundMiddleware<B, E, ReqB>>,
+    routes: Vec<Route<B, E, ReqB>>,
     post_middlewares: Vec<PostMiddleware<B, E>>,
     data_maps: HashMap<String, Vec<DataMap>>,
+    body_limits: Vec<(String, u64)>,
+    host_routers: Vec<HostRouter<B, E, ReqB>>,
     err_handler: Option<ErrHandler<B>>,
+    err_mappers: Vec<ErrMapper<B>>,
+    catch_panics: bool,
+    route_matcher: RouteMatcher,
+    match_cache_capacity: usize,
+    percent_decode_mode: PercentDecodeMode,
+    slash_normalization: SlashNormalization,
+    trailing_slash: TrailingSlash,
+    on_request_complete: Option<RequestCompleteHook>,
+    dynamic_routes: Option<DynamicRoutes<B, E, ReqB>>,
+    method_not_allowed: bool,
+    auto_options: bool,
+    auto_head: bool,
+    timeout: Option<Duration>,
+    trusted_proxies: Vec<IpRange>,
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RouterBuilder<B, E, ReqB>
 {
     /// Creates a new `RouterBuilder` instance with default options.
-    pub fn new() -> RouterBuilder<B, E> {
+    pub fn new() -> RouterBuilder<B, E, ReqB> {
         RouterBuilder::default()
     }
 
     /// Creates a new [Router](./struct.Router.html) instance from the added configuration.
-    pub fn build(self) -> crate::Result<Router<B, E>> {
-        self.inner.and_then(|inner| {
-            let scoped_data_maps = inner
-                .data_maps
-                .into_iter()
-                .map(|(path, data_map_arr)| {
-                    data_map_arr
-                        .into_iter()
-                        .map(|data_map| ScopedDataMap::new(path.clone(), Arc::new(data_map)))
-                        .collect::<Vec<crate::Result<ScopedDataMap>>>()
-                })
-                .flatten()
-                .collect::<Result<Vec<ScopedDataMap>, crate::Error>>()?;
-
-            Ok(Router::new(
-                inner.pre_middlewares,
-                inner.routes,
-                inner.post_middlewares,
-                scoped_data_maps,
-                inner.err_handler,
-            ))
-        })
+    ///
+    /// If any of the registrations is not valid e.g. a route has an invalid header matcher, an
+    /// [`Error::BuildRouter`](./enum.Error.html#variant.BuildRouter) error is returned which lists all the
+    /// [problems](./struct.BuildError.html), one per line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Error, HeaderMatcher, Router};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// let result: routerify::Result<Router<Body, Infallible>> = Router::builder()
+    ///     .get_with_header("/users", HeaderMatcher::regex("X-Version", "(1"), |_| async move {
+    ///         Ok(Response::new(Body::from("Users")))
+    ///     })
+    ///     .host_scope("api.*.com", Router::builder().build().unwrap())
+    ///     .build();
+    ///
+    /// match result {
+    ///     Err(Error::BuildRouter(errors)) => assert_eq!(errors.len(), 2),
+    ///     _ => panic!("The routes are not valid"),
+    /// }
+    /// ```
+    #[track_caller]
+    pub fn build(self) -> crate::Result<Router<B, E, ReqB>> {
+        let location = Location::caller();
+        let RouterBuilder { inner, mut errors } = self;
+
+        let mut scoped_data_maps = Vec::new();
+        for (path, data_map_arr) in inner.data_maps {
+            for data_map in data_map_arr {
+                match ScopedDataMap::new(path.clone(), Arc::new(data_map)) {
+                    Ok(scoped_data_map) => scoped_data_maps.push(scoped_data_map),
+                    Err(err) => errors.push(BuildError::new(&[], path.clone(), location, err)),
+                }
+            }
+        }
+
+        // The data is looked up in the matched maps in order, so the maps of the innermost scopes come first to shadow the
+        // data of the outer routers.
+        scoped_data_maps.sort_by(|a, b| {
+            let depth = |scoped_data_map: &ScopedDataMap| scoped_data_map.path.matches('/').count();
+            depth(b).cmp(&depth(a)).then_with(|| a.path.cmp(&b.path))
+        });
+
+        let mut scoped_body_limits = Vec::new();
+        for (path, limit) in inner.body_limits {
+            match ScopedBodyLimit::new(path.clone(), limit) {
+                Ok(scoped_body_limit) => scoped_body_limits.push(scoped_body_limit),
+                Err(err) => errors.push(BuildError::new(&[], path, location, err)),
+            }
+        }
+
+        let mut names = HashSet::new();
+        for route in inner.routes.iter() {
+            if let Some(name) = route.name.as_ref() {
+                if !names.insert(name.as_str()) {
+                    let err = crate::Error::DuplicateRouteName(name.clone());
+                    errors.push(BuildError::new(&route.methods, route.path.clone(), location, err));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(crate::Error::BuildRouter(errors));
+        }
+
+        let mut router = Router::new(
+            inner.pre_middlewares,
+            inner.around_middlewares,
+            inner.routes,
+            inner.post_middlewares,
+            scoped_data_maps,
+            scoped_body_limits,
+            inner.host_routers,
+            inner.err_handler,
+            inner.catch_panics,
+        );
+        router.err_mappers = inner.err_mappers;
+        router.route_matcher = inner.route_matcher;
+        router.match_cache_capacity = inner.match_cache_capacity;
+        router.percent_decode_mode = inner.percent_decode_mode;
+        router.slash_normalization = inner.slash_normalization;
+        router.trailing_slash = inner.trailing_slash;
+        router.on_request_complete = inner.on_request_complete;
+        router.dynamic_routes = inner.dynamic_routes;
+        router.method_not_allowed = inner.method_not_allowed;
+        router.auto_options = inner.auto_options;
+        router.auto_head = inner.auto_head;
+        router.timeout = inner.timeout;
+        router.trusted_proxies = inner.trusted_proxies.into();
+
+        Ok(router)
     }
 
-    fn and_then<F>(self, func: F) -> Self
+    fn update<F>(mut self, func: F) -> Self
     where
-        F: FnOnce(BuilderInner<B, E>) -> crate::Result<BuilderInner<B, E>>,
+        F: FnOnce(&mut BuilderInner<B, E, ReqB>),
     {
-        RouterBuilder {
-            inner: self.inner.and_then(func),
-        }
+        func(&mut self.inner);
+        self
+    }
+
+    fn push_error(&mut self, err: BuildError) {
+        self.errors.push(err);
     }
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    RouterBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RouterBuilder<B, E, ReqB>
 {
     /// Adds a new route with `GET` method and the handler at the specified path.
     ///
@@ -129,11 +240,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn get<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn get<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::GET], handler)
     }
@@ -159,11 +272,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn get_or_head<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn get_or_head<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::GET, Method::HEAD], handler)
     }
@@ -189,11 +304,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn post<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn post<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::POST], handler)
     }
@@ -219,11 +336,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn put<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn put<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::PUT], handler)
     }
@@ -249,11 +368,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn delete<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn delete<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::DELETE], handler)
     }
@@ -279,11 +400,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn head<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn head<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::HEAD], handler)
     }
@@ -309,11 +432,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn trace<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn trace<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::TRACE], handler)
     }
@@ -339,11 +464,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn connect<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn connect<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::CONNECT], handler)
     }
@@ -369,11 +496,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn patch<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn patch<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::PATCH], handler)
     }
@@ -399,11 +528,13 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn options<P, H, R>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn options<P, H, R, T>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, vec![Method::OPTIONS], handler)
     }
@@ -440,14 +571,71 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn any<H, R>(self, handler: H) -> Self
+    #[track_caller]
+    pub fn any<H, R, T>(self, handler: H) -> Self
     where
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
 
+    /// Adds a handler for the requests which are not matched by any other route of the router, e.g. to respond with a custom
+    /// 404 page. Unlike the [`any`](#method.any) method, it doesn't depend on the registration order and each scoped router can
+    /// have its own one, the handler of the innermost scope matching the request path is run.
+    ///
+    /// If a route matches the request path but not the method, the request is responded with `405 Method Not Allowed` instead
+    /// unless it's disabled by the [`method_not_allowed`](#method.method_not_allowed) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let api = Router::builder()
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+    ///     .not_found(|_| async move {
+    ///         Ok(Response::builder()
+    ///             .status(StatusCode::NOT_FOUND)
+    ///             .body(Body::from(r#"{"error":"Not found"}"#))
+    ///             .unwrap())
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .not_found(|_| async move {
+    ///         Ok(Response::builder()
+    ///             .status(StatusCode::NOT_FOUND)
+    ///             .body(Body::from("Page not found"))
+    ///             .unwrap())
+    ///     })
+    ///     .scope("/api", api)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn not_found<H, R, T>(self, handler: H) -> Self
+    where
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.push_route("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler, |route| {
+            route.all_methods = true;
+            route.fallback = true;
+            route.priority = route::fallback_priority(&route.path);
+            Ok(())
+        })
+    }
+
     /// Adds a new route with any method type and the handler at the specified path.
     ///
     /// # Examples
@@ -470,277 +658,2191 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     /// # }
     /// # run();
     /// ```
-    pub fn any_method<H, R, P>(self, path: P, handler: H) -> Self
+    #[track_caller]
+    pub fn any_method<H, R, T, P>(self, path: P, handler: H) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
         self.add(path, constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), handler)
     }
 
-    /// Adds a new route with the specified method(s) and the handler at the specified path. It can be used to define routes with multiple method types.
+    /// Adds a new route which matches every method, including the non-standard ones e.g. `PROPFIND`, and the handler at the
+    /// specified path. Unlike the [`any_method`](#method.any_method) method, it's not limited to the standard methods.
+    ///
+    /// Same as any other route, the path is matched exactly and the first registered route which matches the request wins, so a
+    /// method specific route on the same path must be registered before it to take precedence.
     ///
     /// # Examples
     ///
     /// ```
     /// use routerify::Router;
-    /// use hyper::{Response, Request, Body, StatusCode, Method};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
     ///
-    /// async fn cart_checkout_handler(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
-    ///     Ok(Response::new(Body::from("You shopping cart is being checking out")))
-    /// }
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/gateway/health", |_| async move { Ok(Response::new(Body::from("OK"))) })
+    ///     // Forwards the requests with any method to the upstream.
+    ///     .all_methods("/gateway/*", |_| async move { Ok(Response::new(Body::from("Forwarded"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn all_methods<P, H, R, T>(self, path: P, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.push_route(path, Vec::new(), handler, |route| {
+            route.all_methods = true;
+            Ok(())
+        })
+    }
+
+    /// Mounts a [Service](https://docs.rs/hyper/0.13.5/hyper/service/trait.Service.html) e.g. a metrics exporter or an app built
+    /// with another framework at the specified path prefix. The requests with any method whose path starts with the prefix are
+    /// passed to the service with the prefix stripped from the URI.
+    ///
+    /// The service is cloned for every request. It's wrapped by the middlewares of the router same as a route handler, and its
+    /// errors are passed to the error handler as the [`Error::HandleRequest`](./enum.Error.html#variant.HandleRequest) errors,
+    /// so they don't have to be converted into the router's error type. A `tower` service is accepted as well, as hyper
+    /// services are `tower` services.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{service::service_fn, Body, Request, Response};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let metrics = service_fn(|req: Request<Body>| async move {
+    ///     // The service receives `/metrics/jobs` as `/jobs`.
+    ///     Ok::<_, Infallible>(Response::new(Body::from(format!("Metrics of {}", req.uri().path()))))
+    /// });
     ///
-    /// # fn run() -> Router<Body, hyper::Error> {
     /// let router = Router::builder()
-    ///     .add("/checkout", vec![Method::GET, Method::POST], cart_checkout_handler)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .mount_service("/metrics", metrics)
     ///     .build()
     ///     .unwrap();
     /// # router
     /// # }
     /// # run();
     /// ```
-    pub fn add<P, H, R>(self, path: P, methods: Vec<Method>, handler: H) -> Self
+    #[track_caller]
+    pub fn mount_service<P, S>(self, path_prefix: P, service: S) -> Self
     where
         P: Into<String>,
-        H: FnMut(Request<hyper::Body>) -> R + Send + Sync + 'static,
-        R: Future<Output = Result<Response<B>, E>> + Send + 'static,
+        S: hyper::service::Service<Request<ReqB>, Response = Response<B>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
     {
-        self.and_then(move |mut inner| {
-            let mut path = path.into();
+        self.mount_service_with_options(path_prefix, service, MountServiceOptions::default())
+    }
 
-            if !path.ends_with("/") && !path.ends_with("*") {
-                path.push_str("/");
-            }
+    /// Mounts a [Service](https://docs.rs/hyper/0.13.5/hyper/service/trait.Service.html) at the specified path prefix with the
+    /// options, e.g. to pass the requests to the service with the whole path.
+    ///
+    /// Please refer to the [`mount_service`](#method.mount_service) method for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{MountServiceOptions, Router};
+    /// use hyper::{service::service_fn, Body, Request, Response};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let legacy_app = service_fn(|req: Request<Body>| async move {
+    ///     // The service receives `/legacy/users` as it is.
+    ///     Ok::<_, Infallible>(Response::new(Body::from(format!("Legacy {}", req.uri().path()))))
+    /// });
+    ///
+    /// let router = Router::builder()
+    ///     .mount_service_with_options("/legacy", legacy_app, MountServiceOptions { strip_prefix: false })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn mount_service_with_options<P, S>(mut self, path_prefix: P, service: S, options: MountServiceOptions) -> Self
+    where
+        P: Into<String>,
+        S: hyper::service::Service<Request<ReqB>, Response = Response<B>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let location = Location::caller();
+        let path = path_prefix.into();
+        let route_path = format!("{}/*", path.trim_end_matches('/'));
 
-            let route = Route::new(path, methods, handler)?;
-            inner.routes.push(route);
+        match Route::new_with_service(route_path, service, options) {
+            Ok(route) => self.inner.routes.push(route),
+            Err(err) => self.push_error(BuildError::new(&[], path, location, err)),
+        }
 
-            crate::Result::Ok(inner)
-        })
+        self
     }
 
-    /// It mounts a router onto another router. It can be very useful when you want to write modular routing logic.
+    /// Adds a new route with the specified method(s) and the handler at the specified path. It can be used to define routes with multiple method types.
     ///
     /// # Examples
     ///
     /// ```
     /// use routerify::Router;
-    /// use hyper::{Response, Request, Body};
-    ///
-    /// mod api {
-    ///     use routerify::Router;
-    ///     use hyper::{Response, Request, Body};
+    /// use hyper::{Response, Request, Body, StatusCode, Method};
     ///
-    ///     pub fn router() -> Router<Body, hyper::Error> {
-    ///         Router::builder()
-    ///          .get("/users", |req| async move { Ok(Response::new(Body::from("User list"))) })
-    ///          .get("/books", |req| async move { Ok(Response::new(Body::from("Book list"))) })
-    ///          .build()
-    ///          .unwrap()
-    ///     }
+    /// async fn cart_checkout_handler(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    ///     Ok(Response::new(Body::from("You shopping cart is being checking out")))
     /// }
     ///
     /// # fn run() -> Router<Body, hyper::Error> {
     /// let router = Router::builder()
-    ///     // Now, mount the api router at `/api` path.
-    ///     .scope("/api", api::router())
+    ///     .add("/checkout", vec![Method::GET, Method::POST], cart_checkout_handler)
     ///     .build()
     ///     .unwrap();
     /// # router
     /// # }
     /// # run();
     /// ```
-    ///
-    /// Now, the app can handle requests on: `/api/users` and `/api/books` paths.
-    pub fn scope<P>(self, path: P, mut router: Router<B, E>) -> Self
+    #[track_caller]
+    pub fn add<P, H, R, T>(self, path: P, methods: Vec<Method>, handler: H) -> Self
     where
         P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
-        let mut path = path.into();
-
-        if path.ends_with("/") {
-            path = (&path[..path.len() - 1]).to_string();
-        }
+        self.push_route(path, methods, handler, |_| Ok(()))
+    }
 
-        let mut builder = self;
+    #[track_caller]
+    fn push_route<P, H, R, T, F>(mut self, path: P, methods: Vec<Method>, handler: H, configure: F) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        F: FnOnce(&mut Route<B, E, ReqB>) -> crate::Result<()>,
+    {
+        let location = Location::caller();
+        let path = path.into();
+        let mut route_path = path.clone();
 
-        for pre_middleware in router.pre_middlewares.iter_mut() {
-            let new_pre_middleware = PreMiddleware::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), pre_middleware.path.as_str()),
-                pre_middleware
-                    .handler
-                    .take()
-                    .expect("No handler found in one of the pre-middlewares"),
-            );
-            builder = builder.and_then(move |mut inner| {
-                inner.pre_middlewares.push(new_pre_middleware?);
-                crate::Result::Ok(inner)
-            });
+        if !route_path.ends_with('/') && !route_path.ends_with('*') {
+            route_path.push('/');
         }
 
-        for route in router.routes.iter_mut() {
-            let new_route = Route::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), route.path.as_str()),
-                route.methods.clone(),
-                route.handler.take().expect("No handler found in one of the routes"),
-            );
-            builder = builder.and_then(move |mut inner| {
-                inner.routes.push(new_route?);
-                crate::Result::Ok(inner)
-            });
-        }
+        let route = Route::new(route_path, methods.clone(), handler).and_then(|mut route| {
+            route.trailing_slash = route::trailing_slash_of(&path);
+            configure(&mut route)?;
+            Ok(route)
+        });
 
-        for post_middleware in router.post_middlewares.iter_mut() {
-            let new_post_middleware = PostMiddleware::new_with_boxed_handler(
-                format!("{}{}", path.as_str(), post_middleware.path.as_str()),
-                post_middleware
-                    .handler
-                    .take()
-                    .expect("No handler found in one of the post-middlewares"),
-            );
-            builder = builder.and_then(move |mut inner| {
-                inner.post_middlewares.push(new_post_middleware?);
-                crate::Result::Ok(inner)
-            });
+        match route {
+            Ok(route) => self.inner.routes.push(route),
+            Err(err) => self.push_error(BuildError::new(&methods, path, location, err)),
         }
 
-        for scoped_data_map in router.scoped_data_maps.iter_mut() {
-            let new_path = format!("{}{}", path.as_str(), scoped_data_map.path.as_str());
-            let data_map = Arc::try_unwrap(
-                scoped_data_map
-                    .data_map
-                    .take()
-                    .expect("No data map found in one of the scoped data maps"),
-            )
-            .expect("Non-zero owner of the shared data map in one of the scoped data maps");
+        self
+    }
 
-            builder = builder.and_then(move |mut inner| {
-                let data_maps = &mut inner.data_maps;
+    /// Adds a new route with `GET` method and the handler at the specified path, which only handles the requests with a matching header.
+    ///
+    /// Please refer to the [`add_with_header`](#method.add_with_header) method for more info.
+    #[track_caller]
+    pub fn get_with_header<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_header(path, vec![Method::GET], matcher, handler)
+    }
 
-                let data_map_arr = data_maps.get_mut(&new_path);
-                if let Some(data_map_arr) = data_map_arr {
-                    data_map_arr.push(data_map);
-                } else {
-                    data_maps.insert(new_path, vec![data_map]);
-                }
+    /// Adds a new route with `POST` method and the handler at the specified path, which only handles the requests with a matching header.
+    ///
+    /// Please refer to the [`add_with_header`](#method.add_with_header) method for more info.
+    #[track_caller]
+    pub fn post_with_header<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_header(path, vec![Method::POST], matcher, handler)
+    }
 
-                crate::Result::Ok(inner)
-            });
-        }
+    /// Adds a new route with `PUT` method and the handler at the specified path, which only handles the requests with a matching header.
+    ///
+    /// Please refer to the [`add_with_header`](#method.add_with_header) method for more info.
+    #[track_caller]
+    pub fn put_with_header<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_header(path, vec![Method::PUT], matcher, handler)
+    }
 
-        builder
+    /// Adds a new route with `DELETE` method and the handler at the specified path, which only handles the requests with a matching header.
+    ///
+    /// Please refer to the [`add_with_header`](#method.add_with_header) method for more info.
+    #[track_caller]
+    pub fn delete_with_header<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_header(path, vec![Method::DELETE], matcher, handler)
     }
-}
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    RouterBuilder<B, E>
-{
-    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
-    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
+    /// Adds a new route with `PATCH` method and the handler at the specified path, which only handles the requests with a matching header.
+    ///
+    /// Please refer to the [`add_with_header`](#method.add_with_header) method for more info.
+    #[track_caller]
+    pub fn patch_with_header<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_header(path, vec![Method::PATCH], matcher, handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, which only handles the requests with
+    /// a matching header. The header value can be matched exactly by passing a `(name, value)` tuple, or against a regex with the
+    /// [`HeaderMatcher::regex`](./struct.HeaderMatcher.html#method.regex) method.
+    ///
+    /// Multiple routes can share the same path and methods and be selected by a header. If the header doesn't match, the following
+    /// routes are tried, so a route without any header criteria acts as the fallback when it's added after the header matching routes.
     ///
     /// # Examples
     ///
     /// ```
-    /// use routerify::{Router, Middleware};
-    /// use hyper::{Response, Request, Body};
-    /// use std::convert::Infallible;
+    /// use routerify::{Router, HeaderMatcher};
+    /// use hyper::{Response, Body, StatusCode};
+    /// # use std::convert::Infallible;
     ///
     /// # fn run() -> Router<Body, Infallible> {
     /// let router = Router::builder()
-    ///      // Create and attach a pre middleware.
-    ///      .middleware(Middleware::pre(|req| async move { /* Do some operations */ Ok(req) }))
-    ///      // Create and attach a post middleware.
-    ///      .middleware(Middleware::post(|res| async move { /* Do some operations */ Ok(res) }))
-    ///      .build()
-    ///      .unwrap();
+    ///     .post_with_header("/webhook", ("X-GitHub-Event", "push"), |_| async move {
+    ///         Ok(Response::new(Body::from("Push received")))
+    ///     })
+    ///     .post_with_header("/webhook", HeaderMatcher::regex("X-GitHub-Event", "issues|issue_comment"), |_| async move {
+    ///         Ok(Response::new(Body::from("Issue event received")))
+    ///     })
+    ///     // Handles any other event.
+    ///     .post("/webhook", |_| async move {
+    ///         Ok(Response::builder().status(StatusCode::ACCEPTED).body(Body::empty()).unwrap())
+    ///     })
+    ///     .build()
+    ///     .unwrap();
     /// # router
     /// # }
     /// # run();
     /// ```
-    pub fn middleware(self, m: Middleware<B, E>) -> Self {
-        self.and_then(move |mut inner| {
-            match m {
-                Middleware::Pre(middleware) => {
-                    inner.pre_middlewares.push(middleware);
-                }
-                Middleware::Post(middleware) => {
-                    inner.post_middlewares.push(middleware);
-                }
-            }
-            crate::Result::Ok(inner)
-        })
-    }
-
-    /// Specify app data to be shared across route handlers, middlewares and the error handler.
-    ///
-    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
-    pub fn data<T: Send + Sync + 'static>(self, data: T) -> Self {
-        self.and_then(move |mut inner| {
-            let data_maps = &mut inner.data_maps;
-
-            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
-            if let Some(data_map_arr) = data_map_arr {
-                let first_data_map = data_map_arr.get_mut(0).unwrap();
-                first_data_map.insert(data);
-            } else {
-                let mut data_map = DataMap::new();
-                data_map.insert(data);
-                data_maps.insert("/*".to_owned(), vec![data_map]);
-            }
+    #[track_caller]
+    pub fn add_with_header<P, M, H, R, T>(self, path: P, methods: Vec<Method>, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<HeaderMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let matcher = matcher.into();
 
-            crate::Result::Ok(inner)
+        self.push_route(path, methods, handler, move |route| {
+            route.header_matcher = Some(matcher.compile()?);
+            Ok(())
         })
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
-    /// for more info.
-    pub fn err_handler<H, R>(self, mut handler: H) -> Self
+    /// Adds a new route with `GET` method and the handler at the specified path, which only handles the requests with a matching
+    /// query string.
+    ///
+    /// Please refer to the [`add_with_query`](#method.add_with_query) method for more info.
+    #[track_caller]
+    pub fn get_with_query<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
     where
-        H: FnMut(crate::Error) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<B>> + Send + 'static,
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
-        let handler: ErrHandlerWithoutInfo<B> = Box::new(move |err: crate::Error| Box::new(handler(err)));
-
-        self.and_then(move |mut inner| {
-            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
-            crate::Result::Ok(inner)
-        })
+        self.add_with_query(path, vec![Method::GET], matcher, handler)
     }
 
-    /// Adds a handler to handle any error raised by the routes or any middlewares.
-    ///
-    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
+    /// Adds a new route with `POST` method and the handler at the specified path, which only handles the requests with a matching
+    /// query string.
     ///
-    /// Please refer to [Error Handling](./index.html#error-handling) section
-    /// for more info.
-    pub fn err_handler_with_info<H, R>(self, mut handler: H) -> Self
+    /// Please refer to the [`add_with_query`](#method.add_with_query) method for more info.
+    #[track_caller]
+    pub fn post_with_query<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
     where
-        H: FnMut(crate::Error, RequestInfo) -> R + Send + Sync + 'static,
-        R: Future<Output = Response<B>> + Send + 'static,
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
     {
-        let handler: ErrHandlerWithInfo<B> =
-            Box::new(move |err: crate::Error, req_info: RequestInfo| Box::new(handler(err, req_info)));
+        self.add_with_query(path, vec![Method::POST], matcher, handler)
+    }
+
+    /// Adds a new route with `PUT` method and the handler at the specified path, which only handles the requests with a matching
+    /// query string.
+    ///
+    /// Please refer to the [`add_with_query`](#method.add_with_query) method for more info.
+    #[track_caller]
+    pub fn put_with_query<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_query(path, vec![Method::PUT], matcher, handler)
+    }
+
+    /// Adds a new route with `DELETE` method and the handler at the specified path, which only handles the requests with a matching
+    /// query string.
+    ///
+    /// Please refer to the [`add_with_query`](#method.add_with_query) method for more info.
+    #[track_caller]
+    pub fn delete_with_query<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_query(path, vec![Method::DELETE], matcher, handler)
+    }
+
+    /// Adds a new route with `PATCH` method and the handler at the specified path, which only handles the requests with a matching
+    /// query string.
+    ///
+    /// Please refer to the [`add_with_query`](#method.add_with_query) method for more info.
+    #[track_caller]
+    pub fn patch_with_query<P, M, H, R, T>(self, path: P, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_query(path, vec![Method::PATCH], matcher, handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, which only handles the requests with
+    /// a matching query string. The decoded value of a key can be matched exactly by passing a `(key, value)` tuple, against a regex
+    /// with the [`QueryMatcher::regex`](./struct.QueryMatcher.html#method.regex) method, or the key can be required with any value
+    /// by the [`QueryMatcher::present`](./struct.QueryMatcher.html#method.present) method.
+    ///
+    /// Like the [header matching routes](#method.add_with_header), multiple routes can share the same path and methods and be selected
+    /// by the query string. If it doesn't match, the following routes are tried.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, QueryMatcher};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get_with_query("/export", ("format", "csv"), |_| async move {
+    ///         Ok(Response::new(Body::from("id,name")))
+    ///     })
+    ///     .get_with_query("/export", QueryMatcher::regex("format", "xlsx?"), |_| async move {
+    ///         Ok(Response::new(Body::from("A spreadsheet")))
+    ///     })
+    ///     // Handles any other format.
+    ///     .get("/export", |_| async move { Ok(Response::new(Body::from("[]"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_query<P, M, H, R, T>(self, path: P, methods: Vec<Method>, matcher: M, handler: H) -> Self
+    where
+        P: Into<String>,
+        M: Into<QueryMatcher>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let matcher = matcher.into();
+
+        self.push_route(path, methods, handler, move |route| {
+            route.query_matcher = Some(matcher.compile()?);
+            Ok(())
+        })
+    }
+
+    /// Adds a new route with `POST` method and the handler at the specified path, which only handles the requests with a matching
+    /// `Content-Type` header.
+    ///
+    /// Please refer to the [`add_with_content_type`](#method.add_with_content_type) method for more info.
+    #[track_caller]
+    pub fn post_with_content_type<P, C, H, R, T>(self, path: P, content_type: C, handler: H) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_content_type(path, vec![Method::POST], content_type, handler)
+    }
+
+    /// Adds a new route with `PUT` method and the handler at the specified path, which only handles the requests with a matching
+    /// `Content-Type` header.
+    ///
+    /// Please refer to the [`add_with_content_type`](#method.add_with_content_type) method for more info.
+    #[track_caller]
+    pub fn put_with_content_type<P, C, H, R, T>(self, path: P, content_type: C, handler: H) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_content_type(path, vec![Method::PUT], content_type, handler)
+    }
+
+    /// Adds a new route with `PATCH` method and the handler at the specified path, which only handles the requests with a matching
+    /// `Content-Type` header.
+    ///
+    /// Please refer to the [`add_with_content_type`](#method.add_with_content_type) method for more info.
+    #[track_caller]
+    pub fn patch_with_content_type<P, C, H, R, T>(self, path: P, content_type: C, handler: H) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_content_type(path, vec![Method::PATCH], content_type, handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, which only consumes the request bodies
+    /// of the media type e.g. `application/json`, or of a range of the media types e.g. `text/*`. The media type of the `Content-Type`
+    /// header is compared case-insensitively and its parameters e.g. `charset` are ignored.
+    ///
+    /// Multiple routes can share the same path and methods and be selected by the content type. If none of them consumes the
+    /// content type and no other route matches, the request is responded with an
+    /// [`Error::UnsupportedContentType`](./enum.Error.html#variant.UnsupportedContentType) error, which the default error handler
+    /// turns into `415 Unsupported Media Type`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .post_with_content_type("/items", "application/json", |_| async move {
+    ///         Ok(Response::new(Body::from("Created from JSON")))
+    ///     })
+    ///     .post_with_content_type("/items", "multipart/form-data", |_| async move {
+    ///         Ok(Response::new(Body::from("Created from an upload")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_content_type<P, C, H, R, T>(
+        self,
+        path: P,
+        methods: Vec<Method>,
+        content_type: C,
+        handler: H,
+    ) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let content_type = content_type.into();
+
+        self.push_route(path, methods, handler, move |route| {
+            let media_type = content_type.trim().to_ascii_lowercase();
+            match media_type.split_once('/') {
+                Some((ty, subtype)) if !ty.is_empty() && !subtype.is_empty() && !ty.contains('*') => {
+                    route.content_type = Some(media_type);
+                    Ok(())
+                }
+                _ => Err(crate::Error::InvalidHeaderMatcher(format!(
+                    "Invalid content type: {}",
+                    content_type
+                ))),
+            }
+        })
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path, the route can be referred by the name to
+    /// generate its URL.
+    ///
+    /// Please refer to the [`add_named`](#method.add_named) method for more info.
+    #[track_caller]
+    pub fn get_named<N, P, H, R, T>(self, name: N, path: P, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_named(name, path, vec![Method::GET], handler)
+    }
+
+    /// Adds a new route with `POST` method and the handler at the specified path, the route can be referred by the name to
+    /// generate its URL.
+    ///
+    /// Please refer to the [`add_named`](#method.add_named) method for more info.
+    #[track_caller]
+    pub fn post_named<N, P, H, R, T>(self, name: N, path: P, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_named(name, path, vec![Method::POST], handler)
+    }
+
+    /// Adds a new route with `PUT` method and the handler at the specified path, the route can be referred by the name to
+    /// generate its URL.
+    ///
+    /// Please refer to the [`add_named`](#method.add_named) method for more info.
+    #[track_caller]
+    pub fn put_named<N, P, H, R, T>(self, name: N, path: P, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_named(name, path, vec![Method::PUT], handler)
+    }
+
+    /// Adds a new route with `DELETE` method and the handler at the specified path, the route can be referred by the name to
+    /// generate its URL.
+    ///
+    /// Please refer to the [`add_named`](#method.add_named) method for more info.
+    #[track_caller]
+    pub fn delete_named<N, P, H, R, T>(self, name: N, path: P, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_named(name, path, vec![Method::DELETE], handler)
+    }
+
+    /// Adds a new route with `PATCH` method and the handler at the specified path, the route can be referred by the name to
+    /// generate its URL.
+    ///
+    /// Please refer to the [`add_named`](#method.add_named) method for more info.
+    #[track_caller]
+    pub fn patch_named<N, P, H, R, T>(self, name: N, path: P, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_named(name, path, vec![Method::PATCH], handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, the route can be referred by the
+    /// name to generate its URL by the [`Router::url_for`](./struct.Router.html#method.url_for) method instead of hard-coding it
+    /// e.g. in the redirects.
+    ///
+    /// The names are kept when the router is [scoped](#method.scope), so the URL includes the mount path. A name must be unique in
+    /// the whole router, otherwise the router can't be built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, Method};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .add_named("user_detail", "/users/:id", vec![Method::GET, Method::HEAD], |_| async move {
+    ///         Ok(Response::new(Body::from("A user")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(router.url_for("user_detail", &[("id", "42")]).unwrap(), "/users/42");
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_named<N, P, H, R, T>(self, name: N, path: P, methods: Vec<Method>, handler: H) -> Self
+    where
+        N: Into<String>,
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let name = name.into();
+
+        self.push_route(path, methods, handler, move |route| {
+            route.name = Some(name);
+            Ok(())
+        })
+    }
+
+    /// Adds a new route with `GET` method, the priority and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    #[track_caller]
+    pub fn get_with_priority<P, H, R, T>(self, path: P, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_priority(path, vec![Method::GET], priority, handler)
+    }
+
+    /// Adds a new route with `POST` method, the priority and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    #[track_caller]
+    pub fn post_with_priority<P, H, R, T>(self, path: P, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_priority(path, vec![Method::POST], priority, handler)
+    }
+
+    /// Adds a new route with `PUT` method, the priority and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    #[track_caller]
+    pub fn put_with_priority<P, H, R, T>(self, path: P, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_priority(path, vec![Method::PUT], priority, handler)
+    }
+
+    /// Adds a new route with `DELETE` method, the priority and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    #[track_caller]
+    pub fn delete_with_priority<P, H, R, T>(self, path: P, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_priority(path, vec![Method::DELETE], priority, handler)
+    }
+
+    /// Adds a new route with `PATCH` method, the priority and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    #[track_caller]
+    pub fn patch_with_priority<P, H, R, T>(self, path: P, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_priority(path, vec![Method::PATCH], priority, handler)
+    }
+
+    /// Adds a new route with the specified method(s), the priority and the handler at the specified path.
+    ///
+    /// The routes are matched in the order of their priorities, a route with a higher priority wins over a route with a lower one.
+    /// The routes with equal priorities are matched in the registration order. The routes added without any priority have the priority `0`.
+    ///
+    /// It's useful when the routes are registered by multiple modules and a generic route would shadow a more specific one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, Method};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/:page", |_| async move { Ok(Response::new(Body::from("A page"))) })
+    ///     // It's matched before the `/:page` route even though it's added later.
+    ///     .add_with_priority("/health", vec![Method::GET, Method::HEAD], 10, |_| async move {
+    ///         Ok(Response::new(Body::from("OK")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_priority<P, H, R, T>(self, path: P, methods: Vec<Method>, priority: i32, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.push_route(path, methods, handler, move |route| {
+            route.priority = priority;
+            Ok(())
+        })
+    }
+
+    /// Adds a new route with `GET` method, the timeout and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_timeout`](#method.add_with_timeout) method for more info.
+    #[track_caller]
+    pub fn get_with_timeout<P, H, R, T>(self, path: P, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_timeout(path, vec![Method::GET], timeout, handler)
+    }
+
+    /// Adds a new route with `POST` method, the timeout and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_timeout`](#method.add_with_timeout) method for more info.
+    #[track_caller]
+    pub fn post_with_timeout<P, H, R, T>(self, path: P, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_timeout(path, vec![Method::POST], timeout, handler)
+    }
+
+    /// Adds a new route with `PUT` method, the timeout and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_timeout`](#method.add_with_timeout) method for more info.
+    #[track_caller]
+    pub fn put_with_timeout<P, H, R, T>(self, path: P, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_timeout(path, vec![Method::PUT], timeout, handler)
+    }
+
+    /// Adds a new route with `DELETE` method, the timeout and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_timeout`](#method.add_with_timeout) method for more info.
+    #[track_caller]
+    pub fn delete_with_timeout<P, H, R, T>(self, path: P, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_timeout(path, vec![Method::DELETE], timeout, handler)
+    }
+
+    /// Adds a new route with `PATCH` method, the timeout and the handler at the specified path.
+    ///
+    /// Please refer to the [`add_with_timeout`](#method.add_with_timeout) method for more info.
+    #[track_caller]
+    pub fn patch_with_timeout<P, H, R, T>(self, path: P, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_timeout(path, vec![Method::PATCH], timeout, handler)
+    }
+
+    /// Adds a new route with the specified method(s), the timeout and the handler at the specified path.
+    ///
+    /// The handler is cancelled if it doesn't respond within the timeout, same as the router-wide
+    /// [`timeout`](#method.timeout) which it overrides. The timeout can be longer than the router-wide one e.g. for a slow
+    /// report, or shorter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, Method};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .timeout(Duration::from_secs(5))
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .add_with_timeout("/reports", vec![Method::GET], Duration::from_secs(60), |_| async move {
+    ///         Ok(Response::new(Body::from("A slow report")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_timeout<P, H, R, T>(self, path: P, methods: Vec<Method>, timeout: Duration, handler: H) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.push_route(path, methods, handler, move |route| {
+            route.timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path, the state is passed to the handler as the second argument.
+    ///
+    /// Please refer to the [`add_with_state`](#method.add_with_state) method for more info.
+    #[track_caller]
+    pub fn get_with_state<P, S, A, H, R, T>(self, path: P, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_state(path, vec![Method::GET], state, handler)
+    }
+
+    /// Adds a new route with `POST` method and the handler at the specified path, the state is passed to the handler as the second argument.
+    ///
+    /// Please refer to the [`add_with_state`](#method.add_with_state) method for more info.
+    #[track_caller]
+    pub fn post_with_state<P, S, A, H, R, T>(self, path: P, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_state(path, vec![Method::POST], state, handler)
+    }
+
+    /// Adds a new route with `PUT` method and the handler at the specified path, the state is passed to the handler as the second argument.
+    ///
+    /// Please refer to the [`add_with_state`](#method.add_with_state) method for more info.
+    #[track_caller]
+    pub fn put_with_state<P, S, A, H, R, T>(self, path: P, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_state(path, vec![Method::PUT], state, handler)
+    }
+
+    /// Adds a new route with `DELETE` method and the handler at the specified path, the state is passed to the handler as the second argument.
+    ///
+    /// Please refer to the [`add_with_state`](#method.add_with_state) method for more info.
+    #[track_caller]
+    pub fn delete_with_state<P, S, A, H, R, T>(self, path: P, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_state(path, vec![Method::DELETE], state, handler)
+    }
+
+    /// Adds a new route with `PATCH` method and the handler at the specified path, the state is passed to the handler as the second argument.
+    ///
+    /// Please refer to the [`add_with_state`](#method.add_with_state) method for more info.
+    #[track_caller]
+    pub fn patch_with_state<P, S, A, H, R, T>(self, path: P, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        self.add_with_state(path, vec![Method::PATCH], state, handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, the state is passed to the handler
+    /// as the second argument. So a plain `async fn(req, state)` function can be registered without reading the state from
+    /// the request by the [`data`](./ext/trait.RequestExt.html#tymethod.data) method.
+    ///
+    /// The state can be passed as a value or as an `Arc`, it's stored once by the route and the `Arc` is cloned for each request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Request, Body, Method};
+    /// use std::convert::Infallible;
+    /// use std::sync::Arc;
+    ///
+    /// struct Db {
+    ///     name: String,
+    /// }
+    ///
+    /// async fn user_handler(req: Request<Body>, db: Arc<Db>) -> Result<Response<Body>, Infallible> {
+    ///     Ok(Response::new(Body::from(format!("Fetching {} from {}", req.uri().path(), db.name))))
+    /// }
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let db = Arc::new(Db { name: "users".to_owned() });
+    ///
+    /// let router = Router::builder()
+    ///     .get_with_state("/users/:id", db.clone(), user_handler)
+    ///     .add_with_state("/users", vec![Method::GET, Method::HEAD], db, user_handler)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_state<P, S, A, H, R, T>(self, path: P, methods: Vec<Method>, state: A, handler: H) -> Self
+    where
+        P: Into<String>,
+        S: Send + Sync + 'static,
+        A: Into<Arc<S>>,
+        H: Fn(Request<ReqB>, Arc<S>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let state = state.into();
+        self.push_route(path, methods, move |req| handler(req, state.clone()), |_| Ok(()))
+    }
+
+    /// Adds a new route with `GET` method and the handler at the specified path, the errors of the handler are handled by the
+    /// specified error handler.
+    ///
+    /// Please refer to the [`add_with_err_handler`](#method.add_with_err_handler) method for more info.
+    #[track_caller]
+    pub fn get_with_err_handler<P, H, R, T, EH, ER>(self, path: P, handler: H, err_handler: EH) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        self.add_with_err_handler(path, vec![Method::GET], handler, err_handler)
+    }
+
+    /// Adds a new route with `POST` method and the handler at the specified path, the errors of the handler are handled by the
+    /// specified error handler.
+    ///
+    /// Please refer to the [`add_with_err_handler`](#method.add_with_err_handler) method for more info.
+    #[track_caller]
+    pub fn post_with_err_handler<P, H, R, T, EH, ER>(self, path: P, handler: H, err_handler: EH) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        self.add_with_err_handler(path, vec![Method::POST], handler, err_handler)
+    }
+
+    /// Adds a new route with `PUT` method and the handler at the specified path, the errors of the handler are handled by the
+    /// specified error handler.
+    ///
+    /// Please refer to the [`add_with_err_handler`](#method.add_with_err_handler) method for more info.
+    #[track_caller]
+    pub fn put_with_err_handler<P, H, R, T, EH, ER>(self, path: P, handler: H, err_handler: EH) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        self.add_with_err_handler(path, vec![Method::PUT], handler, err_handler)
+    }
+
+    /// Adds a new route with `DELETE` method and the handler at the specified path, the errors of the handler are handled by the
+    /// specified error handler.
+    ///
+    /// Please refer to the [`add_with_err_handler`](#method.add_with_err_handler) method for more info.
+    #[track_caller]
+    pub fn delete_with_err_handler<P, H, R, T, EH, ER>(self, path: P, handler: H, err_handler: EH) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        self.add_with_err_handler(path, vec![Method::DELETE], handler, err_handler)
+    }
+
+    /// Adds a new route with `PATCH` method and the handler at the specified path, the errors of the handler are handled by the
+    /// specified error handler.
+    ///
+    /// Please refer to the [`add_with_err_handler`](#method.add_with_err_handler) method for more info.
+    #[track_caller]
+    pub fn patch_with_err_handler<P, H, R, T, EH, ER>(self, path: P, handler: H, err_handler: EH) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        self.add_with_err_handler(path, vec![Method::PATCH], handler, err_handler)
+    }
+
+    /// Adds a new route with the specified method(s) and the handler at the specified path, the errors returned by the handler
+    /// are passed to the specified error handler instead of the router's one.
+    ///
+    /// The error handler of a route takes precedence over the error handler of a [scoped](#method.scope) router, which takes
+    /// precedence over the root router's one. The errors raised by the middlewares are handled by the error handler of the
+    /// router they're added to, and the response of the route's error handler goes through the post middlewares like any other
+    /// response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, Method, StatusCode};
+    /// use std::io;
+    ///
+    /// # fn run() -> Router<Body, io::Error> {
+    /// let router = Router::builder()
+    ///     .get_with_err_handler(
+    ///         "/api/users/:id",
+    ///         |_| async move { Err::<Response<Body>, _>(io::Error::new(io::ErrorKind::Other, "Database is down")) },
+    ///         |err: routerify::Error| async move {
+    ///             Response::builder()
+    ///                 .status(StatusCode::INTERNAL_SERVER_ERROR)
+    ///                 .header("content-type", "application/json")
+    ///                 .body(Body::from(format!("{{\"error\":\"{}\"}}", err)))
+    ///                 .unwrap()
+    ///         },
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn add_with_err_handler<P, H, R, T, EH, ER>(
+        self,
+        path: P,
+        methods: Vec<Method>,
+        handler: H,
+        err_handler: EH,
+    ) -> Self
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+        EH: Fn(crate::Error) -> ER + Send + Sync + 'static,
+        ER: Future + Send + 'static,
+        ER::Output: ErrHandlerOutput<B>,
+    {
+        let err_handler: ErrHandlerWithoutInfo<B> =
+            Box::new(move |err: crate::Error| Box::new(err_handler(err).map(ErrHandlerOutput::into_response)));
+
+        self.push_route(path, methods, handler, move |route| {
+            route.err_handler = Some(Arc::new(ErrHandler::WithoutInfo(err_handler)));
+            Ok(())
+        })
+    }
+
+    /// It mounts a router onto another router. It can be very useful when you want to write modular routing logic.
+    ///
+    /// The mount path is joined with the inner paths without duplicating or dropping any slash. A router mounted at `/` or at an
+    /// empty path serves its routes as they are, and the inner `/` route of a router mounted at e.g. `/api` serves both `/api`
+    /// and `/api/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Request, Body};
+    ///
+    /// mod api {
+    ///     use routerify::Router;
+    ///     use hyper::{Response, Request, Body};
+    ///
+    ///     pub fn router() -> Router<Body, hyper::Error> {
+    ///         Router::builder()
+    ///          .get("/users", |req| async move { Ok(Response::new(Body::from("User list"))) })
+    ///          .get("/books", |req| async move { Ok(Response::new(Body::from("Book list"))) })
+    ///          .build()
+    ///          .unwrap()
+    ///     }
+    /// }
+    ///
+    /// # fn run() -> Router<Body, hyper::Error> {
+    /// let router = Router::builder()
+    ///     // Now, mount the api router at `/api` path.
+    ///     .scope("/api", api::router())
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    ///
+    /// Now, the app can handle requests on: `/api/users` and `/api/books` paths.
+    ///
+    /// The routes and the middlewares of the mounted router are moved into this router, so they are matched by a single matcher
+    /// regardless of the nesting depth. Please refer to the [Scoping/Mounting Router](./index.html#scopingmounting-router) section for
+    /// the order of the mounted middlewares.
+    ///
+    /// The host scoped routers of the mounted router are ignored, please add them to the root router with the
+    /// [`host_scope`](#method.host_scope) method.
+    #[track_caller]
+    pub fn scope<P>(self, path: P, router: Router<B, E, ReqB>) -> Self
+    where
+        P: Into<String>,
+    {
+        self.scope_with_priority(path, 0, router)
+    }
+
+    /// Same as the [`scope`](#method.scope) method, but the specified priority is added to the priority of every route of the mounted
+    /// router. Please refer to the [`add_with_priority`](#method.add_with_priority) method for more info.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let health_router = Router::builder()
+    ///     .get("/health", |_| async move { Ok(Response::new(Body::from("OK"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .get("/:page", |_| async move { Ok(Response::new(Body::from("A page"))) })
+    ///     // The `/health` route is matched before the `/:page` route.
+    ///     .scope_with_priority("/", 10, health_router)
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn scope_with_priority<P>(self, path: P, priority: i32, mut router: Router<B, E, ReqB>) -> Self
+    where
+        P: Into<String>,
+    {
+        let location = Location::caller();
+        let path = helpers::normalize_scope_path(path.into());
+
+        let mut builder = self;
+
+        // The routes and the middlewares which don't have their own error handler inherit the one of the scoped router.
+        let scope_err_handler = router.err_handler.take().map(Arc::new);
+        builder.inner.err_mappers.append(&mut router.err_mappers);
+
+        for pre_middleware in router.pre_middlewares.iter_mut() {
+            let new_path = format!("{}{}", path.as_str(), pre_middleware.path.as_str());
+            let new_pre_middleware = PreMiddleware::new_with_boxed_handler(
+                new_path.as_str(),
+                pre_middleware
+                    .handler
+                    .take()
+                    .expect("No handler found in one of the pre-middlewares"),
+            );
+
+            let new_pre_middleware = new_pre_middleware.map(|mut new_pre_middleware| {
+                new_pre_middleware.err_handler =
+                    pre_middleware.err_handler.take().or_else(|| scope_err_handler.clone());
+                new_pre_middleware
+            });
+
+            match new_pre_middleware {
+                Ok(new_pre_middleware) => builder.inner.pre_middlewares.push(new_pre_middleware),
+                Err(err) => builder.push_error(BuildError::new(&[], new_path, location, err)),
+            }
+        }
+
+        for around_middleware in router.around_middlewares.iter_mut() {
+            let new_path = format!("{}{}", path.as_str(), around_middleware.path.as_str());
+            let new_around_middleware = AroundMiddleware::new_with_boxed_handler(
+                new_path.as_str(),
+                around_middleware
+                    .handler
+                    .take()
+                    .expect("No handler found in one of the around-middlewares"),
+            );
+
+            let new_around_middleware = new_around_middleware.map(|mut new_around_middleware| {
+                new_around_middleware.err_handler = around_middleware
+                    .err_handler
+                    .take()
+                    .or_else(|| scope_err_handler.clone());
+                new_around_middleware
+            });
+
+            match new_around_middleware {
+                Ok(new_around_middleware) => builder.inner.around_middlewares.push(new_around_middleware),
+                Err(err) => builder.push_error(BuildError::new(&[], new_path, location, err)),
+            }
+        }
+
+        for route in router.routes.iter_mut() {
+            let header_matcher = route.header_matcher.take();
+            let query_matcher = route.query_matcher.take();
+            let content_type = route.content_type.take();
+            let new_path = format!("{}{}", path.as_str(), route.path.as_str());
+            let new_route = Route::new_with_boxed_handler(
+                new_path.as_str(),
+                route.methods.clone(),
+                route.handler.take().expect("No handler found in one of the routes"),
+            )
+            .map(|mut new_route| {
+                new_route.header_matcher = header_matcher;
+                new_route.query_matcher = query_matcher;
+                new_route.content_type = content_type;
+                new_route.all_methods = route.all_methods;
+                new_route.priority = if route.fallback {
+                    route::fallback_priority(&new_path)
+                } else {
+                    route.priority.saturating_add(priority)
+                };
+                new_route.fallback = route.fallback;
+                new_route.err_handler = route.err_handler.take().or_else(|| scope_err_handler.clone());
+                new_route.mount_prefix = Arc::from(format!("{}{}", path, route.mount_prefix));
+                new_route.name = route.name.take();
+                new_route.trailing_slash = route.trailing_slash;
+                new_route.timeout = route.timeout;
+                new_route
+            });
+
+            match new_route {
+                Ok(new_route) => builder.inner.routes.push(new_route),
+                Err(err) => builder.push_error(BuildError::new(&route.methods, new_path, location, err)),
+            }
+        }
+
+        for post_middleware in router.post_middlewares.iter_mut() {
+            let new_path = format!("{}{}", path.as_str(), post_middleware.path.as_str());
+            let new_post_middleware = PostMiddleware::new_with_boxed_handler(
+                new_path.as_str(),
+                post_middleware
+                    .handler
+                    .take()
+                    .expect("No handler found in one of the post-middlewares"),
+            );
+
+            let new_post_middleware = new_post_middleware.map(|mut new_post_middleware| {
+                new_post_middleware.err_handler =
+                    post_middleware.err_handler.take().or_else(|| scope_err_handler.clone());
+                new_post_middleware
+            });
+
+            match new_post_middleware {
+                Ok(new_post_middleware) => builder.inner.post_middlewares.push(new_post_middleware),
+                Err(err) => builder.push_error(BuildError::new(&[], new_path, location, err)),
+            }
+        }
+
+        for scoped_data_map in router.scoped_data_maps.iter_mut() {
+            let new_path = format!("{}{}", path.as_str(), scoped_data_map.path.as_str());
+            let data_map = Arc::try_unwrap(
+                scoped_data_map
+                    .data_map
+                    .take()
+                    .expect("No data map found in one of the scoped data maps"),
+            )
+            .expect("Non-zero owner of the shared data map in one of the scoped data maps");
+
+            builder = builder.update(move |inner| {
+                let data_maps = &mut inner.data_maps;
+
+                let data_map_arr = data_maps.get_mut(&new_path);
+                if let Some(data_map_arr) = data_map_arr {
+                    data_map_arr.push(data_map);
+                } else {
+                    data_maps.insert(new_path, vec![data_map]);
+                }
+            });
+        }
+
+        for scoped_body_limit in router.scoped_body_limits.iter() {
+            let new_path = format!("{}{}", path.as_str(), scoped_body_limit.path.as_str());
+            let limit = scoped_body_limit.limit;
+
+            builder = builder.update(move |inner| {
+                inner.body_limits.push((new_path, limit));
+            });
+        }
+
+        builder
+    }
+
+    /// It mounts a router which handles the requests for the specified host only. The host is matched against the `Host` header
+    /// or the `:authority` pseudo-header of the HTTP/2 requests, ignoring the port.
+    ///
+    /// The host pattern can either be an exact host name e.g. `api.example.com`, or it can start with a wildcard e.g. `*.example.com`
+    /// which matches any subdomain. The portion matched by the wildcard can be accessed with the
+    /// [`host_param`](./ext/trait.RequestExt.html#tymethod.host_param) method. A host pattern can also have the named params
+    /// e.g. `:tenant.example.com` which match a single label each, they can be accessed with the
+    /// [`host_params`](./ext/trait.RequestExt.html#tymethod.host_params) method.
+    ///
+    /// The host is matched before the request path, so the requests to a matching host are processed with the middlewares and the
+    /// routes of the mounted router only. The requests to any other host fall through to the routes which are not host scoped.
+    /// If multiple host patterns match, the first added one wins. The error handler and the error mappers of the root router are
+    /// used for all hosts and the data shared on the root router is accessible from the mounted routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let api_router = Router::builder()
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("User list"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let tenant_router = Router::builder()
+    ///     .get("/", |req| async move {
+    ///         let tenant = req.host_param().unwrap().to_owned();
+    ///         Ok(Response::new(Body::from(format!("Welcome {}", tenant))))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder()
+    ///     .host_scope("api.example.com", api_router)
+    ///     .host_scope("*.example.com", tenant_router)
+    ///     // Handles the requests to any other host.
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    #[track_caller]
+    pub fn host_scope<H>(mut self, host: H, router: Router<B, E, ReqB>) -> Self
+    where
+        H: Into<String>,
+    {
+        let location = Location::caller();
+        let host = host.into();
+
+        match HostPattern::new(host.as_str()) {
+            Ok(pattern) => self.inner.host_routers.push(HostRouter { pattern, router }),
+            Err(err) => self.push_error(BuildError::new(&[], host, location, err)),
+        }
+
+        self
+    }
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RouterBuilder<B, E, ReqB>
+{
+    /// Adds a single middleware. A pre middleware can be created by [`Middleware::pre`](./enum.Middleware.html#method.pre) method and a post
+    /// middleware can be created by [`Middleware::post`](./enum.Middleware.html#method.post) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, Middleware};
+    /// use hyper::{Response, Request, Body};
+    /// use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///      // Create and attach a pre middleware.
+    ///      .middleware(Middleware::pre(|req| async move { /* Do some operations */ Ok(req) }))
+    ///      // Create and attach a post middleware.
+    ///      .middleware(Middleware::post(|res| async move { /* Do some operations */ Ok(res) }))
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn middleware(self, m: Middleware<B, E, ReqB>) -> Self {
+        self.update(move |inner| match m {
+            Middleware::Pre(middleware) => {
+                inner.pre_middlewares.push(middleware);
+            }
+            Middleware::Post(middleware) => {
+                inner.post_middlewares.push(middleware);
+            }
+            Middleware::Around(middleware) => {
+                inner.around_middlewares.push(middleware);
+            }
+        })
+    }
+
+    /// Specify app data to be shared across route handlers, middlewares and the error handler.
+    ///
+    /// Please refer to the [Data and State Sharing](./index.html#data-and-state-sharing) for more info.
+    pub fn data<T: Send + Sync + 'static>(self, data: T) -> Self {
+        self.update(move |inner| {
+            let data_maps = &mut inner.data_maps;
+
+            let data_map_arr = data_maps.get_mut(&"/*".to_owned());
+            if let Some(data_map_arr) = data_map_arr {
+                let first_data_map = data_map_arr.get_mut(0).unwrap();
+                first_data_map.insert(data);
+            } else {
+                let mut data_map = DataMap::new();
+                data_map.insert(data);
+                data_maps.insert("/*".to_owned(), vec![data_map]);
+            }
+        })
+    }
+
+    /// Specify the maximum size of the request body in bytes for all the routes.
+    ///
+    /// A request whose `Content-Length` header exceeds the limit is rejected before running any pre middlewares or the route
+    /// handler. A chunked request body is counted while it's being streamed and the reading is aborted as soon as it exceeds the limit.
+    ///
+    /// In both cases, a [`Error::RequestBodyTooLarge`](./enum.Error.html#variant.RequestBodyTooLarge) error is passed to the error handler and
+    /// the generated response goes through the post middlewares. The default error handler responds with `413 Payload Too Large` status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // Accept request bodies up to 1 MiB.
+    ///     .max_body_size(1024 * 1024)
+    ///     .post("/comments", |req| async move { Ok(Response::new(Body::from("Comment posted"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn max_body_size(self, bytes: u64) -> Self {
+        self.max_body_size_with_path("/*", bytes)
+    }
+
+    /// Specify the maximum size of the request body in bytes for the routes at the specified path. It overrides the router-wide
+    /// limit set by the [`max_body_size`](#method.max_body_size) method. If multiple limits match a request, the one with the
+    /// most specific path wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .max_body_size(1024 * 1024)
+    ///     // Allow bigger bodies for the upload route only.
+    ///     .max_body_size_with_path("/upload", 100 * 1024 * 1024)
+    ///     .post("/comments", |req| async move { Ok(Response::new(Body::from("Comment posted"))) })
+    ///     .post("/upload", |req| async move { Ok(Response::new(Body::from("File uploaded"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn max_body_size_with_path<P: Into<String>>(self, path: P, bytes: u64) -> Self {
+        self.update(move |inner| {
+            let mut path = path.into();
+
+            if !path.ends_with('/') && !path.ends_with('*') {
+                path.push('/');
+            }
+
+            inner.body_limits.push((path, bytes));
+        })
+    }
+
+    /// Specify whether a panic in a route handler or in a middleware should be caught. It's enabled by default.
+    ///
+    /// When enabled, the panic is converted into an [`Error::Panicked`](./enum.Error.html#variant.Panicked) error containing the panic message
+    /// and it's passed to the error handler like any other error, so the client receives a proper response instead of a reset connection.
+    /// A panic in the error handler itself is answered with a plain `500 Internal Server Error` response.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // Let the panics unwind through the connection task.
+    ///     .catch_panics(false)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn catch_panics(self, enable: bool) -> Self {
+        self.update(move |inner| {
+            inner.catch_panics = enable;
+        })
+    }
+
+    /// Specify the maximum time a route handler can take to respond, there's no limit by default.
+    ///
+    /// Once the time elapses, the handler is cancelled by dropping its future and an
+    /// [`Error::HandlerTimeout`](./enum.Error.html#variant.HandlerTimeout) error is passed to the error handler, so the post
+    /// middlewares still see the response. The default error handler responds with `504 Gateway Timeout`. The time spent in the
+    /// middlewares isn't counted, and a streamed response body isn't bounded once the handler returns it.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers. A route can have its own timeout which
+    /// overrides this one, please refer to the [`add_with_timeout`](#method.add_with_timeout) method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// use std::time::Duration;
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .timeout(Duration::from_secs(30))
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.update(move |inner| {
+            inner.timeout = Some(timeout);
+        })
+    }
+
+    /// Trusts the forwarded headers of the requests sent by the proxies in the range, so the
+    /// [`RequestExt::client_ip`](./ext/trait.RequestExt.html#tymethod.client_ip) method resolves the address of the client behind
+    /// them. It can be called multiple times to trust multiple ranges, no proxy is trusted by default.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{IpRange, Router};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> routerify::Result<Router<Body, Infallible>> {
+    /// let router = Router::builder()
+    ///     // The load balancers.
+    ///     .trusted_proxy("10.0.0.0/24".parse::<IpRange>()?)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # Ok(router)
+    /// # }
+    /// # run().unwrap();
+    /// ```
+    pub fn trusted_proxy(self, range: IpRange) -> Self {
+        self.update(move |inner| {
+            inner.trusted_proxies.push(range);
+        })
+    }
+
+    /// Specify whether a request should be responded with `405 Method Not Allowed` if a route matches its path but not its
+    /// method. It's enabled by default.
+    ///
+    /// When enabled, an [`Error::MethodNotAllowed`](./enum.Error.html#variant.MethodNotAllowed) error carrying the methods of the
+    /// routes matching the path is passed to the error handler instead of running the 404 route, i.e. the `/*` route for all
+    /// methods. The default error handler responds with the `Allow` header listing the methods. The other `/*` routes e.g.
+    /// a global `OPTIONS` route still handle the request.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // Respond with 404 to a `POST /` request.
+    ///     .method_not_allowed(false)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn method_not_allowed(self, enable: bool) -> Self {
+        self.update(move |inner| {
+            inner.method_not_allowed = enable;
+        })
+    }
+
+    /// Specify whether the default `OPTIONS` route should respond with the `Allow` header listing the methods of the routes
+    /// matching the request path, so the routes don't need their own `OPTIONS` handlers. It's disabled by default.
+    ///
+    /// The default `OPTIONS` route is only added to the routers with [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
+    /// response body, and an `OPTIONS` route added to the router takes precedence over it. The requests to the paths without
+    /// any routes are still responded with `204 No Content` and no `Allow` header.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // Respond to `OPTIONS /users` with `Allow: GET, POST, OPTIONS`.
+    ///     .auto_options(true)
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+    ///     .post("/users", |_| async move { Ok(Response::new(Body::from("Created"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn auto_options(self, enable: bool) -> Self {
+        self.update(move |inner| {
+            inner.auto_options = enable;
+        })
+    }
+
+    /// Specify whether a `GET` route should answer the `HEAD` requests to its path, so the routes don't need their own `HEAD`
+    /// handlers. It's disabled by default.
+    ///
+    /// The `GET` handler runs as usual and the body of its response is dropped, the `Content-Length` header is kept or set
+    /// from the size of the body if it's known. A route added for `HEAD` takes precedence over the `GET` route.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // Respond to `HEAD /users` with the headers of `GET /users`.
+    ///     .auto_head(true)
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn auto_head(self, enable: bool) -> Self {
+        self.update(move |inner| {
+            inner.auto_head = enable;
+        })
+    }
+
+    /// Specify the engine used to find the routes matching a request path, it's [`RouteMatcher::Trie`](./enum.RouteMatcher.html#variant.Trie)
+    /// by default. Both engines produce the same matches, so the routes are still matched in the order of their priorities and
+    /// registration.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, RouteMatcher};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .route_matcher(RouteMatcher::RegexSet)
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn route_matcher(self, matcher: RouteMatcher) -> Self {
+        self.update(move |inner| {
+            inner.route_matcher = matcher;
+        })
+    }
+
+    /// Enables a cache which remembers the matched routes and middlewares for up to `capacity` distinct request paths. The least
+    /// recently used path is evicted when the cache is full. It's disabled by default and a zero capacity disables it.
+    ///
+    /// It's useful when most of the traffic hits a small set of paths. The route params are still extracted for every request.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .match_cache(1024)
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn match_cache(self, capacity: usize) -> Self {
+        self.update(move |inner| {
+            inner.match_cache_capacity = capacity;
+        })
+    }
+
+    /// Specify how the request path is percent-decoded before matching it against the routes, it's
+    /// [`PercentDecodeMode::Full`](./enum.PercentDecodeMode.html#variant.Full) by default.
+    ///
+    /// A path which doesn't decode to valid UTF-8 is a client error, so a [`Error::DecodeRequestPath`](./enum.Error.html#variant.DecodeRequestPath)
+    /// error is passed to the error handler and the default error handler responds with `400 Bad Request` status.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, PercentDecodeMode};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // A request to `/files/docs%2Freadme.md` is routed to the handler below.
+    ///     .percent_decode(PercentDecodeMode::KeepEncodedSlashes)
+    ///     .get("/files/:name", |req| async move {
+    ///         // The param value is decoded to `docs/readme.md`.
+    ///         let name = req.param("name").unwrap().clone();
+    ///         Ok(Response::new(Body::from(name)))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn percent_decode(self, mode: PercentDecodeMode) -> Self {
+        self.update(move |inner| {
+            inner.percent_decode_mode = mode;
+        })
+    }
+
+    /// Specify how the consecutive slashes in the request path are handled, it's [`SlashNormalization::Off`](./enum.SlashNormalization.html#variant.Off)
+    /// by default. The slashes are collapsed before the path is percent-decoded, so the encoded slashes are kept intact.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, SlashNormalization};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // A `GET` request to `//users///3` is redirected to `/users/3`.
+    ///     .normalize_slashes(SlashNormalization::Redirect)
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn normalize_slashes(self, mode: SlashNormalization) -> Self {
+        self.update(move |inner| {
+            inner.slash_normalization = mode;
+        })
+    }
+
+    /// Specify how a trailing slash in the request path is handled, it's [`TrailingSlash::Ignore`](./enum.TrailingSlash.html#variant.Ignore)
+    /// by default i.e. `/users` and `/users/` are matched identically. A route's form is the path it's added with, the scoped
+    /// routes get the form of their own paths.
+    ///
+    /// Only the root router's setting is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, TrailingSlash};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     // A `GET` request to `/users/` is redirected to `/users` and `/docs` is redirected to `/docs/`.
+    ///     .trailing_slash(TrailingSlash::RedirectToCanonical)
+    ///     .get("/users", |_| async move { Ok(Response::new(Body::from("Users"))) })
+    ///     .get("/docs/", |_| async move { Ok(Response::new(Body::from("Docs"))) })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn trailing_slash(self, mode: TrailingSlash) -> Self {
+        self.update(move |inner| {
+            inner.trailing_slash = mode;
+        })
+    }
+
+    /// Registers a callback which is called with the [metrics](./struct.RequestMetrics.html) of every request once its response is
+    /// generated, i.e. after the post middlewares are run. It's also called for the responses generated by the error handler and the
+    /// default 404 route.
+    ///
+    /// The metrics are labeled by the path template of the matched route instead of the request path, so they can be recorded
+    /// without the label cardinality growing with the number of distinct paths. Nothing is recorded if no callback is registered.
+    ///
+    /// Only the root router's callback is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/users/:id", |_| async move { Ok(Response::new(Body::from("User"))) })
+    ///     // e.g. observe a latency histogram with the `route`, `method` and `status` labels.
+    ///     .on_request_complete(|metrics| {
+    ///         println!(
+    ///             "{} {} {} {:?}",
+    ///             metrics.method, metrics.route_path, metrics.status, metrics.latency
+    ///         );
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn on_request_complete<F>(self, callback: F) -> Self
+    where
+        F: Fn(&RequestMetrics<'_>) + Send + Sync + 'static,
+    {
+        let callback: RequestCompleteHook = Arc::new(callback);
+
+        self.update(move |inner| {
+            inner.on_request_complete = Some(callback);
+        })
+    }
+
+    /// Attaches a [`DynamicRoutes`](./struct.DynamicRoutes.html) table whose routes can be added and removed while the service is running.
+    /// They're matched after the routes added to this builder, but before the `/*` routes.
+    ///
+    /// Only the root router's table is considered, it's ignored for the scoped routers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{DynamicRoutes, Router};
+    /// use hyper::{Body, Method, Response};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let dynamic_routes = DynamicRoutes::new();
+    ///
+    /// let router = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home"))) })
+    ///     .dynamic_routes(dynamic_routes.clone())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// dynamic_routes
+    ///     .add("/about", vec![Method::GET], |_| async move { Ok(Response::new(Body::from("About"))) })
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn dynamic_routes(self, dynamic_routes: DynamicRoutes<B, E, ReqB>) -> Self {
+        self.update(move |inner| {
+            inner.dynamic_routes = Some(dynamic_routes);
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares. Please refer to [Error Handling](./index.html#error-handling) section
+    /// for more info.
+    ///
+    /// The error handler of a [scoped](#method.scope) router handles the errors of its route handlers and middlewares only, unless
+    /// a route has its own error handler added by e.g. the [`add_with_err_handler`](#method.add_with_err_handler) method. The
+    /// other errors fall back to the root router's error handler.
+    ///
+    /// The handler returns either a response or a `Result` of a response, if it fails a plain `500 Internal Server Error`
+    /// response is sent instead. Please refer to [`ErrHandlerOutput`](./handler/trait.ErrHandlerOutput.html) for more info.
+    pub fn err_handler<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::Error) -> R + Send + Sync + 'static,
+        R: Future + Send + 'static,
+        R::Output: ErrHandlerOutput<B>,
+    {
+        let handler: ErrHandlerWithoutInfo<B> =
+            Box::new(move |err: crate::Error| Box::new(handler(err).map(ErrHandlerOutput::into_response)));
+
+        self.update(move |inner| {
+            inner.err_handler = Some(ErrHandler::WithoutInfo(handler));
+        })
+    }
+
+    /// Adds a mapper which converts the errors of type `T` returned by the route handlers and the middlewares into responses,
+    /// e.g. to respond with a structured body for the errors of the application.
+    ///
+    /// The mappers are tried in the registration order before any error handler, an error which isn't of any mapped type is
+    /// passed to the error handlers as usual. The mappers of a [scoped](#method.scope) router apply to the whole router. Same
+    /// as the error handler, the mapper returns either a response or a `Result` of a response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use hyper::{Response, Body, StatusCode};
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct NotFound(String);
+    ///
+    /// impl fmt::Display for NotFound {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         write!(f, "{} not found", self.0)
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for NotFound {}
+    ///
+    /// # fn run() -> Router<Body, NotFound> {
+    /// let router = Router::builder()
+    ///     .get("/users/:id", |_| async move { Err::<Response<Body>, _>(NotFound("user".into())) })
+    ///     .map_err(|err: NotFound| async move {
+    ///         Response::builder()
+    ///             .status(StatusCode::NOT_FOUND)
+    ///             .body(Body::from(format!(r#"{{"error":"{}"}}"#, err)))
+    ///             .unwrap()
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn map_err<T, H, R>(self, mapper: H) -> Self
+    where
+        T: std::error::Error + 'static,
+        H: Fn(T) -> R + Send + Sync + 'static,
+        R: Future + Send + 'static,
+        R::Output: ErrHandlerOutput<B>,
+    {
+        let mapper: ErrMapper<B> = Box::new(move |err: crate::Error| {
+            err.downcast::<T>()
+                .map(|err| Box::new(mapper(err).map(ErrHandlerOutput::into_response)) as _)
+        });
 
-        self.and_then(move |mut inner| {
+        self.update(move |inner| {
+            inner.err_mappers.push(mapper);
+        })
+    }
+
+    /// Adds a handler to handle any error raised by the routes or any middlewares.
+    ///
+    /// Here, the handler also access [request info](./struct.RequestInfo.html) e.g. headers, method, uri etc to generate response based on the request information.
+    /// The [`matched_route`](./struct.RequestInfo.html#method.matched_route) is set if the error is raised after a route is matched.
+    ///
+    /// Please refer to [Error Handling](./index.html#error-handling) section
+    /// for more info.
+    pub fn err_handler_with_info<H, R>(self, handler: H) -> Self
+    where
+        H: Fn(crate::Error, RequestInfo) -> R + Send + Sync + 'static,
+        R: Future + Send + 'static,
+        R::Output: ErrHandlerOutput<B>,
+    {
+        let handler: ErrHandlerWithInfo<B> = Box::new(move |err: crate::Error, req_info: RequestInfo| {
+            Box::new(handler(err, req_info).map(ErrHandlerOutput::into_response))
+        });
+
+        self.update(move |inner| {
             inner.err_handler = Some(ErrHandler::WithInfo(handler));
-            crate::Result::Ok(inner)
         })
     }
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Default
-    for RouterBuilder<B, E>
+impl<E: std::error::Error + Send + Sync + Unpin + 'static, ReqB: HttpBody + Send + 'static>
+    RouterBuilder<Body, E, ReqB>
+{
+    /// Enables `CORS` for the routes of this router by adding the [pre](./cors/struct.Cors.html#method.pre_middleware) and the
+    /// [post](./cors/struct.Cors.html#method.post_middleware) middlewares of the options. It can be called on a router which is
+    /// mounted by the [`scope`](#method.scope) method to enable `CORS` for the scope only.
+    ///
+    /// Please refer to the [cors](./cors/index.html) module for an example.
+    pub fn cors(self, cors: Cors) -> Self {
+        self.middleware(cors.pre_middleware())
+            .middleware(cors.post_middleware())
+    }
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Default for RouterBuilder<B, E, ReqB>
 {
-    fn default() -> RouterBuilder<B, E> {
+    fn default() -> RouterBuilder<B, E, ReqB> {
         RouterBuilder {
-            inner: Ok(BuilderInner {
+            inner: BuilderInner {
                 pre_middlewares: Vec::new(),
+                around_middlewares: Vec::new(),
                 routes: Vec::new(),
                 post_middlewares: Vec::new(),
                 data_maps: HashMap::new(),
+                body_limits: Vec::new(),
+                host_routers: Vec::new(),
                 err_handler: None,
-            }),
+                err_mappers: Vec::new(),
+                catch_panics: true,
+                route_matcher: RouteMatcher::default(),
+                match_cache_capacity: 0,
+                percent_decode_mode: PercentDecodeMode::default(),
+                slash_normalization: SlashNormalization::default(),
+                trailing_slash: TrailingSlash::default(),
+                on_request_complete: None,
+                dynamic_routes: None,
+                method_not_allowed: true,
+                auto_options: false,
+                auto_head: false,
+                timeout: None,
+                trusted_proxies: Vec::new(),
+            },
+            errors: Vec::new(),
         }
     }
 }
@@ -0,0 +1,204 @@
+use crate::response::IntoResponse;
+use crate::route::{self, Route};
+use crate::router::matcher::{RouteIndex, RouteMatcher};
+use hyper::{body::HttpBody, Method, Request};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// A table of routes which can be added and removed while the service is running.
+///
+/// It's attached to the root router by the [`RouterBuilder::dynamic_routes`](./struct.RouterBuilder.html#method.dynamic_routes)
+/// method and it's cheap to clone, all the clones share the same routes. Every change rebuilds the route matcher and swaps it in
+/// atomically, the requests already being processed keep using the routes they were matched against.
+///
+/// The dynamic routes are matched after the routes added to the router builder, but before the `/*` routes e.g. the
+/// default 404 route.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{DynamicRoutes, Router};
+/// use hyper::{Body, Method, Response};
+/// # use std::convert::Infallible;
+///
+/// # fn run() -> Router<Body, Infallible> {
+/// let dynamic_routes = DynamicRoutes::new();
+///
+/// let router = Router::builder()
+///     .dynamic_routes(dynamic_routes.clone())
+///     .build()
+///     .unwrap();
+///
+/// // Later, e.g. when a plugin is loaded.
+/// let handle = dynamic_routes
+///     .add("/plugins/hello", vec![Method::GET], |_| async move { Ok(Response::new(Body::from("Hello"))) })
+///     .unwrap();
+///
+/// // And when it's unloaded.
+/// dynamic_routes.remove(handle);
+/// # router
+/// # }
+/// # run();
+/// ```
+pub struct DynamicRoutes<B, E, ReqB = hyper::Body> {
+    inner: Arc<DynamicRoutesInner<B, E, ReqB>>,
+}
+
+struct DynamicRoutesInner<B, E, ReqB> {
+    next_id: AtomicU64,
+    snapshot: RwLock<Arc<Snapshot<B, E, ReqB>>>,
+}
+
+pub(crate) struct Snapshot<B, E, ReqB> {
+    ids: Vec<u64>,
+    pub(crate) routes: Vec<Arc<Route<B, E, ReqB>>>,
+    route_index: RouteIndex,
+}
+
+/// A handle to a route added to [`DynamicRoutes`](./struct.DynamicRoutes.html), which can be used to remove it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteHandle(u64);
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > DynamicRoutes<B, E, ReqB>
+{
+    /// Creates an empty route table.
+    pub fn new() -> DynamicRoutes<B, E, ReqB> {
+        let snapshot = Snapshot::new(Vec::new(), Vec::new()).expect("An empty route index can't fail");
+
+        DynamicRoutes {
+            inner: Arc::new(DynamicRoutesInner {
+                next_id: AtomicU64::new(0),
+                snapshot: RwLock::new(Arc::new(snapshot)),
+            }),
+        }
+    }
+
+    /// Adds a new route with the specified methods and the handler at the specified path, it's served by the next requests.
+    pub fn add<P, H, R, T>(&self, path: P, methods: Vec<Method>, handler: H) -> crate::Result<RouteHandle>
+    where
+        P: Into<String>,
+        H: Fn(Request<ReqB>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: IntoResponse<B> + 'static,
+    {
+        let mut path = path.into();
+        let trailing_slash = route::trailing_slash_of(&path);
+
+        if !path.ends_with('/') && !path.ends_with('*') {
+            path.push('/');
+        }
+
+        let mut route = Route::new(path, methods, handler)?;
+        route.trailing_slash = trailing_slash;
+        let route = Arc::new(route);
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        self.update(|ids, routes| {
+            ids.push(id);
+            routes.push(route);
+        })?;
+
+        Ok(RouteHandle(id))
+    }
+
+    /// Removes a route, it returns `false` if the route was already removed.
+    ///
+    /// The requests which are already being processed by the route are not affected.
+    pub fn remove(&self, handle: RouteHandle) -> bool {
+        let mut removed = false;
+
+        // Removing a route can't make a valid route index invalid.
+        let _ = self.update(|ids, routes| {
+            if let Some(pos) = ids.iter().position(|id| *id == handle.0) {
+                ids.remove(pos);
+                routes.remove(pos);
+                removed = true;
+            }
+        });
+
+        removed
+    }
+
+    /// Returns the number of the routes.
+    pub fn len(&self) -> usize {
+        self.snapshot().routes.len()
+    }
+
+    /// Checks if there are no routes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The write lock is held while rebuilding, so the concurrent changes are applied one by one.
+    fn update<F>(&self, func: F) -> crate::Result<()>
+    where
+        F: FnOnce(&mut Vec<u64>, &mut Vec<Arc<Route<B, E, ReqB>>>),
+    {
+        let mut snapshot = self.inner.snapshot.write().unwrap_or_else(|err| err.into_inner());
+
+        let mut ids = snapshot.ids.clone();
+        let mut routes = snapshot.routes.clone();
+        func(&mut ids, &mut routes);
+
+        *snapshot = Arc::new(Snapshot::new(ids, routes)?);
+        Ok(())
+    }
+}
+
+impl<B, E, ReqB> DynamicRoutes<B, E, ReqB> {
+    pub(crate) fn snapshot(&self) -> Arc<Snapshot<B, E, ReqB>> {
+        self.inner
+            .snapshot
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl<B, E, ReqB> Snapshot<B, E, ReqB> {
+    fn new(ids: Vec<u64>, routes: Vec<Arc<Route<B, E, ReqB>>>) -> crate::Result<Snapshot<B, E, ReqB>> {
+        let route_index = RouteIndex::new(RouteMatcher::default(), &routes)?;
+
+        Ok(Snapshot {
+            ids,
+            routes,
+            route_index,
+        })
+    }
+
+    /// Returns the indices of the matched routes in the registration order.
+    pub(crate) fn matches(&self, target_path: &str) -> Vec<usize> {
+        self.route_index.matches(target_path)
+    }
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Default for DynamicRoutes<B, E, ReqB>
+{
+    fn default() -> DynamicRoutes<B, E, ReqB> {
+        DynamicRoutes::new()
+    }
+}
+
+impl<B, E, ReqB> Clone for DynamicRoutes<B, E, ReqB> {
+    fn clone(&self) -> Self {
+        DynamicRoutes {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<B, E, ReqB> Debug for DynamicRoutes<B, E, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ routes: {:?} }}", self.snapshot().routes)
+    }
+}
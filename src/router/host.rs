@@ -0,0 +1,222 @@
+use crate::router::Router;
+use crate::types::RouteParams;
+use crate::Error;
+use hyper::{header, Request};
+use std::fmt::{self, Debug, Formatter};
+
+pub(crate) struct HostRouter<B, E, ReqB> {
+    pub(crate) pattern: HostPattern,
+    pub(crate) router: Router<B, E, ReqB>,
+}
+
+impl<B, E, ReqB> Debug for HostRouter<B, E, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ pattern: {:?}, router: {:?} }}", self.pattern, self.router)
+    }
+}
+
+/// A host pattern is either an exact host name e.g. `api.example.com`, a host name with a leading wildcard
+/// e.g. `*.example.com` which matches any non-empty subdomain, or a host name with the named labels e.g. `:tenant.example.com`
+/// which match a single label each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum HostPattern {
+    Exact(String),
+    Wildcard(String),
+    Params(Vec<HostLabel>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum HostLabel {
+    Static(String),
+    Param(String),
+}
+
+impl HostPattern {
+    pub fn new<H: Into<String>>(pattern: H) -> crate::Result<HostPattern> {
+        let pattern = pattern.into();
+        let lower = pattern.to_ascii_lowercase();
+
+        let host_pattern = if let Some(suffix) = lower.strip_prefix('*') {
+            if !suffix.starts_with('.') || suffix.len() == 1 {
+                return Err(Error::InvalidHostPattern(pattern));
+            }
+            HostPattern::Wildcard(suffix.to_owned())
+        } else if lower.split('.').any(|label| label.starts_with(':')) {
+            // The param names keep their case as they're looked up by the handlers.
+            let labels = pattern
+                .split('.')
+                .map(|label| match label.strip_prefix(':') {
+                    Some(name) if !name.is_empty() && !name.contains(['*', '/', ':']) => {
+                        Ok(HostLabel::Param(name.to_owned()))
+                    }
+                    None if !label.is_empty() && !label.contains(['*', '/', ':']) => {
+                        Ok(HostLabel::Static(label.to_ascii_lowercase()))
+                    }
+                    _ => Err(Error::InvalidHostPattern(pattern.clone())),
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            return Ok(HostPattern::Params(labels));
+        } else {
+            HostPattern::Exact(lower)
+        };
+
+        match host_pattern {
+            HostPattern::Exact(ref host) | HostPattern::Wildcard(ref host)
+                if host.is_empty() || host.contains(['*', '/', ':']) =>
+            {
+                Err(Error::InvalidHostPattern(pattern))
+            }
+            _ => Ok(host_pattern),
+        }
+    }
+
+    /// Matches the host name without the port, it returns the captured wildcard portion and the named params on a match.
+    /// The portion is an empty string for an exact pattern and the value of the first param for a pattern with the params.
+    pub fn matches<'a>(&self, host: &'a str) -> Option<(&'a str, RouteParams)> {
+        match self {
+            HostPattern::Exact(ref exact) => {
+                if host.eq_ignore_ascii_case(exact) {
+                    Some(("", RouteParams::new()))
+                } else {
+                    None
+                }
+            }
+            HostPattern::Params(ref labels) => {
+                let host_labels = host.split('.').collect::<Vec<_>>();
+                if host_labels.len() != labels.len() {
+                    return None;
+                }
+
+                let mut first_param = None;
+                let mut params = RouteParams::with_capacity(labels.len());
+
+                for (label, host_label) in labels.iter().zip(host_labels) {
+                    match label {
+                        HostLabel::Static(ref label) if host_label.eq_ignore_ascii_case(label) => {}
+                        HostLabel::Param(ref name) if !host_label.is_empty() => {
+                            first_param.get_or_insert(host_label);
+                            params.append(name.as_str(), host_label);
+                        }
+                        _ => return None,
+                    }
+                }
+
+                Some((first_param.unwrap_or_default(), params))
+            }
+            HostPattern::Wildcard(ref suffix) => {
+                if host.len() <= suffix.len() || !host.is_char_boundary(host.len() - suffix.len()) {
+                    return None;
+                }
+
+                let (wildcard, rest) = host.split_at(host.len() - suffix.len());
+                if rest.eq_ignore_ascii_case(suffix) {
+                    Some((wildcard, RouteParams::new()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Extracts the host name of the request from the `Host` header, or from the URI authority for the HTTP/2 requests
+/// which carry it in the `:authority` pseudo-header. The port is stripped off.
+pub(crate) fn request_host<ReqB>(req: &Request<ReqB>) -> Option<&str> {
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|val| val.to_str().ok())
+        .or_else(|| req.uri().authority().map(|authority| authority.as_str()))?;
+
+    // Drop any userinfo which is allowed in a URI authority.
+    let host = host.rsplit('@').next().unwrap_or(host);
+
+    Some(strip_port(host))
+}
+
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        // An IPv6 literal e.g. `[::1]:8080`.
+        return match host.find(']') {
+            Some(idx) => &host[..=idx],
+            None => host,
+        };
+    }
+
+    match host.rfind(':') {
+        Some(idx) => &host[..idx],
+        None => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_pattern() {
+        fn matches<'a>(pattern: &HostPattern, host: &'a str) -> Option<&'a str> {
+            pattern.matches(host).map(|(host_param, _)| host_param)
+        }
+
+        let pattern = HostPattern::new("api.example.com").unwrap();
+        assert_eq!(matches(&pattern, "api.example.com"), Some(""));
+        assert_eq!(matches(&pattern, "API.Example.com"), Some(""));
+        assert_eq!(matches(&pattern, "www.example.com"), None);
+
+        let pattern = HostPattern::new("*.example.com").unwrap();
+        assert_eq!(matches(&pattern, "tenant.example.com"), Some("tenant"));
+        assert_eq!(matches(&pattern, "a.b.example.com"), Some("a.b"));
+        assert_eq!(matches(&pattern, "example.com"), None);
+        assert_eq!(matches(&pattern, ".example.com"), None);
+        assert_eq!(matches(&pattern, "example.org"), None);
+
+        assert!(HostPattern::new("").is_err());
+        assert!(HostPattern::new("*").is_err());
+        assert!(HostPattern::new("*example.com").is_err());
+        assert!(HostPattern::new("api.*.com").is_err());
+        assert!(HostPattern::new("example.com:8080").is_err());
+        assert!(HostPattern::new(":.example.com").is_err());
+        assert!(HostPattern::new(":tenant..com").is_err());
+    }
+
+    #[test]
+    fn test_host_pattern_params() {
+        let pattern = HostPattern::new(":tenant.:region.Example.com").unwrap();
+        let (host_param, params) = pattern.matches("acme.EU.example.com").unwrap();
+        assert_eq!(host_param, "acme");
+        assert_eq!(params.get("tenant").unwrap(), "acme");
+        assert_eq!(params.get("region").unwrap(), "EU");
+
+        assert!(pattern.matches("acme.example.com").is_none());
+        assert!(pattern.matches("a.acme.eu.example.com").is_none());
+        assert!(pattern.matches(".eu.example.com").is_none());
+        assert!(pattern.matches("acme.eu.example.org").is_none());
+    }
+
+    #[test]
+    fn test_request_host() {
+        let req = Request::builder()
+            .uri("/")
+            .header(header::HOST, "api.example.com:8080")
+            .body(())
+            .unwrap();
+        assert_eq!(request_host(&req), Some("api.example.com"));
+
+        let req = Request::builder()
+            .uri("https://www.example.com/about")
+            .body(())
+            .unwrap();
+        assert_eq!(request_host(&req), Some("www.example.com"));
+
+        let req = Request::builder()
+            .uri("/")
+            .header(header::HOST, "[::1]:3000")
+            .body(())
+            .unwrap();
+        assert_eq!(request_host(&req), Some("[::1]"));
+
+        let req = Request::builder().uri("/").body(()).unwrap();
+        assert_eq!(request_host(&req), None);
+    }
+}
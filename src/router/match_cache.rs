@@ -0,0 +1,67 @@
+use lru::LruCache;
+use std::fmt::{self, Debug, Formatter};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+/// A bounded LRU cache which maps a target path to its match result. It's shared by all the connections, so the entries are
+/// guarded by a mutex which is held only while looking up or inserting an entry.
+pub(crate) struct MatchCache<T> {
+    inner: Mutex<LruCache<String, Arc<T>>>,
+}
+
+impl<T> MatchCache<T> {
+    pub(crate) fn new(capacity: usize) -> MatchCache<T> {
+        let capacity = NonZeroUsize::new(capacity).expect("The match cache capacity must not be zero");
+
+        MatchCache {
+            inner: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub(crate) fn get_or_insert_with<F>(&self, key: &str, f: F) -> Arc<T>
+    where
+        F: FnOnce() -> T,
+    {
+        if let Some(value) = self.lock().get(key) {
+            return value.clone();
+        }
+
+        // The value is computed without holding the lock, a concurrent miss for the same key just computes it twice.
+        let value = Arc::new(f());
+        self.lock().put(key.to_owned(), value.clone());
+        value
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<String, Arc<T>>> {
+        // A panic can't leave the cache in a broken state as the entries are only touched by the methods above.
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<T> Debug for MatchCache<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let cache = self.lock();
+        write!(f, "{{ capacity: {:?}, len: {:?} }}", cache.cap(), cache.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_cache_evicts_least_recently_used() {
+        let cache = MatchCache::new(2);
+
+        assert_eq!(*cache.get_or_insert_with("/a/", || 1), 1);
+        assert_eq!(*cache.get_or_insert_with("/b/", || 2), 2);
+        // Touch "/a/" so "/b/" becomes the least recently used entry.
+        assert_eq!(*cache.get_or_insert_with("/a/", || 10), 1);
+        assert_eq!(*cache.get_or_insert_with("/c/", || 3), 3);
+
+        assert_eq!(*cache.get_or_insert_with("/a/", || 10), 1);
+        assert_eq!(*cache.get_or_insert_with("/c/", || 30), 3);
+        assert_eq!(*cache.get_or_insert_with("/b/", || 20), 20);
+        assert_eq!(cache.lock().len(), 2);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::helpers;
+use crate::route::{Route, Segment};
+use crate::Error;
+use regex::RegexSet;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+/// The engine used by a router to find the routes matching a request path.
+///
+/// It can be selected by the [`RouterBuilder::route_matcher`](./struct.RouterBuilder.html#method.route_matcher) method. Both engines
+/// produce the same matches, they differ in performance only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum RouteMatcher {
+    /// The route paths are split into segments and the static, the `:param` and the trailing `*` segments are put into a prefix
+    /// trie, so the lookup cost mostly depends on the path length instead of the number of routes, and the params are extracted
+    /// without the regex captures. The routes with a wildcard in the middle or with a param inside a segment e.g. `/files/v:version`
+    /// are matched by a `RegexSet`. This is the default engine.
+    #[default]
+    Trie,
+
+    /// All the routes are matched by a single `RegexSet`.
+    RegexSet,
+}
+
+pub(crate) enum RouteIndex {
+    RegexSet(RegexSet),
+    Trie {
+        trie: TrieNode,
+        fallback_set: RegexSet,
+        fallback_idxs: Vec<usize>,
+    },
+}
+
+impl RouteIndex {
+    pub(crate) fn new<B, E, ReqB, R: Borrow<Route<B, E, ReqB>>>(
+        matcher: RouteMatcher,
+        routes: &[R],
+    ) -> crate::Result<RouteIndex> {
+        match matcher {
+            RouteMatcher::RegexSet => {
+                let regex_set = RegexSet::new(routes.iter().map(|r| r.borrow().regex.as_str()))
+                    .map_err(Error::CreateRouterRegexSet)?;
+                Ok(RouteIndex::RegexSet(regex_set))
+            }
+            RouteMatcher::Trie => {
+                let mut trie = TrieNode::default();
+                let mut fallback_idxs = Vec::new();
+
+                for (idx, route) in routes.iter().map(Borrow::borrow).enumerate() {
+                    match route.segments {
+                        Some(ref segments) => trie.insert(segments, idx),
+                        None => fallback_idxs.push(idx),
+                    }
+                }
+
+                let fallback_set = RegexSet::new(fallback_idxs.iter().map(|idx| routes[*idx].borrow().regex.as_str()))
+                    .map_err(Error::CreateRouterRegexSet)?;
+
+                Ok(RouteIndex::Trie {
+                    trie,
+                    fallback_set,
+                    fallback_idxs,
+                })
+            }
+        }
+    }
+
+    /// Returns the indices of the matched routes in ascending order, so the first registered route still wins.
+    pub(crate) fn matches(&self, target_path: &str) -> Vec<usize> {
+        match self {
+            RouteIndex::RegexSet(ref regex_set) => regex_set.matches(target_path).into_iter().collect(),
+            RouteIndex::Trie {
+                ref trie,
+                ref fallback_set,
+                ref fallback_idxs,
+            } => {
+                let mut route_idxs = Vec::new();
+
+                if let Some(segments) = helpers::path_segments(target_path) {
+                    trie.collect_matches(&segments, &mut route_idxs);
+                }
+
+                if !fallback_idxs.is_empty() {
+                    route_idxs.extend(
+                        fallback_set
+                            .matches(target_path)
+                            .into_iter()
+                            .map(|idx| fallback_idxs[idx]),
+                    );
+                }
+
+                route_idxs.sort_unstable();
+                route_idxs
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct TrieNode {
+    statics: HashMap<String, TrieNode>,
+    param: Option<Box<TrieNode>>,
+    route_idxs: Vec<usize>,
+    // The routes ending with a wildcard at this node, they match the rest of the path including an empty one.
+    wildcard_idxs: Vec<usize>,
+}
+
+impl TrieNode {
+    fn insert(&mut self, segments: &[Segment], route_idx: usize) {
+        let mut node = self;
+
+        for segment in segments {
+            node = match segment {
+                Segment::Static(ref s) => node.statics.entry(s.clone()).or_default(),
+                Segment::Param => node.param.get_or_insert_with(Default::default),
+                Segment::Wildcard => {
+                    node.wildcard_idxs.push(route_idx);
+                    return;
+                }
+            };
+        }
+
+        node.route_idxs.push(route_idx);
+    }
+
+    fn collect_matches(&self, segments: &[&str], route_idxs: &mut Vec<usize>) {
+        route_idxs.extend_from_slice(&self.wildcard_idxs);
+
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                route_idxs.extend_from_slice(&self.route_idxs);
+                return;
+            }
+        };
+
+        if let Some(node) = self.statics.get(*segment) {
+            node.collect_matches(rest, route_idxs);
+        }
+
+        // A param never matches an empty segment.
+        if let Some(ref node) = self.param {
+            if !segment.is_empty() {
+                node.collect_matches(rest, route_idxs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::{Body, Method, Response};
+    use std::convert::Infallible;
+
+    fn routes(paths: &[&str]) -> Vec<Route<Body, Infallible>> {
+        paths
+            .iter()
+            .map(|path| {
+                Route::new(
+                    *path,
+                    vec![Method::GET],
+                    |_| async move { Ok(Response::new(Body::empty())) },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trie_matches_like_regex_set() {
+        let routes = routes(&[
+            "/",
+            "/users/",
+            "/users/:id/",
+            "/users/me/",
+            "/users/:id/books/:book/",
+            "/files/v:version/",
+            "/assets/*",
+            "//",
+            "/users/:id/*",
+            "/v:version/*",
+            "/*",
+        ]);
+        let trie = RouteIndex::new(RouteMatcher::Trie, &routes).unwrap();
+        let regex_set = RouteIndex::new(RouteMatcher::RegexSet, &routes).unwrap();
+
+        for path in &[
+            "/",
+            "/users/",
+            "/users/10/",
+            "/users/me/",
+            "/users/10/books/rust/",
+            "/users//books/rust/",
+            "/files/v2/",
+            "/assets/css/main.css/",
+            "/assets/",
+            "/assetsx/",
+            "/users/10/books/",
+            "/users//",
+            "/v1/docs/",
+            "//",
+            "*/",
+            "/unknown/path/",
+        ] {
+            assert_eq!(trie.matches(path), regex_set.matches(path), "path: {}", path);
+        }
+
+        assert_eq!(trie.matches("/users/me/"), vec![2, 3, 8, 10]);
+
+        // Only the routes with a param inside a segment are left to the regex.
+        match trie {
+            RouteIndex::Trie { ref fallback_idxs, .. } => assert_eq!(fallback_idxs, &vec![5, 9]),
+            RouteIndex::RegexSet(_) => unreachable!(),
+        }
+    }
+}
@@ -1,44 +1,75 @@
+use crate::body::{self, ScopedBodyLimit};
 use crate::constants;
 use crate::data_map::ScopedDataMap;
-use crate::middleware::{PostMiddleware, PreMiddleware};
+use crate::error::{self, Error};
+use crate::handler;
+use crate::helpers::{self, RawPath};
+use crate::middleware::{AroundMiddleware, PostMiddleware, PreMiddleware, PreOutcome};
+use crate::regex_generator;
 use crate::route::Route;
-use crate::types::RequestInfo;
-use crate::Error;
+#[cfg(feature = "tracing")]
+use crate::trace;
+use crate::types::{
+    IpRange, MatchedRoutePath, PercentDecodeMode, RequestInfo, RequestMeta, RequestMetrics, SlashNormalization,
+    TrailingSlash,
+};
+use futures::future::{self, Either, FutureExt};
 use hyper::{
     body::HttpBody,
     header::{self, HeaderValue},
     Method, Request, Response, StatusCode,
 };
 use regex::RegexSet;
-use std::any::Any;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub use self::builder::RouterBuilder;
+pub use self::dynamic::{DynamicRoutes, RouteHandle};
+pub(crate) use self::host::{HostPattern, HostRouter};
+use self::match_cache::MatchCache;
+use self::matcher::RouteIndex;
+pub use self::matcher::RouteMatcher;
 
 mod builder;
+mod dynamic;
+mod host;
+mod match_cache;
+mod matcher;
 
 pub(crate) type ErrHandlerWithoutInfo<B> =
-    Box<dyn FnMut(crate::Error) -> ErrHandlerWithoutInfoReturn<B> + Send + Sync + 'static>;
+    Box<dyn Fn(crate::Error) -> ErrHandlerWithoutInfoReturn<B> + Send + Sync + 'static>;
 pub(crate) type ErrHandlerWithoutInfoReturn<B> = Box<dyn Future<Output = Response<B>> + Send + 'static>;
 
+// Converts the error into a response if it's of the mapped type, otherwise the error is returned back.
+pub(crate) type ErrMapper<B> =
+    Box<dyn Fn(crate::Error) -> Result<ErrHandlerWithoutInfoReturn<B>, crate::Error> + Send + Sync + 'static>;
+
 pub(crate) type ErrHandlerWithInfo<B> =
-    Box<dyn FnMut(crate::Error, RequestInfo) -> ErrHandlerWithInfoReturn<B> + Send + Sync + 'static>;
+    Box<dyn Fn(crate::Error, RequestInfo) -> ErrHandlerWithInfoReturn<B> + Send + Sync + 'static>;
 pub(crate) type ErrHandlerWithInfoReturn<B> = Box<dyn Future<Output = Response<B>> + Send + 'static>;
 
 /// Represents a modular, lightweight and mountable router type.
 ///
 /// A router consists of some routes, some pre-middlewares and some post-middlewares.
 ///
-/// This `Router<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `Router<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
 ///
-/// A `Router` can be created using the `Router::builder()` method.
+/// A `Router` can be created using the `Router::builder()` method, or using the `RouterBuilder::new()` method for a request body type
+/// other than `hyper::Body`.
 ///
 /// # Examples
 ///
@@ -62,81 +93,217 @@ pub(crate) type ErrHandlerWithInfoReturn<B> = Box<dyn Future<Output = Response<B
 /// # }
 /// # run();
 /// ```
-pub struct Router<B, E> {
-    pub(crate) pre_middlewares: Vec<PreMiddleware<E>>,
-    pub(crate) routes: Vec<Route<B, E>>,
+pub struct Router<B, E, ReqB = hyper::Body> {
+    pub(crate) pre_middlewares: Vec<PreMiddleware<E, ReqB, B>>,
+    pub(crate) around_middlewares: Vec<AroundMiddleware<B, E, ReqB>>,
+    pub(crate) routes: Vec<Route<B, E, ReqB>>,
     pub(crate) post_middlewares: Vec<PostMiddleware<B, E>>,
     pub(crate) scoped_data_maps: Vec<ScopedDataMap>,
+    pub(crate) scoped_body_limits: Vec<ScopedBodyLimit>,
 
-    // This handler should be added only on root Router.
-    // Any error handler attached to scoped router will be ignored.
+    // The host scoped routers are only considered on the root Router.
+    pub(crate) host_routers: Vec<HostRouter<B, E, ReqB>>,
+
+    // The error handler of a scoped router is moved to its routes and middlewares, it only handles their errors.
     pub(crate) err_handler: Option<ErrHandler<B>>,
 
+    // The error mappers of a scoped router are added to the parent router, only the root Router's ones are considered.
+    pub(crate) err_mappers: Vec<ErrMapper<B>>,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) catch_panics: bool,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) route_matcher: RouteMatcher,
+
+    // Same as the error handler, only the root Router's value is considered. Zero means no cache.
+    pub(crate) match_cache_capacity: usize,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) percent_decode_mode: PercentDecodeMode,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) slash_normalization: SlashNormalization,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) trailing_slash: TrailingSlash,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) on_request_complete: Option<RequestCompleteHook>,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) dynamic_routes: Option<DynamicRoutes<B, E, ReqB>>,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) method_not_allowed: bool,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) auto_options: bool,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) auto_head: bool,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) timeout: Option<Duration>,
+
+    // Same as the error handler, only the root Router's value is considered.
+    pub(crate) trusted_proxies: Arc<[IpRange]>,
+
     // We'll initialize it from the RouterService via Router::init_regex_set() method.
     regex_set: Option<RegexSet>,
 
+    // We'll initialize it from the RouterService via Router::init_regex_set() method.
+    route_index: Option<RouteIndex>,
+
+    // We'll initialize it from the RouterService via Router::init_regex_set() method.
+    match_cache: Option<MatchCache<RegexSetMatches>>,
+
     // We'll initialize it from the RouterService via Router::init_req_info_gen() method.
     pub(crate) should_gen_req_info: Option<bool>,
 }
 
+// The methods of the routes matching the request path, the default OPTIONS route responds with them in the `Allow` header.
+struct AllowedMethods(Vec<Method>);
+
+pub(crate) type RequestCompleteHook = Arc<dyn Fn(&RequestMetrics<'_>) + Send + Sync + 'static>;
+
+struct RegexSetMatches {
+    pre_middleware_idxs: Vec<usize>,
+    around_middleware_idxs: Vec<usize>,
+    route_idxs: Vec<usize>,
+    post_middleware_idxs: Vec<usize>,
+    scoped_data_map_idxs: Vec<usize>,
+    scoped_body_limit_idxs: Vec<usize>,
+}
+
 pub(crate) enum ErrHandler<B> {
     WithoutInfo(ErrHandlerWithoutInfo<B>),
     WithInfo(ErrHandlerWithInfo<B>),
 }
 
 impl<B: HttpBody + Send + Sync + Unpin + 'static> ErrHandler<B> {
-    pub(crate) async fn execute(&mut self, err: crate::Error, req_info: Option<RequestInfo>) -> Response<B> {
+    pub(crate) async fn execute(&self, err: crate::Error, req_info: Option<RequestInfo>) -> Response<B> {
         match self {
-            ErrHandler::WithoutInfo(ref mut err_handler) => Pin::from(err_handler(err)).await,
-            ErrHandler::WithInfo(ref mut err_handler) => {
+            ErrHandler::WithoutInfo(ref err_handler) => Pin::from(err_handler(err)).await,
+            ErrHandler::WithInfo(ref err_handler) => {
                 Pin::from(err_handler(err, req_info.expect("No RequestInfo is provided"))).await
             }
         }
     }
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Router<B, E> {
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Router<B, E, ReqB>
+{
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
-        pre_middlewares: Vec<PreMiddleware<E>>,
-        routes: Vec<Route<B, E>>,
+        pre_middlewares: Vec<PreMiddleware<E, ReqB, B>>,
+        around_middlewares: Vec<AroundMiddleware<B, E, ReqB>>,
+        routes: Vec<Route<B, E, ReqB>>,
         post_middlewares: Vec<PostMiddleware<B, E>>,
         scoped_data_maps: Vec<ScopedDataMap>,
+        scoped_body_limits: Vec<ScopedBodyLimit>,
+        host_routers: Vec<HostRouter<B, E, ReqB>>,
         err_handler: Option<ErrHandler<B>>,
+        catch_panics: bool,
     ) -> Self {
         Router {
             pre_middlewares,
+            around_middlewares,
             routes,
             post_middlewares,
             scoped_data_maps,
+            scoped_body_limits,
+            host_routers,
             err_handler,
+            err_mappers: Vec::new(),
+            catch_panics,
+            route_matcher: RouteMatcher::default(),
+            match_cache_capacity: 0,
+            percent_decode_mode: PercentDecodeMode::default(),
+            slash_normalization: SlashNormalization::default(),
+            trailing_slash: TrailingSlash::default(),
+            on_request_complete: None,
+            dynamic_routes: None,
+            method_not_allowed: true,
+            auto_options: false,
+            auto_head: false,
+            timeout: None,
+            trusted_proxies: Arc::from(Vec::new()),
             regex_set: None,
+            route_index: None,
+            match_cache: None,
             should_gen_req_info: None,
         }
     }
 
     pub(crate) fn init_regex_set(&mut self) -> crate::Result<()> {
+        for host_router in self.host_routers.iter_mut() {
+            host_router.router.route_matcher = self.route_matcher;
+            host_router.router.match_cache_capacity = self.match_cache_capacity;
+            host_router.router.init_regex_set()?;
+        }
+
+        // The routes must be ordered before creating the RegexSet as the matched indices are mapped back to the routes.
+        // It's a stable sort, so the routes with equal priorities keep the registration order.
+        self.routes.sort_by_key(|route| Reverse(route.priority));
+        self.route_index = Some(RouteIndex::new(self.route_matcher, &self.routes)?);
+
         let regex_iter = self
             .pre_middlewares
             .iter()
             .map(|m| m.regex.as_str())
-            .chain(self.routes.iter().map(|r| r.regex.as_str()))
             .chain(self.post_middlewares.iter().map(|m| m.regex.as_str()))
-            .chain(self.scoped_data_maps.iter().map(|d| d.regex.as_str()));
+            .chain(self.scoped_data_maps.iter().map(|d| d.regex.as_str()))
+            .chain(self.scoped_body_limits.iter().map(|l| l.regex.as_str()))
+            .chain(self.around_middlewares.iter().map(|m| m.regex.as_str()));
 
         self.regex_set = Some(RegexSet::new(regex_iter).map_err(Error::CreateRouterRegexSet)?);
 
+        if self.match_cache_capacity > 0 {
+            self.match_cache = Some(MatchCache::new(self.match_cache_capacity));
+        }
+
         Ok(())
     }
 
     pub(crate) fn init_req_info_gen(&mut self) -> crate::Result<()> {
-        if let Some(ref err_handler) = self.err_handler {
-            if let ErrHandler::WithInfo(_) = err_handler {
-                self.should_gen_req_info = Some(true);
-                return Ok(());
+        if let Some(ErrHandler::WithInfo(_)) = self.err_handler {
+            self.should_gen_req_info = Some(true);
+            return Ok(());
+        }
+
+        for router in std::iter::once(&*self).chain(self.host_routers.iter().map(|host_router| &host_router.router)) {
+            let err_handlers = router
+                .routes
+                .iter()
+                .filter_map(|route| route.err_handler.as_deref())
+                .chain(router.pre_middlewares.iter().filter_map(|m| m.err_handler.as_deref()))
+                .chain(
+                    router
+                        .around_middlewares
+                        .iter()
+                        .filter_map(|m| m.err_handler.as_deref()),
+                )
+                .chain(router.post_middlewares.iter().filter_map(|m| m.err_handler.as_deref()));
+
+            for err_handler in err_handlers {
+                if let ErrHandler::WithInfo(_) = err_handler {
+                    self.should_gen_req_info = Some(true);
+                    return Ok(());
+                }
             }
         }
 
-        for post_middleware in self.post_middlewares.iter() {
+        let host_post_middlewares = self
+            .host_routers
+            .iter()
+            .flat_map(|host_router| host_router.router.post_middlewares.iter());
+
+        for post_middleware in self.post_middlewares.iter().chain(host_post_middlewares) {
             if post_middleware.should_require_req_meta() {
                 self.should_gen_req_info = Some(true);
                 return Ok(());
@@ -149,6 +316,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     }
 
     pub(crate) fn init_x_powered_by_middleware(&mut self) {
+        for host_router in self.host_routers.iter_mut() {
+            host_router.router.init_x_powered_by_middleware();
+        }
+
         let x_powered_by_post_middleware = PostMiddleware::new("/*", |mut res| async move {
             res.headers_mut().insert(
                 constants::HEADER_NAME_X_POWERED_BY,
@@ -173,6 +344,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     // }
 
     pub(crate) fn init_global_options_route(&mut self) {
+        for host_router in self.host_routers.iter_mut() {
+            host_router.router.init_global_options_route();
+        }
+
         let options_method = vec![Method::OPTIONS];
         let found = self
             .routes
@@ -184,13 +359,19 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         }
 
         if let Some(router) = self.downcast_to_hyper_body_type() {
-            let options_route: Route<hyper::Body, E> = Route::new("/*", options_method, |_req| async move {
-                Ok(Response::builder()
-                    .status(StatusCode::NO_CONTENT)
+            let mut options_route: Route<hyper::Body, E, ReqB> = Route::new("/*", options_method, |req| async move {
+                let mut builder = Response::builder().status(StatusCode::NO_CONTENT);
+                if let Some(AllowedMethods(methods)) = req.extensions().get::<AllowedMethods>() {
+                    builder = builder.header(header::ALLOW, error::join_methods(methods));
+                }
+
+                Ok(builder
                     .body(hyper::Body::empty())
                     .expect("Couldn't create the default OPTIONS response"))
             })
             .unwrap();
+            options_route.priority = i32::MIN;
+            options_route.builtin = true;
 
             router.routes.push(options_route);
         } else {
@@ -202,6 +383,10 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
     }
 
     pub(crate) fn init_default_404_route(&mut self) {
+        for host_router in self.host_routers.iter_mut() {
+            host_router.router.init_default_404_route();
+        }
+
         let found = self
             .routes
             .iter()
@@ -212,7 +397,7 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         }
 
         if let Some(router) = self.downcast_to_hyper_body_type() {
-            let default_404_route: Route<hyper::Body, E> =
+            let mut default_404_route: Route<hyper::Body, E, ReqB> =
                 Route::new("/*", constants::ALL_POSSIBLE_HTTP_METHODS.to_vec(), |_req| async move {
                     Ok(Response::builder()
                         .status(StatusCode::NOT_FOUND)
@@ -221,6 +406,8 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
                         .expect("Couldn't create the default 404 response"))
                 })
                 .unwrap();
+            default_404_route.priority = i32::MIN;
+            default_404_route.builtin = true;
             router.routes.push(default_404_route);
         } else {
             eprintln!(
@@ -240,12 +427,37 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         if let Some(router) = self.downcast_to_hyper_body_type() {
             let handler: ErrHandler<hyper::Body> = ErrHandler::WithoutInfo(Box::new(move |err: crate::Error| {
                 Box::new(async move {
-                    Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    // A route handler can return a routerify error as is e.g. by `req.params_as()?`.
+                    let status = match err.downcast_ref::<Error>().unwrap_or(&err) {
+                        Error::DecodeRequestPath(_) => StatusCode::BAD_REQUEST,
+                        Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+                        Error::InvalidRouteParam(..) => StatusCode::BAD_REQUEST,
+                        #[cfg(feature = "serde")]
+                        Error::DeserializeRouteParams(_)
+                        | Error::DeserializeQuery(_)
+                        | Error::DeserializeBody(_)
+                        | Error::DeserializeForm(_) => StatusCode::BAD_REQUEST,
+                        #[cfg(feature = "multipart")]
+                        Error::ParseMultipart(_) => StatusCode::BAD_REQUEST,
+                        Error::UnsupportedContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        Error::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+                        Error::RequestBodyTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                        Error::RequestBodyTimeout(_) => StatusCode::REQUEST_TIMEOUT,
+                        Error::HandlerTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+                        Error::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                        _ => StatusCode::INTERNAL_SERVER_ERROR,
+                    };
+
+                    let mut builder = Response::builder().status(status);
+                    if let Error::MethodNotAllowed(methods) = err.downcast_ref::<Error>().unwrap_or(&err) {
+                        builder = builder.header(header::ALLOW, error::join_methods(methods));
+                    }
+
+                    builder
                         .header(header::CONTENT_TYPE, "text/plain")
                         .body(hyper::Body::from(format!(
                             "{}: {}",
-                            StatusCode::INTERNAL_SERVER_ERROR.canonical_reason().unwrap(),
+                            status.canonical_reason().unwrap(),
                             err
                         )))
                         .expect("Couldn't create a response while handling the server error")
@@ -260,87 +472,616 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         }
     }
 
-    fn downcast_to_hyper_body_type(&mut self) -> Option<&mut Router<hyper::Body, E>> {
+    fn downcast_to_hyper_body_type(&mut self) -> Option<&mut Router<hyper::Body, E, ReqB>> {
         let any_obj: &mut dyn Any = self;
-        any_obj.downcast_mut::<Router<hyper::Body, E>>()
+        any_obj.downcast_mut::<Router<hyper::Body, E, ReqB>>()
     }
 
-    /// Return a [RouterBuilder](./struct.RouterBuilder.html) instance to build a `Router`.
-    pub fn builder() -> RouterBuilder<B, E> {
-        builder::RouterBuilder::new()
+    /// Generates the path which is matched against the routes from a raw request path, by applying the slash normalization and
+    /// the percent-decoding of the router and appending a trailing slash.
+    pub(crate) fn gen_target_path(&self, path: &str) -> crate::Result<String> {
+        let path = match self.slash_normalization {
+            SlashNormalization::Off => Cow::Borrowed(path),
+            SlashNormalization::Collapse | SlashNormalization::Redirect => helpers::collapse_slashes(path),
+        };
+
+        let mut target_path = helpers::percent_decode_request_path(&path, self.percent_decode_mode)?;
+
+        if target_path.as_bytes()[target_path.len() - 1] != b'/' {
+            target_path.push('/');
+        }
+
+        Ok(target_path)
+    }
+
+    /// The request path which the target path is generated from, the route param values are sliced from it as they're sent.
+    pub(crate) fn raw_path<'a>(&self, path: &'a str) -> RawPath<'a> {
+        RawPath {
+            path,
+            collapse_slashes: !matches!(self.slash_normalization, SlashNormalization::Off),
+            mode: self.percent_decode_mode,
+        }
     }
 
     pub(crate) async fn process(
-        &mut self,
+        &self,
+        target_path: &str,
+        #[cfg_attr(not(feature = "tracing"), allow(unused_mut))] mut req: Request<ReqB>,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        #[cfg(feature = "tracing")]
+        let span = trace::request_span(&mut req);
+
+        let fut = self.process_by_host(target_path, req, req_info);
+
+        #[cfg(feature = "tracing")]
+        let fut = trace::instrument_request(span, fut);
+
+        fut.await
+    }
+
+    async fn process_by_host(
+        &self,
         target_path: &str,
-        mut req: Request<hyper::Body>,
+        mut req: Request<ReqB>,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        // The host is matched before the path, so a host scoped router runs its own middlewares only.
+        let host_match = host::request_host(&req).and_then(|host| {
+            self.host_routers.iter().find_map(|host_router| {
+                host_router
+                    .pattern
+                    .matches(host)
+                    .map(|(host_param, host_params)| (host_router, host_param.to_owned(), host_params))
+            })
+        });
+
+        match host_match {
+            Some((host_router, host_param, host_params)) => {
+                helpers::update_req_meta_in_extensions(
+                    req.extensions_mut(),
+                    RequestMeta::with_host_param(host_param, host_params),
+                );
+                host_router
+                    .router
+                    .process_with_root(self, target_path, req, req_info)
+                    .await
+            }
+            None => self.process_with_root(self, target_path, req, req_info).await,
+        }
+    }
+
+    // The root router provides the error handler and the options which are only considered on the root router.
+    async fn process_with_root(
+        &self,
+        root: &Router<B, E, ReqB>,
+        target_path: &str,
+        mut req: Request<ReqB>,
         mut req_info: Option<RequestInfo>,
     ) -> crate::Result<Response<B>> {
-        let (
-            matched_pre_middleware_idxs,
-            matched_route_idxs,
-            matched_post_middleware_idxs,
-            matched_scoped_data_map_idxs,
-        ) = self.match_regex_set(target_path);
-
-        let shared_data_maps = matched_scoped_data_map_idxs
-            .into_iter()
-            .map(|idx| self.scoped_data_maps[idx].clone_data_map())
+        let matches = self.find_matches(target_path);
+
+        let mut shared_data_maps = matches
+            .scoped_data_map_idxs
+            .iter()
+            .map(|idx| self.scoped_data_maps[*idx].clone_data_map())
             .collect::<Vec<_>>();
 
+        // The data shared on the root router is also accessible from the host scoped routers.
+        if !std::ptr::eq(self, root) {
+            shared_data_maps.extend(
+                root.find_matches(target_path)
+                    .scoped_data_map_idxs
+                    .iter()
+                    .map(|idx| root.scoped_data_maps[*idx].clone_data_map()),
+            );
+        }
+
         if let Some(ref mut req_info) = req_info {
             if !shared_data_maps.is_empty() {
-                req_info.shared_data_maps.replace(Box::new(shared_data_maps.clone()));
+                req_info.shared_data_maps.replace(shared_data_maps.clone());
             }
         }
 
         let ext = req.extensions_mut();
         ext.insert(shared_data_maps);
 
+        // The most specific body limit wins, so a limit set on a path overrides the router-wide one.
+        let body_limit = matches
+            .scoped_body_limit_idxs
+            .iter()
+            .map(|idx| &self.scoped_body_limits[*idx])
+            .max_by_key(|scoped_body_limit| scoped_body_limit.path.len())
+            .map(|scoped_body_limit| scoped_body_limit.limit);
+
+        self.process_around(
+            root,
+            &matches.around_middleware_idxs,
+            target_path,
+            req,
+            req_info,
+            matches.clone(),
+            body_limit,
+        )
+        .await
+    }
+
+    // The around middlewares wrap the rest of the processing in the registration order, the first one is the outermost. The
+    // downstream is driven together with the around middleware's handler, so the handler can e.g. time it out.
+    #[allow(clippy::too_many_arguments)]
+    fn process_around<'a>(
+        &'a self,
+        root: &'a Router<B, E, ReqB>,
+        around_middleware_idxs: &'a [usize],
+        target_path: &'a str,
+        req: Request<ReqB>,
+        req_info: Option<RequestInfo>,
+        matches: Arc<RegexSetMatches>,
+        body_limit: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = crate::Result<Response<B>>> + Send + 'a>> {
+        Box::pin(async move {
+            let (idx, rest_idxs) = match around_middleware_idxs.split_first() {
+                Some(split) => split,
+                None => {
+                    return self
+                        .process_inner(root, target_path, req, req_info, matches, body_limit)
+                        .await
+                }
+            };
+
+            let around_middleware = &self.around_middlewares[*idx];
+            let (handler_fut, req_rx) = around_middleware.process(req);
+
+            #[cfg(feature = "tracing")]
+            let handler_fut = trace::instrument(trace::Stage::AroundMiddleware, &around_middleware.path, handler_fut);
+
+            let handler_fut = guard_panic(root.catch_panics, handler_fut);
+
+            let downstream_req_info = req_info.clone();
+            let downstream = async move {
+                // The request is not received if the handler doesn't run the `Next`.
+                if let Ok((req, res_tx)) = req_rx.await {
+                    let res = self
+                        .process_around(
+                            root,
+                            rest_idxs,
+                            target_path,
+                            req,
+                            downstream_req_info,
+                            matches,
+                            body_limit,
+                        )
+                        .await;
+                    let _ = res_tx.send(res);
+                }
+            };
+
+            futures::pin_mut!(handler_fut, downstream);
+
+            let res = match future::select(handler_fut, downstream).await {
+                Either::Left((res, _)) => res,
+                Either::Right(((), handler_fut)) => handler_fut.await,
+            };
+
+            match (res, around_middleware.err_handler.as_ref()) {
+                (Ok(res), _) => Ok(res),
+                (Err(err), Some(err_handler)) => root.execute_scoped_err_handler(err_handler, err, &req_info).await,
+                (Err(err), None) => root.execute_err_handler(err, req_info).await,
+            }
+        })
+    }
+
+    async fn process_inner(
+        &self,
+        root: &Router<B, E, ReqB>,
+        target_path: &str,
+        req: Request<ReqB>,
+        req_info: Option<RequestInfo>,
+        mut matches: Arc<RegexSetMatches>,
+        body_limit: Option<u64>,
+    ) -> crate::Result<Response<B>> {
+        let catch_panics = root.catch_panics;
+
+        // Any error raised before the post middlewares e.g. by a pre middleware or by the route handler is passed
+        // to the error handler and the generated response goes through the post middlewares.
+        let mut transformed_res = match self
+            .process_request(
+                root,
+                target_path,
+                req,
+                &req_info,
+                &mut matches,
+                body_limit,
+                catch_panics,
+            )
+            .await
+        {
+            Ok(res) => res,
+            Err(err) => root.execute_err_handler(err, req_info.clone()).await?,
+        };
+
+        // The post middlewares are matched against the rewritten path if any pre middleware rewrote it.
+        // If a post middleware fails, the error handler's response is passed to the remaining post middlewares.
+        for idx in matches.post_middleware_idxs.iter() {
+            let post_middleware = &self.post_middlewares[*idx];
+
+            let fut = post_middleware.process(transformed_res, req_info.clone());
+
+            #[cfg(feature = "tracing")]
+            let fut = trace::instrument(trace::Stage::PostMiddleware, &post_middleware.path, fut);
+
+            transformed_res = match (
+                guard_panic(catch_panics, fut).await,
+                post_middleware.err_handler.as_ref(),
+            ) {
+                (Ok(res), _) => res,
+                (Err(err), Some(err_handler)) => root.execute_scoped_err_handler(err_handler, err, &req_info).await?,
+                (Err(err), None) => root.execute_err_handler(err, req_info.clone()).await?,
+            };
+        }
+
+        Ok(transformed_res)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn process_request(
+        &self,
+        root: &Router<B, E, ReqB>,
+        target_path: &str,
+        req: Request<ReqB>,
+        req_info: &Option<RequestInfo>,
+        matches: &mut Arc<RegexSetMatches>,
+        body_limit: Option<u64>,
+        catch_panics: bool,
+    ) -> crate::Result<Response<B>> {
         let mut transformed_req = req;
-        for idx in matched_pre_middleware_idxs {
-            let pre_middleware = &mut self.pre_middlewares[idx];
+        let mut body_limit_exceeded = None;
+
+        if let Some(limit) = body_limit {
+            body::check_content_length(&transformed_req, limit)?;
+
+            let (limited_req, exceeded) = body::limit_body(transformed_req, limit);
+            transformed_req = limited_req;
+            body_limit_exceeded = exceeded.map(|exceeded| (limit, exceeded));
+        }
+
+        // A pre middleware can rewrite the request path, then the remaining pre middlewares, the routes and the post middlewares
+        // are matched against the new path. The pre middlewares which are registered before the rewriting one are not run again.
+        let mut target_path = Cow::Borrowed(target_path);
+        let mut rewrites = 0;
+        let mut pos = 0;
+
+        while let Some(&idx) = matches.pre_middleware_idxs.get(pos) {
+            let pre_middleware = &self.pre_middlewares[idx];
+            let uri = transformed_req.uri().clone();
+
+            let fut = pre_middleware.process(transformed_req);
 
-            transformed_req = pre_middleware.process(transformed_req).await?;
+            #[cfg(feature = "tracing")]
+            let fut = trace::instrument(trace::Stage::PreMiddleware, &pre_middleware.path, fut);
+
+            let outcome = match guard_panic(catch_panics, fut).await {
+                Ok(outcome) => outcome,
+                Err(err) => match pre_middleware.err_handler.as_deref() {
+                    Some(err_handler) => return root.execute_scoped_err_handler(err_handler, err, req_info).await,
+                    None => return Err(err),
+                },
+            };
+
+            transformed_req = match outcome {
+                PreOutcome::Continue(req) => req,
+                PreOutcome::Respond(res) => return Ok(res),
+            };
+            pos += 1;
+
+            if transformed_req.uri().query() != uri.query() {
+                if let Some(req_meta) = transformed_req.extensions_mut().get_mut::<RequestMeta>() {
+                    req_meta.reset_query_params();
+                }
+            }
+
+            if transformed_req.uri().path() != uri.path() {
+                rewrites += 1;
+                if rewrites > constants::MAX_REWRITES {
+                    return Err(Error::TooManyRewrites(transformed_req.uri().path().to_owned()));
+                }
+
+                target_path = Cow::Owned(root.gen_target_path(transformed_req.uri().path())?);
+                *matches = self.find_matches(&target_path);
+                pos = matches.pre_middleware_idxs.partition_point(|pre_idx| *pre_idx <= idx);
+            }
         }
 
-        let mut resp = None;
-        for idx in matched_route_idxs {
-            let route = &mut self.routes[idx];
+        // The dynamic routes are tried after the static ones except the `/*` and `not_found` routes, so they can't be shadowed by the
+        // default 404 route. The snapshot is kept until the request is handled, so the route can be removed meanwhile.
+        let (catch_all_routes, static_routes): (Vec<_>, Vec<_>) = matches
+            .route_idxs
+            .iter()
+            .map(|idx| &self.routes[*idx])
+            .partition(|route| route.is_catch_all());
+
+        let dynamic_snapshot = root.dynamic_routes.as_ref().map(DynamicRoutes::snapshot);
+        let dynamic_routes = dynamic_snapshot.iter().flat_map(|snapshot| {
+            snapshot
+                .matches(&target_path)
+                .into_iter()
+                .map(move |idx| &*snapshot.routes[idx])
+        });
+
+        // The methods of the routes matching the path but not the method, they're allowed in the 405 response which replaces
+        // the 404 fallback route.
+        let mut allowed_methods = Vec::new();
+        let mut matched_route = None;
+        // A GET route answers a HEAD request if it's enabled and no route is added for HEAD, except the `/*` routes.
+        let mut head_route = None;
+
+        // The routes added with the other form of the trailing slash are skipped unless it's ignored, the first one is the
+        // target of the redirection.
+        let trailing_slash = match root.trailing_slash {
+            TrailingSlash::RedirectToCanonical
+                if !transformed_req.method().is_idempotent() || TypeId::of::<B>() != TypeId::of::<hyper::Body>() =>
+            {
+                TrailingSlash::Ignore
+            }
+            mode => mode,
+        };
+        let has_trailing_slash = transformed_req.uri().path().ends_with('/');
+        let mut canonical_route = None;
+        // A route matched the request except the content type, it's responded with 415 unless another route matches.
+        let mut unsupported_content_type = false;
 
-            if route.is_match_method(transformed_req.method()) {
-                let route_resp_res = route.process(target_path, transformed_req).await;
+        for route in static_routes.into_iter().chain(dynamic_routes).chain(catch_all_routes) {
+            // The header and the query matchers are the criteria besides the path and the method.
+            let is_match_header =
+                route.is_match_header(transformed_req.headers()) && route.is_match_query(&transformed_req);
 
-                let route_resp = match route_resp_res {
-                    Ok(route_resp) => route_resp,
-                    Err(err) => {
-                        if let Some(ref mut err_handler) = self.err_handler {
-                            err_handler.execute(err, req_info.clone()).await
-                        } else {
-                            return Err(err);
-                        }
+            if trailing_slash != TrailingSlash::Ignore
+                && route.trailing_slash.is_some_and(|slash| slash != has_trailing_slash)
+            {
+                if canonical_route.is_none() && is_match_header {
+                    canonical_route = Some(route);
+                }
+                continue;
+            }
+
+            if route.is_match_method(transformed_req.method()) && is_match_header {
+                if !route.is_match_content_type(&transformed_req) {
+                    unsupported_content_type = true;
+                    continue;
+                }
+
+                matched_route = Some(route);
+                break;
+            }
+
+            if is_match_header && !route.is_catch_all() {
+                if root.auto_head
+                    && head_route.is_none()
+                    && transformed_req.method() == Method::HEAD
+                    && route.is_match_method(&Method::GET)
+                {
+                    head_route = Some(route);
+                }
+
+                for method in route.methods.iter() {
+                    if !allowed_methods.contains(method) {
+                        allowed_methods.push(method.clone());
+                    }
+                    if root.auto_head && *method == Method::GET && !allowed_methods.contains(&Method::HEAD) {
+                        allowed_methods.push(Method::HEAD);
                     }
+                }
+            }
+        }
+
+        if let (TrailingSlash::RedirectToCanonical, Some(canonical_route)) = (trailing_slash, canonical_route) {
+            if head_route.is_none() && matched_route.is_none_or(Route::is_catch_all) {
+                let path = transformed_req.uri().path();
+                let path = if canonical_route.trailing_slash == Some(true) {
+                    Cow::Owned(format!("{}/", path))
+                } else {
+                    Cow::Borrowed(path.trim_end_matches('/'))
                 };
 
-                resp = Some(route_resp);
-                break;
+                if let Some(resp) =
+                    helpers::redirect_response(StatusCode::MOVED_PERMANENTLY, &path, transformed_req.uri().query())
+                {
+                    return Ok(resp);
+                }
             }
         }
 
-        if resp.is_none() {
-            return Err(Error::HandleNonExistentRoute);
+        if unsupported_content_type && head_route.is_none() && matched_route.is_none_or(Route::is_catch_all) {
+            return Err(Error::UnsupportedContentType(body::media_type(&transformed_req)));
         }
 
-        let mut transformed_res = resp.unwrap();
-        for idx in matched_post_middleware_idxs {
-            let post_middleware = &mut self.post_middlewares[idx];
-            transformed_res = post_middleware.process(transformed_res, req_info.clone()).await?;
+        let route = match (matched_route, head_route) {
+            (Some(route), Some(head_route)) if route.is_catch_all() => head_route,
+            (Some(route), _) => route,
+            (None, Some(head_route)) => head_route,
+            (None, None) if root.method_not_allowed && !allowed_methods.is_empty() => {
+                return Err(Error::MethodNotAllowed(allowed_methods))
+            }
+            (None, None) => return Err(Error::HandleNonExistentRoute),
+        };
+
+        if root.method_not_allowed && !allowed_methods.is_empty() && route.is_fallback() {
+            return Err(Error::MethodNotAllowed(allowed_methods));
         }
 
-        Ok(transformed_res)
+        if root.auto_options
+            && route.builtin
+            && transformed_req.method() == Method::OPTIONS
+            && !allowed_methods.is_empty()
+        {
+            if !allowed_methods.contains(&Method::OPTIONS) {
+                allowed_methods.push(Method::OPTIONS);
+            }
+            transformed_req.extensions_mut().insert(AllowedMethods(allowed_methods));
+        }
+
+        if !route.builtin {
+            if let Some(matched_route_path) = transformed_req.extensions().get::<MatchedRoutePath>() {
+                matched_route_path.set(route.template().clone());
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        trace::record_route(&transformed_req, route.template());
+
+        let uri = transformed_req.uri().clone();
+        let fut = route.process(&target_path, root.raw_path(uri.path()), transformed_req, root.timeout);
+
+        #[cfg(feature = "tracing")]
+        let fut = trace::instrument(trace::Stage::Route, route.template(), fut);
+
+        let res = guard_panic(catch_panics, fut)
+            .await
+            .map_err(|err| match body_limit_exceeded {
+                // The handler most likely failed because the body stream was cut off.
+                Some((limit, ref exceeded)) if exceeded.load(Ordering::SeqCst) => Error::RequestBodyTooLarge(limit),
+                _ => err,
+            });
+
+        match (res, route.err_handler.as_ref()) {
+            (Err(err), Some(err_handler)) => root.execute_scoped_err_handler(err_handler, err, req_info).await,
+            (res, _) => res,
+        }
+    }
+
+    // The error mappers take precedence over all the error handlers.
+    async fn map_err(&self, mut err: crate::Error) -> crate::Result<Response<B>> {
+        for err_mapper in self.err_mappers.iter() {
+            match err_mapper(err) {
+                Ok(fut) => return Ok(Pin::from(fut).await),
+                Err(unmapped) => err = unmapped,
+            }
+        }
+
+        Err(err)
+    }
+
+    // The error handler of a route, or of the scoped router which added the failed route or middleware.
+    async fn execute_scoped_err_handler(
+        &self,
+        err_handler: &ErrHandler<B>,
+        err: crate::Error,
+        req_info: &Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        #[cfg(feature = "tracing")]
+        trace::record_error(&err);
+
+        match self.map_err(err).await {
+            Ok(res) => Ok(res),
+            Err(err) => self.run_err_handler(err_handler, err, req_info.clone()).await,
+        }
+    }
+
+    async fn execute_err_handler(
+        &self,
+        err: crate::Error,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        #[cfg(feature = "tracing")]
+        trace::record_error(&err);
+
+        let err = match self.map_err(err).await {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+
+        if let Some(ref err_handler) = self.err_handler {
+            self.run_err_handler(err_handler, err, req_info).await
+        } else {
+            Err(err)
+        }
+    }
+
+    // Runs an error handler with the panic setting of the router, so it's called on the root router. If the error handler panics,
+    // the fallback `500` response of a failed error handler is sent instead, as the panic can't be passed to another error
+    // handler. The fallback can only be generated for the `hyper::Body` responses, otherwise the panic is returned as the error.
+    pub(crate) async fn run_err_handler(
+        &self,
+        err_handler: &ErrHandler<B>,
+        err: crate::Error,
+        req_info: Option<RequestInfo>,
+    ) -> crate::Result<Response<B>> {
+        let fut = err_handler.execute(err, req_info).map(crate::Result::Ok);
+
+        match guard_panic(self.catch_panics, fut).await {
+            Ok(res) => Ok(res),
+            Err(err) => {
+                let mut body = Some(hyper::Body::from("Internal Server Error"));
+                match (&mut body as &mut dyn Any)
+                    .downcast_mut::<Option<B>>()
+                    .and_then(Option::take)
+                {
+                    Some(body) => Ok(handler::err_handler_failed(body, Box::new(err))),
+                    None => Err(err),
+                }
+            }
+        }
+    }
+
+    /// Generates the URL path of the route added by [`add_named`](./struct.RouterBuilder.html#method.add_named) or the other
+    /// `*_named` methods, the params are percent-encoded and filled into the route path. The wildcard `*` is filled by the
+    /// `"*"` param or by its name e.g. `"filepath"` of `*filepath`, which may contain slashes.
+    ///
+    /// The routes of the [host scoped](./struct.RouterBuilder.html#method.host_scope) routers are looked up too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{ErrorKind, Router};
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let api = Router::builder()
+    ///     .get_named("file", "/files/:owner/*", |_| async move { Ok(Response::new(Body::from("A file"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let router = Router::builder().scope("/api", api).build().unwrap();
+    ///
+    /// let url = router.url_for("file", &[("owner", "John Doe"), ("*", "docs/a.txt")]).unwrap();
+    /// assert_eq!(url, "/api/files/John%20Doe/docs/a.txt");
+    ///
+    /// let err = router.url_for("file", &[("owner", "John Doe")]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UrlFor);
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> crate::Result<String> {
+        let route = self
+            .find_named_route(name)
+            .ok_or_else(|| Error::UnknownRouteName(name.to_owned()))?;
+
+        let value_of = |param: &str| params.iter().find(|(key, _)| *key == param).map(|(_, value)| *value);
+        regex_generator::fill_path_params(route.template(), value_of)
+            .map_err(|param| Error::MissingRouteParam(name.to_owned(), param))
     }
 
-    fn match_regex_set(&self, target_path: &str) -> (Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>) {
+    fn find_named_route(&self, name: &str) -> Option<&Route<B, E, ReqB>> {
+        self.routes
+            .iter()
+            .find(|route| route.name.as_deref() == Some(name))
+            .or_else(|| {
+                self.host_routers
+                    .iter()
+                    .find_map(|host_router| host_router.router.find_named_route(name))
+            })
+    }
+
+    fn find_matches(&self, target_path: &str) -> Arc<RegexSetMatches> {
+        match self.match_cache {
+            Some(ref match_cache) => match_cache.get_or_insert_with(target_path, || self.match_regex_set(target_path)),
+            None => Arc::new(self.match_regex_set(target_path)),
+        }
+    }
+
+    fn match_regex_set(&self, target_path: &str) -> RegexSetMatches {
         let matches = self
             .regex_set
             .as_ref()
@@ -349,50 +1090,98 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
             .into_iter();
 
         let pre_middlewares_len = self.pre_middlewares.len();
-        let routes_len = self.routes.len();
         let post_middlewares_len = self.post_middlewares.len();
         let scoped_data_maps_len = self.scoped_data_maps.len();
+        let scoped_body_limits_len = self.scoped_body_limits.len();
+        let around_middlewares_offset =
+            pre_middlewares_len + post_middlewares_len + scoped_data_maps_len + scoped_body_limits_len;
 
         let mut matched_pre_middleware_idxs = Vec::new();
-        let mut matched_route_idxs = Vec::new();
+        let mut matched_around_middleware_idxs = Vec::new();
         let mut matched_post_middleware_idxs = Vec::new();
         let mut matched_scoped_data_map_idxs = Vec::new();
+        let mut matched_scoped_body_limit_idxs = Vec::new();
 
         for idx in matches {
             if idx < pre_middlewares_len {
                 matched_pre_middleware_idxs.push(idx);
-            } else if idx >= pre_middlewares_len && idx < (pre_middlewares_len + routes_len) {
-                matched_route_idxs.push(idx - pre_middlewares_len);
-            } else if idx >= (pre_middlewares_len + routes_len)
-                && idx < (pre_middlewares_len + routes_len + post_middlewares_len)
+            } else if idx >= pre_middlewares_len && idx < (pre_middlewares_len + post_middlewares_len) {
+                matched_post_middleware_idxs.push(idx - pre_middlewares_len);
+            } else if idx >= (pre_middlewares_len + post_middlewares_len)
+                && idx < (pre_middlewares_len + post_middlewares_len + scoped_data_maps_len)
             {
-                matched_post_middleware_idxs.push(idx - pre_middlewares_len - routes_len);
-            } else if idx >= (pre_middlewares_len + routes_len + post_middlewares_len)
-                && idx < (pre_middlewares_len + routes_len + post_middlewares_len + scoped_data_maps_len)
+                matched_scoped_data_map_idxs.push(idx - pre_middlewares_len - post_middlewares_len);
+            } else if idx >= (pre_middlewares_len + post_middlewares_len + scoped_data_maps_len)
+                && idx < (pre_middlewares_len + post_middlewares_len + scoped_data_maps_len + scoped_body_limits_len)
             {
-                matched_scoped_data_map_idxs.push(idx - pre_middlewares_len - routes_len - post_middlewares_len);
+                matched_scoped_body_limit_idxs
+                    .push(idx - pre_middlewares_len - post_middlewares_len - scoped_data_maps_len);
+            } else if idx >= around_middlewares_offset {
+                matched_around_middleware_idxs.push(idx - around_middlewares_offset);
             }
         }
 
-        (
-            matched_pre_middleware_idxs,
-            matched_route_idxs,
-            matched_post_middleware_idxs,
-            matched_scoped_data_map_idxs,
-        )
+        let matched_route_idxs = self
+            .route_index
+            .as_ref()
+            .expect("The 'route_index' field in Router is not initialized")
+            .matches(target_path);
+
+        RegexSetMatches {
+            pre_middleware_idxs: matched_pre_middleware_idxs,
+            around_middleware_idxs: matched_around_middleware_idxs,
+            route_idxs: matched_route_idxs,
+            post_middleware_idxs: matched_post_middleware_idxs,
+            scoped_data_map_idxs: matched_scoped_data_map_idxs,
+            scoped_body_limit_idxs: matched_scoped_body_limit_idxs,
+        }
+    }
+}
+
+async fn guard_panic<T, F>(catch_panics: bool, fut: F) -> crate::Result<T>
+where
+    F: Future<Output = crate::Result<T>>,
+{
+    if catch_panics {
+        helpers::catch_panic(fut).await
+    } else {
+        fut.await
+    }
+}
+
+impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Router<B, E> {
+    /// Return a [RouterBuilder](./struct.RouterBuilder.html) instance to build a `Router`.
+    ///
+    /// The built router accepts requests with [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html) body,
+    /// use [`RouterBuilder::new`](./struct.RouterBuilder.html#method.new) to build a router for any other request body type.
+    pub fn builder() -> RouterBuilder<B, E> {
+        builder::RouterBuilder::new()
     }
 }
 
-impl<B, E> Debug for Router<B, E> {
+impl<B, E, ReqB> Debug for Router<B, E, ReqB> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{ Pre-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, ScopedDataMaps: {:?}, ErrHandler: {:?}, ShouldGenReqInfo: {:?} }}",
+            "{{ Pre-Middlewares: {:?}, Around-Middlewares: {:?}, Routes: {:?}, Post-Middlewares: {:?}, ScopedDataMaps: {:?}, ScopedBodyLimits: {:?}, HostRouters: {:?}, ErrHandler: {:?}, CatchPanics: {:?}, RouteMatcher: {:?}, MatchCache: {:?}, PercentDecodeMode: {:?}, SlashNormalization: {:?}, TrailingSlash: {:?}, OnRequestComplete: {:?}, DynamicRoutes: {:?}, Timeout: {:?}, TrustedProxies: {:?}, ShouldGenReqInfo: {:?} }}",
             self.pre_middlewares,
+            self.around_middlewares,
             self.routes,
             self.post_middlewares,
             self.scoped_data_maps,
+            self.scoped_body_limits,
+            self.host_routers,
             self.err_handler.is_some(),
+            self.catch_panics,
+            self.route_matcher,
+            self.match_cache,
+            self.percent_decode_mode,
+            self.slash_normalization,
+            self.trailing_slash,
+            self.on_request_complete.is_some(),
+            self.dynamic_routes,
+            self.timeout,
+            self.trusted_proxies,
             self.should_gen_req_info
         )
     }
@@ -0,0 +1,34 @@
+use crate::types::PeerAddr;
+use hyper::server::conn::AddrStream;
+
+/// A connection type which a [`RouterService`](./struct.RouterService.html) can serve the requests over.
+///
+/// It's implemented for the hyper's TCP connection type [`AddrStream`](https://docs.rs/hyper/0.13.5/hyper/server/conn/struct.AddrStream.html)
+/// and for the tokio's [`UnixStream`](https://docs.rs/tokio/0.2/tokio/net/struct.UnixStream.html) if the `unix` feature is enabled.
+/// It can be implemented for any other connection type accepted by a hyper server.
+pub trait Connection {
+    /// Returns the peer of this connection.
+    fn peer_addr(&self) -> PeerAddr;
+}
+
+impl Connection for AddrStream {
+    fn peer_addr(&self) -> PeerAddr {
+        PeerAddr::Tcp(self.remote_addr())
+    }
+}
+
+#[cfg(all(unix, feature = "unix"))]
+impl Connection for tokio::net::UnixStream {
+    fn peer_addr(&self) -> PeerAddr {
+        let path = tokio::net::UnixStream::peer_addr(self)
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(|path| path.to_path_buf()));
+        let cred = self.peer_cred().ok();
+
+        PeerAddr::Unix {
+            path,
+            uid: cred.map(|cred| cred.uid),
+            gid: cred.map(|cred| cred.gid),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::service::request_service::RequestService;
+use crate::service::router_service::RouterService;
+use crate::service::Connection;
+use hyper::{body::HttpBody, service::Service, Request, Response};
+use std::convert::Infallible;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A service which converts the responses of a [`RouterService`](./struct.RouterService.html) into another body type, it's
+/// created by the [`RouterService::map_response_body`](./struct.RouterService.html#method.map_response_body) method.
+///
+/// It wraps the per-connection `RequestService` too, so the same type is used both to serve the connections and to handle
+/// the requests.
+pub struct MapResponseBody<S, F> {
+    inner: S,
+    f: Arc<F>,
+}
+
+impl<S, F> MapResponseBody<S, F> {
+    pub(crate) fn new(inner: S, f: F) -> MapResponseBody<S, F> {
+        MapResponseBody { inner, f: Arc::new(f) }
+    }
+}
+
+impl<B, E, ReqB, C, F, Bout> Service<&C> for MapResponseBody<RouterService<B, E, ReqB>, F>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+    C: Connection,
+    F: Fn(Response<B>) -> Response<Bout> + Send + Sync + 'static,
+{
+    type Response = MapResponseBody<RequestService<B, E, ReqB>, F>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, conn: &C) -> Self::Future {
+        let req_service = MapResponseBody {
+            inner: self.inner.build_request_service(conn.peer_addr()),
+            f: self.f.clone(),
+        };
+
+        Box::pin(async move { Ok(req_service) })
+    }
+}
+
+impl<B, E, ReqB, F, Bout> Service<Request<ReqB>> for MapResponseBody<RequestService<B, E, ReqB>, F>
+where
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+    F: Fn(Response<B>) -> Response<Bout> + Send + Sync + 'static,
+{
+    type Response = Response<Bout>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqB>) -> Self::Future {
+        let fut = self.inner.call(req);
+        let f = self.f.clone();
+
+        Box::pin(async move { fut.await.map(|resp| f(resp)) })
+    }
+}
+
+impl<S: Debug, F> Debug for MapResponseBody<S, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ inner: {:?} }}", self.inner)
+    }
+}
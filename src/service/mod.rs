@@ -1,5 +1,17 @@
+#[cfg(feature = "tower")]
+pub use self::tower_service::RouterTowerService;
+pub use connection::Connection;
+pub use map_response_body::MapResponseBody;
+#[cfg(feature = "test-util")]
+pub(crate) use request_service::handle_request;
 pub use request_service::{RequestService, RequestServiceBuilder};
 pub use router_service::RouterService;
+pub use shutdown::{ShutdownHandle, ShutdownSignal};
 
+mod connection;
+mod map_response_body;
 mod request_service;
 mod router_service;
+mod shutdown;
+#[cfg(feature = "tower")]
+mod tower_service;
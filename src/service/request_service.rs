@@ -1,28 +1,40 @@
 use crate::helpers;
 use crate::router::Router;
-use crate::types::{RequestInfo, RequestMeta};
-use hyper::{body::HttpBody, service::Service, Request, Response};
+use crate::service::shutdown::ShutdownHandle;
+use crate::types::{
+    ErrHandlerFailure, MatchedRoutePath, PeerAddr, RequestInfo, RequestMeta, RequestMetrics, SlashNormalization,
+};
+use crate::Error;
+use hyper::{body::HttpBody, service::Service, Request, Response, StatusCode};
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
 
-pub struct RequestService<B, E> {
-    pub(crate) router: *mut Router<B, E>,
-    pub(crate) remote_addr: SocketAddr,
+pub struct RequestService<B, E, ReqB = hyper::Body> {
+    pub(crate) router: Arc<Router<B, E, ReqB>>,
+    pub(crate) peer_addr: PeerAddr,
+    shutdown: ShutdownHandle,
+    hooks: ConnectionHooks,
 }
 
-unsafe impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Send
-    for RequestService<B, E>
-{
-}
-unsafe impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static> Sync
-    for RequestService<B, E>
-{
+pub(crate) type ConnectionHook = Arc<dyn Fn(&PeerAddr) + Send + Sync + 'static>;
+
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionHooks {
+    pub(crate) on_open: Option<ConnectionHook>,
+    pub(crate) on_close: Option<ConnectionHook>,
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    Service<Request<hyper::Body>> for RequestService<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > Service<Request<ReqB>> for RequestService<B, E, ReqB>
 {
     type Response = Response<B>;
     type Error = crate::Error;
@@ -32,53 +44,184 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, mut req: Request<hyper::Body>) -> Self::Future {
-        let router = unsafe { &mut *self.router };
-        let remote_addr = self.remote_addr;
+    fn call(&mut self, req: Request<ReqB>) -> Self::Future {
+        let router = self.router.clone();
+        let peer_addr = self.peer_addr.clone();
 
-        let fut = async move {
-            helpers::update_req_meta_in_extensions(req.extensions_mut(), RequestMeta::with_remote_addr(remote_addr));
+        if self.router.on_request_complete.is_some() {
+            return Box::pin(handle_request_with_metrics(
+                router,
+                req,
+                peer_addr,
+                self.shutdown.clone(),
+            ));
+        }
 
-            let mut target_path = helpers::percent_decode_request_path(req.uri().path())?;
+        if self.shutdown.is_shutdown() {
+            return Box::pin(reject_request(router, req, Error::ShuttingDown));
+        }
 
-            if target_path.as_bytes()[target_path.len() - 1] != b'/' {
-                target_path.push_str("/");
-            }
+        let in_flight_guard = self.shutdown.track_request();
 
-            let mut req_info = None;
-            let should_gen_req_info = router
-                .should_gen_req_info
-                .expect("The `should_gen_req_info` flag in Router is not initialized");
+        Box::pin(async move {
+            let resp = handle_request(router, req, peer_addr).await;
+            drop(in_flight_guard);
+            resp
+        })
+    }
+}
 
-            if should_gen_req_info {
-                req_info = Some(RequestInfo::new_from_req(&req));
-            }
+impl<B, E, ReqB> Drop for RequestService<B, E, ReqB> {
+    fn drop(&mut self) {
+        // Hyper drops the service once the connection is closed.
+        if let Some(ref on_close) = self.hooks.on_close {
+            on_close(&self.peer_addr);
+        }
+    }
+}
+
+pub(crate) async fn handle_request<
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+>(
+    router: Arc<Router<B, E, ReqB>>,
+    mut req: Request<ReqB>,
+    peer_addr: PeerAddr,
+) -> crate::Result<Response<B>> {
+    helpers::update_req_meta_in_extensions(
+        req.extensions_mut(),
+        RequestMeta::with_peer_addr(peer_addr, router.trusted_proxies.clone()),
+    );
 
-            match router.process(target_path.as_str(), req, req_info.clone()).await {
-                Ok(resp) => crate::Result::Ok(resp),
-                Err(err) => {
-                    if let Some(ref mut err_handler) = router.err_handler {
-                        crate::Result::Ok(err_handler.execute(err, req_info.clone()).await)
-                    } else {
-                        crate::Result::Err(err)
-                    }
+    if let SlashNormalization::Redirect = router.slash_normalization {
+        if req.method().is_idempotent() {
+            if let Cow::Owned(ref path) = helpers::collapse_slashes(req.uri().path()) {
+                if let Some(resp) = helpers::redirect_response(StatusCode::PERMANENT_REDIRECT, path, req.uri().query())
+                {
+                    return Ok(resp);
                 }
             }
-        };
+        }
+    }
 
-        Box::pin(fut)
+    // A malformed path is a client error, so it's passed to the error handler instead of failing the connection.
+    let target_path = match router.gen_target_path(req.uri().path()) {
+        Ok(target_path) => target_path,
+        Err(err) => return reject_request(router, req, err).await,
+    };
+
+    let req_info = gen_req_info(&router, &mut req);
+
+    match router.process(target_path.as_str(), req, req_info.clone()).await {
+        Ok(resp) => crate::Result::Ok(resp),
+        Err(err) => execute_err_handler(&router, err, req_info).await,
     }
 }
 
-#[derive(Debug)]
-pub struct RequestServiceBuilder<B, E> {
-    router: Router<B, E>,
+// Same as the plain request handling, but it records the matched route and the latency for the metrics callback.
+async fn handle_request_with_metrics<
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+>(
+    router: Arc<Router<B, E, ReqB>>,
+    mut req: Request<ReqB>,
+    peer_addr: PeerAddr,
+    shutdown: ShutdownHandle,
+) -> crate::Result<Response<B>> {
+    let start = Instant::now();
+    let method = req.method().clone();
+    // The request info shares this slot if it's generated.
+    let matched_route_path = MatchedRoutePath::default();
+    req.extensions_mut().insert(matched_route_path.clone());
+
+    let resp = if shutdown.is_shutdown() {
+        reject_request(router.clone(), req, Error::ShuttingDown).await
+    } else {
+        let in_flight_guard = shutdown.track_request();
+        let resp = handle_request(router.clone(), req, peer_addr).await;
+        drop(in_flight_guard);
+        resp
+    };
+
+    // The connection is dropped if there's no response, so there's nothing to record.
+    if let (Ok(ref resp), Some(ref on_request_complete)) = (&resp, &router.on_request_complete) {
+        on_request_complete(&RequestMetrics {
+            route_path: matched_route_path.get().unwrap_or(RequestMetrics::UNMATCHED_ROUTE_PATH),
+            method: &method,
+            status: resp.status(),
+            latency: start.elapsed(),
+            err_handler_error: resp.extensions().get::<ErrHandlerFailure>().map(|failure| &*failure.0),
+        });
+    }
+
+    resp
+}
+
+// Generates a response for a request which is not routed at all e.g. while the service is shutting down or the path is malformed.
+async fn reject_request<
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+>(
+    router: Arc<Router<B, E, ReqB>>,
+    mut req: Request<ReqB>,
+    err: crate::Error,
+) -> crate::Result<Response<B>> {
+    let req_info = gen_req_info(&router, &mut req);
+    execute_err_handler(&router, err, req_info).await
+}
+
+fn gen_req_info<B, E, ReqB>(router: &Router<B, E, ReqB>, req: &mut Request<ReqB>) -> Option<RequestInfo> {
+    let should_gen_req_info = router
+        .should_gen_req_info
+        .expect("The `should_gen_req_info` flag in Router is not initialized");
+
+    if should_gen_req_info {
+        let mut req_info = RequestInfo::new_from_req(req);
+        // The request shares its context and the matched route slot with the request info, so the post middlewares can read
+        // the values set later on.
+        if let Some(matched_route_path) = req.extensions().get::<MatchedRoutePath>() {
+            req_info.matched_route = matched_route_path.clone();
+        }
+        req.extensions_mut().insert(req_info.context.clone());
+        req.extensions_mut().insert(req_info.matched_route.clone());
+        Some(req_info)
+    } else {
+        None
+    }
+}
+
+async fn execute_err_handler<
+    B: HttpBody + Send + Sync + Unpin + 'static,
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+>(
+    router: &Router<B, E, ReqB>,
+    err: crate::Error,
+    req_info: Option<RequestInfo>,
+) -> crate::Result<Response<B>> {
+    if let Some(ref err_handler) = router.err_handler {
+        router.run_err_handler(err_handler, err, req_info).await
+    } else {
+        crate::Result::Err(err)
+    }
+}
+
+pub struct RequestServiceBuilder<B, E, ReqB = hyper::Body> {
+    pub(crate) router: Arc<Router<B, E, ReqB>>,
+    pub(crate) shutdown: ShutdownHandle,
+    pub(crate) hooks: ConnectionHooks,
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    RequestServiceBuilder<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RequestServiceBuilder<B, E, ReqB>
 {
-    pub fn new(mut router: Router<B, E>) -> crate::Result<Self> {
+    pub fn new(mut router: Router<B, E, ReqB>) -> crate::Result<Self> {
         router.init_x_powered_by_middleware();
         // router.init_keep_alive_middleware();
 
@@ -89,15 +232,35 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
 
         router.init_regex_set()?;
         router.init_req_info_gen()?;
-        Ok(Self { router })
+        Ok(Self {
+            router: Arc::new(router),
+            shutdown: ShutdownHandle::default(),
+            hooks: ConnectionHooks::default(),
+        })
     }
 }
 
-impl<B, E> RequestServiceBuilder<B, E> {
-    pub fn build(&mut self, remote_addr: SocketAddr) -> RequestService<B, E> {
+impl<B, E, ReqB> RequestServiceBuilder<B, E, ReqB> {
+    pub fn build(&self, remote_addr: SocketAddr) -> RequestService<B, E, ReqB> {
+        self.build_with_peer_addr(PeerAddr::Tcp(remote_addr))
+    }
+
+    pub fn build_with_peer_addr(&self, peer_addr: PeerAddr) -> RequestService<B, E, ReqB> {
+        if let Some(ref on_open) = self.hooks.on_open {
+            on_open(&peer_addr);
+        }
+
         RequestService {
-            router: &mut self.router,
-            remote_addr,
+            router: self.router.clone(),
+            peer_addr,
+            shutdown: self.shutdown.clone(),
+            hooks: self.hooks.clone(),
         }
     }
 }
+
+impl<B, E, ReqB> Debug for RequestServiceBuilder<B, E, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ router: {:?}, shutdown: {:?} }}", self.router, self.shutdown)
+    }
+}
@@ -1,19 +1,26 @@
 use crate::router::Router;
+use crate::service::connection::Connection;
+use crate::service::map_response_body::MapResponseBody;
 use crate::service::request_service::{RequestService, RequestServiceBuilder};
-use hyper::{body::HttpBody, server::conn::AddrStream, service::Service};
+use crate::service::shutdown::ShutdownHandle;
+use crate::types::PeerAddr;
+use hyper::{body::HttpBody, service::Service, Response};
 use std::convert::Infallible;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 /// A [`Service`](https://docs.rs/hyper/0.13.5/hyper/service/trait.Service.html) to process incoming requests.
 ///
-/// This `RouterService<B, E>` type accepts two type parameters: `B` and `E`.
+/// This `RouterService<B, E, ReqB>` type accepts three type parameters: `B`, `E` and `ReqB`.
 ///
 /// * The `B` represents the response body type which will be used by route handlers and the middlewares and this body type must implement
 ///   the [HttpBody](https://docs.rs/hyper/0.13.5/hyper/body/trait.HttpBody.html) trait. For an instance, `B` could be [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html)
 ///   type.
 /// * The `E` represents any error type which will be used by route handlers and the middlewares. This error type must implement the [std::error::Error](https://doc.rust-lang.org/std/error/trait.Error.html).
+/// * The `ReqB` represents the request body type which is passed to the route handlers and the pre middlewares, it defaults to
+///   [hyper::Body](https://docs.rs/hyper/0.13.5/hyper/body/struct.Body.html).
 ///
 /// # Examples
 ///
@@ -54,25 +61,181 @@ use std::task::{Context, Poll};
 /// }
 /// ```
 #[derive(Debug)]
-pub struct RouterService<B, E> {
-    builder: RequestServiceBuilder<B, E>,
+pub struct RouterService<B, E, ReqB = hyper::Body> {
+    builder: RequestServiceBuilder<B, E, ReqB>,
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    RouterService<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RouterService<B, E, ReqB>
 {
     /// Creates a new service with the provided router and it's ready to be used with the hyper [`serve`](https://docs.rs/hyper/0.13.5/hyper/server/struct.Builder.html#method.serve)
     /// method.
-    pub fn new(router: Router<B, E>) -> crate::Result<RouterService<B, E>> {
+    pub fn new(router: Router<B, E, ReqB>) -> crate::Result<RouterService<B, E, ReqB>> {
         let builder = RequestServiceBuilder::new(router)?;
         Ok(RouterService { builder })
     }
+
+    /// Returns a handle to gracefully shut down this service.
+    ///
+    /// Once the handle is triggered, the new requests are rejected with [`Error::ShuttingDown`](./enum.Error.html#variant.ShuttingDown)
+    /// error and the in-flight requests are processed till the end. It's paired with the hyper's
+    /// [`with_graceful_shutdown`](https://docs.rs/hyper/0.13.5/hyper/server/struct.Server.html#method.with_graceful_shutdown)
+    /// method to stop accepting new connections.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use hyper::{Body, Response, Server};
+    /// use routerify::{Router, RouterService};
+    /// use std::convert::Infallible;
+    /// use std::net::SocketAddr;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let router: Router<Body, Infallible> = Router::builder()
+    ///         .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let service = RouterService::new(router).unwrap();
+    ///     let handle = service.shutdown_handle();
+    ///
+    ///     tokio::spawn({
+    ///         let handle = handle.clone();
+    ///         async move {
+    ///             tokio::signal::ctrl_c().await.unwrap();
+    ///             handle.shutdown();
+    ///         }
+    ///     });
+    ///
+    ///     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    ///     let server = Server::bind(&addr).serve(service).with_graceful_shutdown(handle.wait());
+    ///
+    ///     if let Err(err) = server.await {
+    ///         eprintln!("Server error: {}", err);
+    ///     }
+    /// }
+    /// ```
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.builder.shutdown.clone()
+    }
+
+    /// Registers a callback which is called with the [peer address](./enum.PeerAddr.html) whenever a new connection is opened.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Response};
+    /// use routerify::{Router, RouterService};
+    /// use std::convert::Infallible;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let active_connections = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let service = RouterService::new(router)
+    ///     .unwrap()
+    ///     .on_connection_open({
+    ///         let active_connections = active_connections.clone();
+    ///         move |_| {
+    ///             active_connections.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///     })
+    ///     .on_connection_close({
+    ///         let active_connections = active_connections.clone();
+    ///         move |_| {
+    ///             active_connections.fetch_sub(1, Ordering::SeqCst);
+    ///         }
+    ///     });
+    /// ```
+    pub fn on_connection_open<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&PeerAddr) + Send + Sync + 'static,
+    {
+        self.builder.hooks.on_open = Some(Arc::new(callback));
+        self
+    }
+
+    /// Registers a callback which is called with the peer address whenever a connection is closed.
+    ///
+    /// Please refer to the [`on_connection_open`](#method.on_connection_open) method for an example.
+    pub fn on_connection_close<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&PeerAddr) + Send + Sync + 'static,
+    {
+        self.builder.hooks.on_close = Some(Arc::new(callback));
+        self
+    }
+
+    /// Converts every response leaving the router into another body type, after the post middlewares and the error handler ran.
+    ///
+    /// The router still works with the `B` body type, so the handlers, the middlewares and the error handler are unchanged, while
+    /// the server sends the `Bout` body e.g. a body which counts or encrypts the bytes. The responses generated by the router itself
+    /// e.g. the redirects and the rejections while shutting down are converted too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hyper::{Body, Response};
+    /// use routerify::{Router, RouterService};
+    /// use std::convert::Infallible;
+    ///
+    /// // A body type which is sent by the server instead of the `hyper::Body`.
+    /// struct AppBody(Body);
+    /// # impl hyper::body::HttpBody for AppBody {
+    /// #     type Data = hyper::body::Bytes;
+    /// #     type Error = hyper::Error;
+    /// #     fn poll_data(
+    /// #         mut self: std::pin::Pin<&mut Self>,
+    /// #         cx: &mut std::task::Context<'_>,
+    /// #     ) -> std::task::Poll<Option<Result<Self::Data, Self::Error>>> {
+    /// #         std::pin::Pin::new(&mut self.0).poll_data(cx)
+    /// #     }
+    /// #     fn poll_trailers(
+    /// #         mut self: std::pin::Pin<&mut Self>,
+    /// #         cx: &mut std::task::Context<'_>,
+    /// #     ) -> std::task::Poll<Result<Option<hyper::HeaderMap>, Self::Error>> {
+    /// #         std::pin::Pin::new(&mut self.0).poll_trailers(cx)
+    /// #     }
+    /// # }
+    ///
+    /// let router: Router<Body, Infallible> = Router::builder()
+    ///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let service = RouterService::new(router)
+    ///     .unwrap()
+    ///     .map_response_body(|resp: Response<Body>| resp.map(AppBody));
+    /// ```
+    pub fn map_response_body<F, Bout>(self, f: F) -> MapResponseBody<RouterService<B, E, ReqB>, F>
+    where
+        F: Fn(Response<B>) -> Response<Bout> + Send + Sync + 'static,
+    {
+        MapResponseBody::new(self, f)
+    }
+
+    pub(crate) fn build_request_service(&self, peer_addr: PeerAddr) -> RequestService<B, E, ReqB> {
+        self.builder.build_with_peer_addr(peer_addr)
+    }
 }
 
-impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
-    Service<&AddrStream> for RouterService<B, E>
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+        C: Connection,
+    > Service<&C> for RouterService<B, E, ReqB>
 {
-    type Response = RequestService<B, E>;
+    type Response = RequestService<B, E, ReqB>;
     type Error = Infallible;
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
 
@@ -80,8 +243,8 @@ impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send +
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, conn: &AddrStream) -> Self::Future {
-        let req_service = self.builder.build(conn.remote_addr());
+    fn call(&mut self, conn: &C) -> Self::Future {
+        let req_service = self.build_request_service(conn.peer_addr());
 
         let fut = async move { Ok(req_service) };
 
@@ -0,0 +1,114 @@
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// A handle to gracefully shut down a [`RouterService`](./struct.RouterService.html).
+///
+/// Once the shutdown is triggered, the new requests are rejected with [`Error::ShuttingDown`](./enum.Error.html#variant.ShuttingDown)
+/// error which is passed to the error handler (the default one responds with `503 Service Unavailable`), while the
+/// in-flight requests are processed till the end.
+///
+/// It can be cloned and shared across tasks. Please refer to the [`RouterService::shutdown_handle`](./struct.RouterService.html#method.shutdown_handle)
+/// method for an example.
+#[derive(Clone, Default)]
+pub struct ShutdownHandle {
+    state: Arc<ShutdownState>,
+}
+
+#[derive(Default)]
+struct ShutdownState {
+    shutdown: AtomicBool,
+    in_flight: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl ShutdownHandle {
+    /// Triggers the shutdown, the new requests are rejected from now on.
+    pub fn shutdown(&self) {
+        self.state.shutdown.store(true, Ordering::SeqCst);
+
+        let wakers = std::mem::take(&mut *self.state.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the shutdown has been triggered.
+    pub fn is_shutdown(&self) -> bool {
+        self.state.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of the requests which are being processed.
+    pub fn in_flight_requests(&self) -> usize {
+        self.state.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Returns a future which resolves once the shutdown is triggered. It can be passed to the hyper's
+    /// [`with_graceful_shutdown`](https://docs.rs/hyper/0.13.5/hyper/server/struct.Server.html#method.with_graceful_shutdown) method.
+    pub fn wait(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            state: self.state.clone(),
+        }
+    }
+
+    pub(crate) fn track_request(&self) -> InFlightGuard {
+        self.state.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Debug for ShutdownHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ shutdown: {:?}, in_flight_requests: {:?} }}",
+            self.is_shutdown(),
+            self.in_flight_requests()
+        )
+    }
+}
+
+/// A future which resolves once the shutdown is triggered. It's created by the [`ShutdownHandle::wait`](./struct.ShutdownHandle.html#method.wait) method.
+pub struct ShutdownSignal {
+    state: Arc<ShutdownState>,
+}
+
+impl Future for ShutdownSignal {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.shutdown.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+
+        self.state.wakers.lock().unwrap().push(cx.waker().clone());
+
+        // Check again as the shutdown might be triggered before the waker is registered.
+        if self.state.shutdown.load(Ordering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Debug for ShutdownSignal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ shutdown: {:?} }}", self.state.shutdown.load(Ordering::SeqCst))
+    }
+}
+
+pub(crate) struct InFlightGuard {
+    state: Arc<ShutdownState>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.state.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
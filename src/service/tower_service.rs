@@ -0,0 +1,91 @@
+use crate::router::Router;
+use crate::service::request_service::{handle_request, RequestServiceBuilder};
+use crate::types::PeerAddr;
+use hyper::{body::HttpBody, Request, Response};
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A [`tower::Service`](https://docs.rs/tower-service/0.3/tower_service/trait.Service.html) adapter for a `Router`, so that the router
+/// can be wrapped with the tower middlewares e.g. timeout, retry, rate limit etc.
+///
+/// The adapter is cheap to clone and all the clones share the same router. As there is no connection info available here,
+/// the [`RequestExt::remote_addr`](./ext/trait.RequestExt.html#tymethod.remote_addr) method can't be used by the route handlers.
+///
+/// It requires the `tower` feature to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use hyper::{Body, Request, Response};
+/// use routerify::{Router, RouterTowerService};
+/// use std::convert::Infallible;
+/// use std::time::Duration;
+/// use tower::{ServiceBuilder, ServiceExt};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let router: Router<Body, Infallible> = Router::builder()
+///     .get("/", |_| async move { Ok(Response::new(Body::from("Home page"))) })
+///     .build()
+///     .unwrap();
+///
+/// let service = ServiceBuilder::new()
+///     .timeout(Duration::from_secs(30))
+///     .service(RouterTowerService::new(router).unwrap());
+///
+/// let resp = service.oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+/// assert_eq!(resp.status(), 200);
+/// # }
+/// ```
+pub struct RouterTowerService<B, E, ReqB = hyper::Body> {
+    router: Arc<Router<B, E, ReqB>>,
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > RouterTowerService<B, E, ReqB>
+{
+    /// Creates a new service with the provided router.
+    pub fn new(router: Router<B, E, ReqB>) -> crate::Result<RouterTowerService<B, E, ReqB>> {
+        let builder = RequestServiceBuilder::new(router)?;
+        Ok(RouterTowerService { router: builder.router })
+    }
+}
+
+impl<
+        B: HttpBody + Send + Sync + Unpin + 'static,
+        E: std::error::Error + Send + Sync + Unpin + 'static,
+        ReqB: HttpBody + Send + 'static,
+    > tower_service::Service<Request<ReqB>> for RouterTowerService<B, E, ReqB>
+{
+    type Response = Response<B>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<ReqB>) -> Self::Future {
+        Box::pin(handle_request(self.router.clone(), req, PeerAddr::Unknown))
+    }
+}
+
+impl<B, E, ReqB> Clone for RouterTowerService<B, E, ReqB> {
+    fn clone(&self) -> Self {
+        RouterTowerService {
+            router: self.router.clone(),
+        }
+    }
+}
+
+impl<B, E, ReqB> Debug for RouterTowerService<B, E, ReqB> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ router: {:?} }}", self.router)
+    }
+}
@@ -0,0 +1,344 @@
+//! Sessions whose data is kept on the server by a [`SessionStore`](./trait.SessionStore.html) and whose id is kept in a
+//! signed cookie on the client.
+//!
+//! The [`session`](./fn.session.html) middleware loads the session of a request, the route handlers access it by the
+//! [`req.session()`](../ext/trait.RequestExt.html#tymethod.session) method, and the middleware saves the changes once the
+//! response is ready.
+//!
+//! It requires the `cookies` feature to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::session::{self, MemoryStore, SessionOptions};
+//! use routerify::prelude::*;
+//! use routerify::{CookieKeys, Router};
+//! use hyper::Body;
+//!
+//! # fn run() -> routerify::Result<Router<Body, routerify::Error>> {
+//! let keys = CookieKeys::new("a secret key of at least 32 bytes!!")?;
+//!
+//! let router = Router::builder()
+//!     .middleware(session::session(SessionOptions::new(MemoryStore::new(), keys)))
+//!     .post("/login/:user", |req| async move {
+//!         let session = req.session().unwrap();
+//!         // A new session id prevents the session fixation attacks.
+//!         session.regenerate();
+//!         session.insert("user", req.param("user").unwrap().as_str());
+//!         Ok("Logged in")
+//!     })
+//!     .get("/me", |req| async move {
+//!         let user = req.session().and_then(|session| session.get("user"));
+//!         Ok(format!("User: {}", user.as_deref().unwrap_or("guest")))
+//!     })
+//!     .post("/logout", |req| async move {
+//!         req.session().unwrap().destroy();
+//!         Ok("Logged out")
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # Ok(router)
+//! # }
+//! # run().unwrap();
+//! ```
+
+use crate::ext::{RequestExt, ResponseExt};
+use crate::middleware::{Middleware, Next};
+use crate::types::{random_token, CookieKeys, SameSite, SetCookie};
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+pub use self::store::{CacheStore, MemoryStore, SessionCache, SessionData, SessionStore};
+
+mod store;
+
+/// Options for the [session](./fn.session.html) middleware.
+#[derive(Clone)]
+pub struct SessionOptions {
+    store: Arc<dyn SessionStore>,
+    keys: CookieKeys,
+    cookie_name: String,
+    path: String,
+    domain: Option<String>,
+    secure: bool,
+    same_site: SameSite,
+    ttl: Duration,
+}
+
+impl SessionOptions {
+    /// Creates the options with the store which keeps the session data and the keys which sign the session cookie.
+    ///
+    /// The cookie is named `sid`, it's sent for every path with the `HttpOnly` and the `SameSite=Lax` attributes, and a
+    /// session expires after `24` hours of inactivity.
+    pub fn new<S: SessionStore>(store: S, keys: CookieKeys) -> SessionOptions {
+        SessionOptions {
+            store: Arc::new(store),
+            keys,
+            cookie_name: "sid".to_owned(),
+            path: "/".to_owned(),
+            domain: None,
+            secure: false,
+            same_site: SameSite::Lax,
+            ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// Sets the name of the session cookie.
+    pub fn cookie_name<N: Into<String>>(mut self, cookie_name: N) -> Self {
+        self.cookie_name = cookie_name.into();
+        self
+    }
+
+    /// Sets the `Path` attribute of the session cookie.
+    pub fn path<P: Into<String>>(mut self, path: P) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets the `Domain` attribute of the session cookie.
+    pub fn domain<D: Into<String>>(mut self, domain: D) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the `Secure` attribute of the session cookie, it should be enabled if the app is served over HTTPS.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the session cookie.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Sets the time after which an inactive session expires, every request of the session extends it.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn cookie(&self, id: &str) -> SetCookie {
+        let cookie = SetCookie::signed(self.cookie_name.as_str(), id, &self.keys).max_age(self.ttl);
+        self.with_attributes(cookie)
+    }
+
+    fn removal_cookie(&self) -> SetCookie {
+        self.with_attributes(SetCookie::removal(self.cookie_name.as_str()))
+    }
+
+    fn with_attributes(&self, cookie: SetCookie) -> SetCookie {
+        let cookie = cookie
+            .path(self.path.as_str())
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(self.same_site);
+
+        match self.domain {
+            Some(ref domain) => cookie.domain(domain.as_str()),
+            None => cookie,
+        }
+    }
+}
+
+impl Debug for SessionOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ cookie_name: {:?}, path: {:?}, domain: {:?}, secure: {:?}, same_site: {:?}, ttl: {:?} }}",
+            self.cookie_name, self.path, self.domain, self.secure, self.same_site, self.ttl
+        )
+    }
+}
+
+/// The session of a request, it's a cheap handle which can be cloned and accessed from the route handler by the
+/// [`req.session()`](../ext/trait.RequestExt.html#tymethod.session) method.
+///
+/// The changes are saved by the [session](./fn.session.html) middleware after the route handler returns. A new session is
+/// saved and its cookie is sent only if any data is inserted into it.
+#[derive(Clone)]
+pub struct Session {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // It's `None` for a new session, the id is generated when it's saved for the first time.
+    id: Option<String>,
+    data: SessionData,
+    changed: bool,
+    regenerate: bool,
+    destroyed: bool,
+}
+
+impl Session {
+    fn new(id: Option<String>, data: SessionData) -> Session {
+        Session {
+            state: Arc::new(Mutex::new(State {
+                id,
+                data,
+                ..State::default()
+            })),
+        }
+    }
+
+    /// Returns the value of the specified key.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.lock().data.get(key).cloned()
+    }
+
+    /// Inserts a value, replacing the previous value of the key.
+    pub fn insert<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        let mut state = self.lock();
+        state.data.insert(key.into(), value.into());
+        state.changed = true;
+    }
+
+    /// Removes the specified key and returns its value.
+    pub fn remove(&self, key: &str) -> Option<String> {
+        let mut state = self.lock();
+        let value = state.data.remove(key);
+        state.changed |= value.is_some();
+        value
+    }
+
+    /// Removes all the keys, the session itself is kept.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.changed |= !state.data.is_empty();
+        state.data.clear();
+    }
+
+    /// Checks if the session has no data.
+    pub fn is_empty(&self) -> bool {
+        self.lock().data.is_empty()
+    }
+
+    /// Moves the session data to a new session id and removes the old one, it should be called when the privilege level of the
+    /// user changes e.g. on a login.
+    pub fn regenerate(&self) {
+        let mut state = self.lock();
+        state.regenerate = true;
+        state.changed = true;
+    }
+
+    /// Removes the session from the store and the cookie from the client.
+    pub fn destroy(&self) {
+        let mut state = self.lock();
+        state.destroyed = true;
+        state.data.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Debug for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        write!(
+            f,
+            "{{ is_new: {:?}, len: {:?}, changed: {:?}, destroyed: {:?} }}",
+            state.id.is_none(),
+            state.data.len(),
+            state.changed,
+            state.destroyed
+        )
+    }
+}
+
+/// Creates an around middleware which loads the session of every request from the store by the id in the signed session
+/// cookie, and saves it back after the rest of the request processing.
+///
+/// A missing, expired or tampered cookie starts a new session. The expiry of an existing session and its cookie is extended on
+/// every request. If the store fails, the request is responded with `500 Internal Server Error`.
+///
+/// Please refer to the [session](./index.html) module for an example.
+pub fn session<E, ReqB>(options: SessionOptions) -> Middleware<Body, E, ReqB>
+where
+    E: std::error::Error + Send + Sync + Unpin + 'static,
+    ReqB: HttpBody + Send + 'static,
+{
+    let options = Arc::new(options);
+
+    Middleware::around(move |mut req: Request<ReqB>, next: Next<Body, ReqB>| {
+        let options = options.clone();
+        let id = req
+            .cookies()
+            .get_signed(&options.cookie_name, &options.keys)
+            .map(str::to_owned);
+
+        async move {
+            let session = match id {
+                Some(id) => match options.store.load(&id).await {
+                    Ok(Some(data)) => Session::new(Some(id), data),
+                    Ok(None) => Session::new(None, SessionData::new()),
+                    Err(_) => return Ok(internal_server_error()),
+                },
+                None => Session::new(None, SessionData::new()),
+            };
+            req.set_context(session.clone());
+
+            // The errors are already passed to the error handler, so it fails only if the `Next` is misused.
+            let mut resp = match next.run(req).await {
+                Ok(resp) => resp,
+                Err(_) => return Ok(internal_server_error()),
+            };
+
+            match save(&options, &session).await {
+                Ok(Some(cookie)) => {
+                    if resp.set_cookie(&cookie).is_err() {
+                        return Ok(internal_server_error());
+                    }
+                }
+                Ok(None) => {}
+                Err(_) => return Ok(internal_server_error()),
+            }
+
+            Ok(resp)
+        }
+    })
+}
+
+// Saves the session and returns the cookie to send.
+async fn save(options: &SessionOptions, session: &Session) -> crate::Result<Option<SetCookie>> {
+    let (old_id, new_id, data) = {
+        let state = session.lock();
+
+        if state.destroyed {
+            (state.id.clone(), None, SessionData::new())
+        } else if state.id.is_none() && !state.changed {
+            return Ok(None);
+        } else {
+            let new_id = match state.id {
+                Some(ref id) if !state.regenerate => id.clone(),
+                _ => random_token(),
+            };
+            (state.id.clone(), Some(new_id), state.data.clone())
+        }
+    };
+
+    if let Some(ref old_id) = old_id {
+        if new_id.as_ref() != Some(old_id) {
+            options.store.remove(old_id).await?;
+        }
+    }
+
+    match new_id {
+        Some(new_id) => {
+            options.store.save(&new_id, data, options.ttl).await?;
+            Ok(Some(options.cookie(&new_id)))
+        }
+        None => Ok(old_id.map(|_| options.removal_cookie())),
+    }
+}
+
+fn internal_server_error() -> Response<Body> {
+    let mut resp = Response::new(Body::from("Internal Server Error"));
+    *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    resp
+}
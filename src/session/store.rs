@@ -0,0 +1,353 @@
+use futures::future::{self, BoxFuture};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// The expired sessions of a `MemoryStore` are swept once every this many saves.
+const SWEEP_INTERVAL: usize = 1024;
+
+const SESSION_DATA_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'%').add(b'&').add(b'=').add(b'+');
+
+/// The data of a session.
+pub type SessionData = HashMap<String, String>;
+
+/// A storage of the session data e.g. a database or a cache server, keyed by the session ids.
+///
+/// A store backend can wrap its own errors in an [`Error::Store`](../enum.Error.html#variant.Store) error.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::session::{SessionData, SessionStore};
+/// use futures::future::BoxFuture;
+/// use std::time::Duration;
+///
+/// struct RedisStore {
+///     // A connection pool.
+/// }
+///
+/// impl SessionStore for RedisStore {
+///     fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, routerify::Result<Option<SessionData>>> {
+///         Box::pin(async move { /* GET session:{id} */ Ok(None) })
+///     }
+///
+///     fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> BoxFuture<'a, routerify::Result<()>> {
+///         Box::pin(async move { /* SET session:{id} EX {ttl} */ Ok(()) })
+///     }
+///
+///     fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, routerify::Result<()>> {
+///         Box::pin(async move { /* DEL session:{id} */ Ok(()) })
+///     }
+/// }
+/// ```
+pub trait SessionStore: Send + Sync + 'static {
+    /// Loads the data of the session, it's `None` if the session doesn't exist or it has expired.
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<Option<SessionData>>>;
+
+    /// Saves the data of the session, which expires after the `ttl` unless it's saved again.
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> BoxFuture<'a, crate::Result<()>>;
+
+    /// Removes the session.
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<()>>;
+}
+
+impl<S: SessionStore> SessionStore for Arc<S> {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<Option<SessionData>>> {
+        (**self).load(id)
+    }
+
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> BoxFuture<'a, crate::Result<()>> {
+        (**self).save(id, data, ttl)
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<()>> {
+        (**self).remove(id)
+    }
+}
+
+/// A [`SessionStore`](./trait.SessionStore.html) which keeps the sessions in the memory of the process, so they are lost on
+/// a restart and they are not shared between the processes.
+///
+/// An expired session is removed when it's loaded, and all the expired sessions are swept once every `1024` saves.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    sessions: Arc<Mutex<Sessions>>,
+}
+
+#[derive(Default)]
+struct Sessions {
+    entries: HashMap<String, (SessionData, Instant)>,
+    // The number of the saves since the last sweep.
+    saves: usize,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Returns the number of the stored sessions, including the expired ones which aren't removed yet.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Checks if there are no stored sessions.
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Sessions> {
+        self.sessions.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<Option<SessionData>>> {
+        let mut sessions = self.lock();
+
+        let data = match sessions.entries.get(id) {
+            Some((data, expires_at)) if *expires_at > Instant::now() => Some(data.clone()),
+            Some(_) => {
+                sessions.entries.remove(id);
+                None
+            }
+            None => None,
+        };
+        Box::pin(future::ready(Ok(data)))
+    }
+
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> BoxFuture<'a, crate::Result<()>> {
+        let now = Instant::now();
+        let mut sessions = self.lock();
+
+        sessions.saves += 1;
+        if sessions.saves >= SWEEP_INTERVAL {
+            sessions.saves = 0;
+            sessions.entries.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+
+        sessions.entries.insert(id.to_owned(), (data, now + ttl));
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<()>> {
+        self.lock().entries.remove(id);
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+impl Debug for MemoryStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ len: {:?} }}", self.len())
+    }
+}
+
+/// A key-value cache whose entries expire e.g. a Redis or a Memcached server, which keeps the sessions of a
+/// [`CacheStore`](./struct.CacheStore.html).
+///
+/// A cache backend can wrap its own errors in an [`Error::Store`](../enum.Error.html#variant.Store) error.
+pub trait SessionCache: Send + Sync + 'static {
+    /// Gets the value of the key, it's `None` if the key doesn't exist or it has expired.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, crate::Result<Option<String>>>;
+
+    /// Sets the value of the key, which expires after the `ttl`.
+    fn set<'a>(&'a self, key: &'a str, value: String, ttl: Duration) -> BoxFuture<'a, crate::Result<()>>;
+
+    /// Deletes the key.
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, crate::Result<()>>;
+}
+
+/// A [`SessionStore`](./trait.SessionStore.html) which keeps the sessions in an asynchronous key-value
+/// [cache](./trait.SessionCache.html), so the sessions are shared between the processes and they survive a restart.
+///
+/// A session is kept under its id prefixed by `session:` by default, its data is encoded like a query string.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::session::{CacheStore, SessionCache, SessionOptions};
+/// use routerify::CookieKeys;
+/// use futures::future::BoxFuture;
+/// use std::time::Duration;
+///
+/// struct RedisCache {
+///     // A connection pool.
+/// }
+///
+/// impl SessionCache for RedisCache {
+///     fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, routerify::Result<Option<String>>> {
+///         Box::pin(async move { /* GET {key} */ Ok(None) })
+///     }
+///
+///     fn set<'a>(&'a self, key: &'a str, value: String, ttl: Duration) -> BoxFuture<'a, routerify::Result<()>> {
+///         Box::pin(async move { /* SET {key} {value} EX {ttl} */ Ok(()) })
+///     }
+///
+///     fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, routerify::Result<()>> {
+///         Box::pin(async move { /* DEL {key} */ Ok(()) })
+///     }
+/// }
+///
+/// # fn run() -> routerify::Result<SessionOptions> {
+/// let store = CacheStore::new(RedisCache {}).prefix("myapp:session:");
+/// let options = SessionOptions::new(store, CookieKeys::new("a secret key of at least 32 bytes!!")?);
+/// # Ok(options)
+/// # }
+/// # run().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CacheStore<C> {
+    cache: C,
+    prefix: String,
+}
+
+impl<C: SessionCache> CacheStore<C> {
+    /// Creates a store which keeps the sessions in the cache.
+    pub fn new(cache: C) -> CacheStore<C> {
+        CacheStore {
+            cache,
+            prefix: "session:".to_owned(),
+        }
+    }
+
+    /// Sets the prefix of the keys of the sessions, it's `session:` by default.
+    pub fn prefix<P: Into<String>>(mut self, prefix: P) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, id: &str) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+impl<C: SessionCache> SessionStore for CacheStore<C> {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<Option<SessionData>>> {
+        Box::pin(async move {
+            let key = self.key(id);
+            let value = self.cache.get(&key).await?;
+            Ok(value.as_deref().map(decode_session_data))
+        })
+    }
+
+    fn save<'a>(&'a self, id: &'a str, data: SessionData, ttl: Duration) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(async move {
+            let key = self.key(id);
+            self.cache.set(&key, encode_session_data(&data), ttl).await
+        })
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(async move {
+            let key = self.key(id);
+            self.cache.delete(&key).await
+        })
+    }
+}
+
+// Encodes the data like a query string, the keys are sorted so the same data is always encoded the same.
+fn encode_session_data(data: &SessionData) -> String {
+    let mut pairs = data.iter().collect::<Vec<_>>();
+    pairs.sort();
+
+    pairs
+        .into_iter()
+        .map(|(key, val)| {
+            format!(
+                "{}={}",
+                utf8_percent_encode(key, SESSION_DATA_ENCODE_SET),
+                utf8_percent_encode(val, SESSION_DATA_ENCODE_SET)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn decode_session_data(encoded: &str) -> SessionData {
+    encoded
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, val) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+            (decode(key), decode(val))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryStore::new();
+        let data: SessionData = vec![("user".to_owned(), "alice".to_owned())].into_iter().collect();
+
+        block_on(store.save("a", data.clone(), Duration::from_secs(60))).unwrap();
+        block_on(store.save("b", data.clone(), Duration::from_secs(0))).unwrap();
+        block_on(store.save("c", data.clone(), Duration::from_secs(0))).unwrap();
+        assert_eq!(block_on(store.load("a")).unwrap(), Some(data.clone()));
+
+        // An expired session is removed when it's loaded.
+        assert_eq!(block_on(store.load("b")).unwrap(), None);
+        assert_eq!(store.len(), 2);
+
+        // The other expired sessions are swept once every `SWEEP_INTERVAL` saves.
+        for _ in 4..SWEEP_INTERVAL {
+            block_on(store.save("a", data.clone(), Duration::from_secs(60))).unwrap();
+        }
+        assert_eq!(store.len(), 2);
+        block_on(store.save("a", data, Duration::from_secs(60))).unwrap();
+        assert_eq!(store.len(), 1);
+
+        block_on(store.remove("a")).unwrap();
+        assert_eq!(block_on(store.load("a")).unwrap(), None);
+    }
+
+    #[derive(Default)]
+    struct TestCache(Mutex<HashMap<String, String>>);
+
+    impl SessionCache for TestCache {
+        fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, crate::Result<Option<String>>> {
+            Box::pin(future::ready(Ok(self.0.lock().unwrap().get(key).cloned())))
+        }
+
+        fn set<'a>(&'a self, key: &'a str, value: String, _: Duration) -> BoxFuture<'a, crate::Result<()>> {
+            self.0.lock().unwrap().insert(key.to_owned(), value);
+            Box::pin(future::ready(Ok(())))
+        }
+
+        fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, crate::Result<()>> {
+            self.0.lock().unwrap().remove(key);
+            Box::pin(future::ready(Ok(())))
+        }
+    }
+
+    #[test]
+    fn test_cache_store() {
+        let store = CacheStore::new(TestCache::default()).prefix("app:");
+        let data: SessionData = vec![
+            ("user".to_owned(), "alice & bob".to_owned()),
+            ("a=b".to_owned(), "100%+ café".to_owned()),
+            ("empty".to_owned(), String::new()),
+        ]
+        .into_iter()
+        .collect();
+
+        block_on(store.save("a", data.clone(), Duration::from_secs(60))).unwrap();
+        assert_eq!(
+            store.cache.0.lock().unwrap().get("app:a").unwrap(),
+            "a%3Db=100%25%2B%20caf%C3%A9&empty=&user=alice%20%26%20bob"
+        );
+        assert_eq!(block_on(store.load("a")).unwrap(), Some(data));
+        assert_eq!(block_on(store.load("b")).unwrap(), None);
+
+        block_on(store.remove("a")).unwrap();
+        assert_eq!(block_on(store.load("a")).unwrap(), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Helpers to respond with a stream of [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+//!
+//! It requires the `sse` feature to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::Router;
+//! use routerify::sse::{self, SseEvent};
+//! use futures::StreamExt;
+//! use hyper::Body;
+//! use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! # fn run() -> Router<Body, Infallible> {
+//! let router = Router::builder()
+//!     .get("/counter", |_| async move {
+//!         let events = futures::stream::iter(1..=3).map(|n| SseEvent::new(n.to_string()).event("count"));
+//!         Ok(sse::sse_response_with_keep_alive(events, Duration::from_secs(15)))
+//!     })
+//!     .build()
+//!     .unwrap();
+//! # router
+//! # }
+//! # run();
+//! ```
+
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Response};
+use std::convert::Infallible;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// An event sent to the client by the [sse_response](./fn.sse_response.html) function.
+///
+/// The `data` can span multiple lines, every line is sent in its own `data:` field and the client joins them back. The line
+/// breaks in the `event` and the `id` are dropped as they can't be represented.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The event type, the client dispatches the event as a `message` if it's `None`.
+    pub event: Option<String>,
+
+    /// The event data.
+    pub data: String,
+
+    /// The event id, the client sends the last id back in the `Last-Event-ID` header when it reconnects.
+    pub id: Option<String>,
+
+    /// The time the client should wait before reconnecting.
+    pub retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates an event with the data.
+    pub fn new<D: Into<String>>(data: D) -> SseEvent {
+        SseEvent {
+            data: data.into(),
+            ..SseEvent::default()
+        }
+    }
+
+    /// Sets the event type.
+    pub fn event<T: Into<String>>(mut self, event: T) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the event id.
+    pub fn id<T: Into<String>>(mut self, id: T) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reconnection time.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+/// Formats the event as it's sent over the wire, it ends with an empty line.
+impl Display for SseEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let single_line = |val: &str| val.replace(['\r', '\n'], "");
+
+        if let Some(ref event) = self.event {
+            writeln!(f, "event: {}", single_line(event))?;
+        }
+
+        if let Some(ref id) = self.id {
+            writeln!(f, "id: {}", single_line(id))?;
+        }
+
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+
+        let data = self.data.replace("\r\n", "\n").replace('\r', "\n");
+        for line in data.split('\n') {
+            writeln!(f, "data: {}", line)?;
+        }
+
+        writeln!(f)
+    }
+}
+
+/// Creates a `text/event-stream` response which sends the events of the stream, the response ends with the stream.
+///
+/// The `Cache-Control: no-cache` and the `X-Accel-Buffering: no` headers are set, so the events aren't held back by the
+/// caches and the proxies like nginx.
+pub fn sse_response<S>(events: S) -> Response<Body>
+where
+    S: Stream<Item = SseEvent> + Send + 'static,
+{
+    event_stream_response(Body::wrap_stream(
+        events.map(|event| Ok::<_, Infallible>(frame(&event))),
+    ))
+}
+
+/// Same as [sse_response](./fn.sse_response.html), but it sends a comment line every `interval` to keep the idle
+/// connection open through the proxies. A zero `interval` disables the keep-alive comments.
+pub fn sse_response_with_keep_alive<S>(events: S, interval: Duration) -> Response<Body>
+where
+    S: Stream<Item = SseEvent> + Send + 'static,
+{
+    if interval == Duration::from_secs(0) {
+        return sse_response(events);
+    }
+
+    let events = events
+        .map(|event| Some(frame(&event)))
+        .chain(stream::once(future::ready(None)));
+
+    let keep_alive = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval)
+        .map(|_| Some(Bytes::from_static(b": keep-alive\n\n")));
+
+    // The keep-alive stream never ends, so the end of the events is marked by a `None`.
+    let frames = stream::select(events, keep_alive)
+        .take_while(|frame| future::ready(frame.is_some()))
+        .map(|frame| Ok::<_, Infallible>(frame.unwrap_or_default()));
+
+    event_stream_response(Body::wrap_stream(frames))
+}
+
+fn frame(event: &SseEvent) -> Bytes {
+    Bytes::from(event.to_string())
+}
+
+fn event_stream_response(body: Body) -> Response<Body> {
+    let mut resp = Response::new(body);
+
+    let headers = resp.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert("x-accel-buffering", HeaderValue::from_static("no"));
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_event_format() {
+        assert_eq!(SseEvent::new("hello").to_string(), "data: hello\n\n");
+        assert_eq!(
+            SseEvent::new("line 1\nline 2\r\nline 3")
+                .event("update")
+                .id("7")
+                .retry(Duration::from_secs(3))
+                .to_string(),
+            "event: update\nid: 7\nretry: 3000\ndata: line 1\ndata: line 2\ndata: line 3\n\n"
+        );
+        assert_eq!(SseEvent::new("").event("a\nb").to_string(), "event: ab\ndata: \n\n");
+    }
+}
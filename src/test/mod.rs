@@ -0,0 +1,224 @@
+//! An in-process client to test a router without binding any TCP listener.
+//!
+//! It requires the `test-util` feature to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use routerify::Router;
+//! use routerify::prelude::*;
+//! use routerify::test::TestClient;
+//! use hyper::{Response, Body};
+//! use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let router: Router<Body, Infallible> = Router::builder()
+//!     .get("/users/:id", |req| async move {
+//!         Ok(Response::new(Body::from(format!("User {}", req.param("id").unwrap()))))
+//!     })
+//!     .build()
+//!     .unwrap();
+//!
+//! let client = TestClient::new(router).unwrap();
+//!
+//! let resp = client.get("/users/3").header("x-request-id", "1").send().await;
+//! assert_eq!(resp.status(), 200);
+//!
+//! let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+//! assert_eq!(body, "User 3");
+//! # }
+//! ```
+
+use crate::router::Router;
+use crate::service::{handle_request, RequestServiceBuilder};
+use crate::types::PeerAddr;
+use http::header::{HeaderName, HeaderValue};
+use http::request::Builder;
+use hyper::{body::HttpBody, Body, Method, Request, Response};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>');
+
+/// A client which sends the requests directly to a router.
+///
+/// The router is initialized the same way as the [`RouterService`](../struct.RouterService.html) does, so the
+/// middlewares, the default routes and the error handler are all applied. The requests carry a synthetic remote address
+/// which defaults to `127.0.0.1:0`.
+pub struct TestClient<B, E> {
+    router: Arc<Router<B, E>>,
+    remote_addr: SocketAddr,
+}
+
+impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
+    TestClient<B, E>
+{
+    /// Creates a new client for the provided router.
+    pub fn new(router: Router<B, E>) -> crate::Result<TestClient<B, E>> {
+        let builder = RequestServiceBuilder::new(router)?;
+
+        Ok(TestClient {
+            router: builder.router,
+            remote_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+        })
+    }
+
+    /// Sets the remote address of the requests sent by this client.
+    pub fn with_remote_addr(mut self, remote_addr: SocketAddr) -> TestClient<B, E> {
+        self.remote_addr = remote_addr;
+        self
+    }
+
+    /// Starts a request with the method and the uri. The uri is percent-encoded like a real client would do.
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest<B, E> {
+        TestRequest {
+            router: self.router.clone(),
+            remote_addr: self.remote_addr,
+            builder: Request::builder().method(method).uri(encode_uri(uri)),
+            body: Body::empty(),
+        }
+    }
+
+    /// Starts a `GET` request.
+    pub fn get(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::GET, uri)
+    }
+
+    /// Starts a `POST` request.
+    pub fn post(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::POST, uri)
+    }
+
+    /// Starts a `PUT` request.
+    pub fn put(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::PUT, uri)
+    }
+
+    /// Starts a `PATCH` request.
+    pub fn patch(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::PATCH, uri)
+    }
+
+    /// Starts a `DELETE` request.
+    pub fn delete(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::DELETE, uri)
+    }
+
+    /// Starts a `HEAD` request.
+    pub fn head(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::HEAD, uri)
+    }
+
+    /// Starts an `OPTIONS` request.
+    pub fn options(&self, uri: &str) -> TestRequest<B, E> {
+        self.request(Method::OPTIONS, uri)
+    }
+}
+
+impl<B, E> Debug for TestClient<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ router: {:?}, remote_addr: {:?} }}",
+            self.router, self.remote_addr
+        )
+    }
+}
+
+/// A request which is being built by a [`TestClient`](./struct.TestClient.html).
+pub struct TestRequest<B, E> {
+    router: Arc<Router<B, E>>,
+    remote_addr: SocketAddr,
+    builder: Builder,
+    body: Body,
+}
+
+impl<B: HttpBody + Send + Sync + Unpin + 'static, E: std::error::Error + Send + Sync + Unpin + 'static>
+    TestRequest<B, E>
+{
+    /// Appends a header to the request.
+    pub fn header<K, V>(mut self, key: K, value: V) -> TestRequest<B, E>
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// Sets the request body.
+    pub fn body<T: Into<Body>>(mut self, body: T) -> TestRequest<B, E> {
+        self.body = body.into();
+        self
+    }
+
+    /// Sends the request to the router and returns the response.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the request is invalid e.g. a header value is not valid, or if the router fails to generate any
+    /// response, which happens only when there is no error handler to handle a failure.
+    pub async fn send(self) -> Response<B> {
+        let req = self.builder.body(self.body).expect("Couldn't build the test request");
+
+        handle_request(self.router, req, PeerAddr::Tcp(self.remote_addr))
+            .await
+            .unwrap_or_else(|err| panic!("The router couldn't process the test request: {}", err))
+    }
+}
+
+impl<B, E> Debug for TestRequest<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ builder: {:?}, remote_addr: {:?} }}",
+            self.builder, self.remote_addr
+        )
+    }
+}
+
+// The fragment is never sent by a real client and the unsafe characters are percent-encoded.
+fn encode_uri(uri: &str) -> String {
+    let uri = uri.split('#').next().unwrap_or("");
+
+    let mut segments = uri.splitn(2, '?');
+    let path = segments.next().unwrap_or("");
+
+    let mut encoded = utf8_percent_encode(path, PATH_ENCODE_SET).to_string();
+    if let Some(query) = segments.next() {
+        encoded.push('?');
+        encoded.push_str(&utf8_percent_encode(query, QUERY_ENCODE_SET).to_string());
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_uri() {
+        assert_eq!(encode_uri("/users/john doe"), "/users/john%20doe");
+        assert_eq!(
+            encode_uri("/users/ünïcode?q=a b#top"),
+            "/users/%C3%BCn%C3%AFcode?q=a%20b"
+        );
+        assert_eq!(encode_uri("/already%20encoded"), "/already%20encoded");
+    }
+}
@@ -0,0 +1,82 @@
+use hyper::{Request, Response};
+use std::future::Future;
+use tracing::{field, Instrument, Span};
+
+/// The processing stages which are instrumented as the child spans of a request span.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Stage {
+    PreMiddleware,
+    AroundMiddleware,
+    Route,
+    PostMiddleware,
+}
+
+/// Creates the span of a request, the route template and the status code are recorded once they're known. The span is also
+/// added to the request extensions, so the route can be recorded on it regardless of the currently entered span.
+pub(crate) fn request_span<ReqB>(req: &mut Request<ReqB>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        http.method = %req.method(),
+        http.target = %req.uri(),
+        http.route = field::Empty,
+        http.status_code = field::Empty,
+    );
+
+    req.extensions_mut().insert(span.clone());
+    span
+}
+
+pub(crate) async fn instrument_request<B, F>(span: Span, fut: F) -> crate::Result<Response<B>>
+where
+    F: Future<Output = crate::Result<Response<B>>>,
+{
+    let res = fut.instrument(span.clone()).await;
+
+    if let Ok(ref res) = res {
+        span.record("http.status_code", res.status().as_u16());
+    }
+
+    res
+}
+
+/// Instruments a middleware or a route handler, the span is named by the stage and carries the path it's registered on.
+pub(crate) fn instrument<F: Future>(stage: Stage, path: &str, fut: F) -> tracing::instrument::Instrumented<F> {
+    let span = match stage {
+        Stage::PreMiddleware => tracing::debug_span!("pre_middleware", path),
+        Stage::AroundMiddleware => tracing::debug_span!("around_middleware", path),
+        Stage::Route => tracing::debug_span!("route", path),
+        Stage::PostMiddleware => tracing::debug_span!("post_middleware", path),
+    };
+
+    fut.instrument(span)
+}
+
+/// Records the matched route on the request span.
+pub(crate) fn record_route<ReqB>(req: &Request<ReqB>, path: &str) {
+    if let Some(span) = req.extensions().get::<Span>() {
+        span.record("http.route", path);
+    }
+}
+
+/// Records an error passed to the error handler as an event with the whole source chain.
+pub(crate) fn record_error(err: &crate::Error) {
+    tracing::warn!(error = %error_chain(err), "the request failed");
+}
+
+/// Records an error returned by the error handler itself, the fallback `500` response is sent instead.
+pub(crate) fn record_err_handler_error(err: &(dyn std::error::Error + 'static)) {
+    tracing::error!(error = %error_chain(err), "the error handler failed");
+}
+
+fn error_chain(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut chain = err.to_string();
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        chain.push_str(": ");
+        chain.push_str(&err.to_string());
+        source = err.source();
+    }
+
+    chain
+}
@@ -0,0 +1,221 @@
+use hyper::header::{self, HeaderMap};
+
+/// Represents the media ranges of the `Accept` request headers e.g. `text/html` or `application/*;q=0.8`, ranked by their
+/// quality values and then by their specificity, so the preferred one comes first.
+///
+/// The media ranges are lowercased and their parameters except the `q` one are dropped. The malformed ones are skipped.
+///
+/// **Note:** This type shouldn't be created directly. It's parsed from the `req` object on the first access by the
+/// `req.accepts()` method.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accept(Vec<MediaRange>);
+
+/// A media range of the `Accept` request header with its quality value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRange {
+    media_type: String,
+    quality: f32,
+}
+
+impl Accept {
+    pub(crate) fn parse(headers: &HeaderMap) -> Accept {
+        let mut ranges = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(','))
+            .filter_map(MediaRange::parse)
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so the equally ranked ones keep the client's order.
+        ranges.sort_by(|a, b| {
+            b.quality
+                .partial_cmp(&a.quality)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.specificity().cmp(&a.specificity()))
+        });
+
+        Accept(ranges)
+    }
+
+    /// Returns the quality value the client gives to the specified media type e.g. `application/json`, which is taken from the
+    /// most specific matching media range. It's `1.0` if the client didn't send an `Accept` header and `0.0` if no media
+    /// range matches.
+    pub fn quality(&self, media_type: &str) -> f32 {
+        if self.0.is_empty() {
+            return 1.0;
+        }
+
+        let media_type = essence(media_type);
+        self.0
+            .iter()
+            .filter(|range| range.matches(&media_type))
+            .max_by_key(|range| range.specificity())
+            .map_or(0.0, |range| range.quality)
+    }
+
+    /// Checks if the client accepts the specified media type.
+    pub fn accepts(&self, media_type: &str) -> bool {
+        self.quality(media_type) > 0.0
+    }
+
+    /// Selects the media type the client prefers the most among the ones the server can produce. The server's order breaks
+    /// the ties, so the first one is selected if the client didn't send an `Accept` header or it accepts anything.
+    ///
+    /// It returns `None` if the client accepts none of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |req| async move {
+    ///         let body = match req.accepts().negotiate(&["application/json", "text/plain"]) {
+    ///             Some("application/json") => r#"{"page":"home"}"#,
+    ///             _ => "Home page",
+    ///         };
+    ///
+    ///         Ok(Response::new(Body::from(body)))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        let mut selected: Option<(&'a str, f32)> = None;
+
+        for media_type in available {
+            let quality = self.quality(media_type);
+            if quality > 0.0 && selected.is_none_or(|(_, selected_quality)| quality > selected_quality) {
+                selected = Some((media_type, quality));
+            }
+        }
+
+        selected.map(|(media_type, _)| media_type)
+    }
+
+    /// Returns the number of the media ranges.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if there are no media ranges i.e. the client didn't send an `Accept` header.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the ranked media ranges.
+    pub fn iter(&self) -> impl Iterator<Item = &MediaRange> {
+        self.0.iter()
+    }
+}
+
+impl MediaRange {
+    fn parse(item: &str) -> Option<MediaRange> {
+        let mut segments = item.split(';');
+        let media_type = segments.next()?.trim().to_ascii_lowercase();
+
+        match media_type.split_once('/') {
+            Some((ty, subtype)) if !ty.is_empty() && !subtype.is_empty() && (ty != "*" || subtype == "*") => {}
+            _ => return None,
+        }
+
+        let quality = segments
+            .filter_map(|param| {
+                let param = param.trim();
+                param.strip_prefix("q=").or_else(|| param.strip_prefix("Q="))
+            })
+            .next()
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok().filter(|q| q.is_finite()))?
+            .clamp(0.0, 1.0);
+
+        Some(MediaRange { media_type, quality })
+    }
+
+    /// Returns the media range without the parameters e.g. `text/html` or `text/*`.
+    pub fn media_type(&self) -> &str {
+        self.media_type.as_str()
+    }
+
+    /// Returns the quality value between `0.0` and `1.0`, which is `1.0` if it's not specified.
+    pub fn quality(&self) -> f32 {
+        self.quality
+    }
+
+    /// Checks if the media range matches the specified media type e.g. `text/*` matches `text/html`.
+    pub fn matches(&self, media_type: &str) -> bool {
+        let media_type = essence(media_type);
+        match self.media_type.strip_suffix('*') {
+            Some("*/") => true,
+            Some(prefix) => media_type.starts_with(prefix),
+            None => self.media_type == media_type,
+        }
+    }
+
+    fn specificity(&self) -> u8 {
+        if self.media_type == "*/*" {
+            0
+        } else if self.media_type.ends_with("/*") {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+// The lowercased media type without the parameters e.g. `text/html` for `text/html; charset=utf-8`.
+fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn accept(val: &'static str) -> Accept {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(val));
+        Accept::parse(&headers)
+    }
+
+    #[test]
+    fn test_parse_accept() {
+        let accept = accept("text/*;q=0.5, application/json;q=0.9, */*;q=0.1, text/HTML, bad, text/plain;q=x");
+        let ranked = accept.iter().map(|range| range.media_type()).collect::<Vec<_>>();
+        assert_eq!(ranked, vec!["text/html", "application/json", "text/*", "*/*"]);
+        assert_eq!(accept.quality("text/html; charset=utf-8"), 1.0);
+        assert_eq!(accept.quality("text/csv"), 0.5);
+        assert_eq!(accept.quality("image/png"), 0.1);
+
+        assert_eq!(Accept::parse(&HeaderMap::new()).quality("image/png"), 1.0);
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let available = ["application/json", "text/html", "text/plain"];
+        assert_eq!(accept("text/html, */*;q=0.8").negotiate(&available), Some("text/html"));
+        assert_eq!(accept("*/*").negotiate(&available), Some("application/json"));
+        assert_eq!(
+            accept("text/*, text/html;q=0").negotiate(&available),
+            Some("text/plain")
+        );
+        assert_eq!(accept("image/png").negotiate(&available), None);
+        assert_eq!(
+            Accept::parse(&HeaderMap::new()).negotiate(&available),
+            Some("application/json")
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::Error;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::{self, Debug, Formatter};
+
+// The minimum size of a key, it's the size of the HMAC-SHA256 output.
+const MIN_KEY_SIZE: usize = 32;
+
+// The size of the AES-GCM nonce which is prepended to an encrypted value.
+const NONCE_SIZE: usize = 12;
+
+/// The secret keys to sign the cookie values with HMAC-SHA256, so the client can read but can't tamper with them e.g. a
+/// session identifier, or to encrypt them with AES-256-GCM, so the client can neither read nor tamper with them e.g. a flash
+/// message.
+///
+/// The first key signs and encrypts the new cookies, the previous keys added by the [`previous`](#method.previous) method are
+/// still accepted while verifying and decrypting, so a key can be rotated without invalidating the cookies of the old one.
+/// The signature and the encryption cover the cookie name too, so a value can't be moved into another cookie.
+///
+/// **Note:** The signed values are not encrypted, they must not carry any secret. Use the
+/// [`SetCookie::encrypted`](./struct.SetCookie.html#method.encrypted) method for the secret values.
+///
+/// This type is available only if the `cookies` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{CookieKeys, Router, SetCookie};
+/// use routerify::prelude::*;
+/// use hyper::{Response, Body, StatusCode};
+///
+/// # fn run() -> routerify::Result<Router<Body, routerify::Error>> {
+/// let keys = CookieKeys::new("a secret key of at least 32 bytes!!")?.previous("the previous key which is rotated out")?;
+///
+/// let router = Router::builder()
+///     .data(keys)
+///     .post("/login", |req| async move {
+///         let keys = req.data::<CookieKeys>().unwrap();
+///         let mut res = Response::new(Body::from("Logged in"));
+///         res.set_cookie(&SetCookie::signed("sid", "abc123", keys).http_only(true))?;
+///         res.set_cookie(&SetCookie::encrypted("flash", "Welcome back!", keys))?;
+///         Ok(res)
+///     })
+///     .get("/me", |req| async move {
+///         let keys = req.data::<CookieKeys>().unwrap();
+///         let flash = req.cookies().get_encrypted("flash", keys).unwrap_or_default();
+///         match req.cookies().get_signed("sid", keys) {
+///             Some(sid) => Ok(Response::new(Body::from(format!("{} Session: {}", flash, sid)))),
+///             None => Ok(Response::builder().status(StatusCode::UNAUTHORIZED).body(Body::empty()).unwrap()),
+///         }
+///     })
+///     .build()
+///     .unwrap();
+/// # Ok(router)
+/// # }
+/// # run().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct CookieKeys {
+    keys: Vec<Key>,
+}
+
+#[derive(Clone)]
+struct Key {
+    signing: Vec<u8>,
+    // The encryption key is derived from the signing key, so a key is never used by both algorithms.
+    encryption: Aes256Gcm,
+}
+
+impl CookieKeys {
+    /// Creates the keys with the key to sign the cookies, it must be at least 32 bytes long and random enough, otherwise an
+    /// [`Error::InvalidCookie`](./enum.Error.html#variant.InvalidCookie) error is returned.
+    pub fn new<K: AsRef<[u8]>>(key: K) -> crate::Result<CookieKeys> {
+        let keys = CookieKeys { keys: Vec::new() };
+        keys.previous(key)
+    }
+
+    /// Adds a previous key, whose signatures and encrypted values are still accepted. The keys are tried in the order they are
+    /// added.
+    pub fn previous<K: AsRef<[u8]>>(mut self, key: K) -> crate::Result<CookieKeys> {
+        let key = key.as_ref();
+        if key.len() < MIN_KEY_SIZE {
+            return Err(Error::InvalidCookie(format!(
+                "The cookie key must be at least {} bytes long",
+                MIN_KEY_SIZE
+            )));
+        }
+
+        let encryption_key = hmac_sha256(key).chain_update(b"routerify cookie encryption").finalize();
+        self.keys.push(Key {
+            signing: key.to_vec(),
+            encryption: Aes256Gcm::new(&encryption_key.into_bytes()),
+        });
+        Ok(self)
+    }
+
+    /// Signs the value of the specified cookie with the first key, the signature is appended to the value after a `.`.
+    pub fn sign(&self, name: &str, value: &str) -> String {
+        let signature = hmac_sha256(&self.keys[0].signing)
+            .chain_update(signed_message(name, value))
+            .finalize();
+        format!("{}.{}", value, to_hex(&signature.into_bytes()))
+    }
+
+    /// Verifies the signed value of the specified cookie with any of the keys and returns the original value, it's `None` if
+    /// the value is not signed or the signature doesn't match.
+    pub fn verify<'a>(&self, name: &str, signed_value: &'a str) -> Option<&'a str> {
+        let (value, signature) = signed_value.rsplit_once('.')?;
+        let signature = from_hex(signature)?;
+        let message = signed_message(name, value);
+
+        self.keys
+            .iter()
+            .any(|key| {
+                hmac_sha256(&key.signing)
+                    .chain_update(&message)
+                    .verify_slice(&signature)
+                    .is_ok()
+            })
+            .then_some(value)
+    }
+
+    /// Encrypts the value of the specified cookie with the first key, the result is the hex encoded random nonce followed by
+    /// the ciphertext.
+    pub fn encrypt(&self, name: &str, value: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: value.as_bytes(),
+            aad: name.as_bytes(),
+        };
+        let ciphertext = self.keys[0]
+            .encryption
+            .encrypt(&nonce, payload)
+            .expect("Couldn't encrypt the cookie value");
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend_from_slice(&ciphertext);
+        to_hex(&encrypted)
+    }
+
+    /// Decrypts the encrypted value of the specified cookie with any of the keys and returns the original value, it's `None`
+    /// if the value is not encrypted by any of the keys or the client tampered with it.
+    pub fn decrypt(&self, name: &str, encrypted_value: &str) -> Option<String> {
+        let encrypted = from_hex(encrypted_value)?;
+        if encrypted.len() < NONCE_SIZE {
+            return None;
+        }
+
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_SIZE);
+        let nonce = Nonce::from_slice(nonce);
+
+        self.keys.iter().find_map(|key| {
+            let payload = Payload {
+                msg: ciphertext,
+                aad: name.as_bytes(),
+            };
+            let value = key.encryption.decrypt(nonce, payload).ok()?;
+            String::from_utf8(value).ok()
+        })
+    }
+}
+
+impl Debug for CookieKeys {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "CookieKeys {{ keys: {} }}", self.keys.len())
+    }
+}
+
+fn hmac_sha256(key: &[u8]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts the keys of any size")
+}
+
+fn signed_message(name: &str, value: &str) -> String {
+    format!("{}={}", name, value)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16).ok())
+        .collect()
+}
+
+// Generates a token of 32 random bytes from the operating system's random number generator e.g. a session id.
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("Couldn't get random bytes from the operating system");
+    to_hex(&bytes)
+}
+
+// Compares the bytes without returning early, so the time doesn't tell how much of a forged token is correct.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let old_keys = CookieKeys::new([1; 32]).unwrap();
+        let keys = CookieKeys::new([2; 32]).unwrap().previous([1; 32]).unwrap();
+
+        let signed = keys.sign("sid", "abc.123");
+        assert_eq!(keys.verify("sid", &signed), Some("abc.123"));
+        assert_eq!(old_keys.verify("sid", &signed), None);
+        assert_eq!(keys.verify("other", &signed), None);
+        assert_eq!(keys.verify("sid", &signed.replace("abc", "abd")), None);
+        assert_eq!(keys.verify("sid", "abc.123"), None);
+
+        // The cookies signed by a rotated out key are still accepted.
+        assert_eq!(keys.verify("sid", &old_keys.sign("sid", "abc")), Some("abc"));
+
+        assert!(CookieKeys::new("short").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let old_keys = CookieKeys::new([1; 32]).unwrap();
+        let keys = CookieKeys::new([2; 32]).unwrap().previous([1; 32]).unwrap();
+
+        let encrypted = keys.encrypt("flash", "Saved!");
+        assert!(!encrypted.contains("Saved"));
+        assert_ne!(encrypted, keys.encrypt("flash", "Saved!"));
+        assert_eq!(keys.decrypt("flash", &encrypted).as_deref(), Some("Saved!"));
+        assert_eq!(old_keys.decrypt("flash", &encrypted), None);
+        assert_eq!(keys.decrypt("other", &encrypted), None);
+
+        let mut tampered = encrypted.into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'0' { b'1' } else { b'0' };
+        assert_eq!(keys.decrypt("flash", std::str::from_utf8(&tampered).unwrap()), None);
+        assert_eq!(keys.decrypt("flash", "00"), None);
+
+        // The cookies encrypted by a rotated out key are still accepted.
+        let encrypted = old_keys.encrypt("flash", "Saved!");
+        assert_eq!(keys.decrypt("flash", &encrypted).as_deref(), Some("Saved!"));
+    }
+
+    #[test]
+    fn test_random_token() {
+        let token = random_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, random_token());
+    }
+}
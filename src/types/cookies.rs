@@ -0,0 +1,188 @@
+use crate::types::cookie_keys::CookieKeys;
+use hyper::header::{self, HeaderMap};
+
+/// Represents the cookies sent by the client in the `Cookie` request headers, in their original order.
+///
+/// The malformed pairs are skipped and the surrounding double quotes of a value are removed. If a cookie name appears more
+/// than once, the [`get`](#method.get) method returns the first one. This type is available only if the `cookies` feature is
+/// enabled.
+///
+/// **Note:** This type shouldn't be created directly. It's parsed from the `req` object on the first access by the
+/// `req.cookies()` or `req.cookie("name")` methods.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cookies(Vec<(String, String)>);
+
+impl Cookies {
+    pub(crate) fn parse(headers: &HeaderMap) -> Cookies {
+        let pairs = headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(|val| val.split(';'))
+            .filter_map(parse_pair)
+            .collect();
+
+        Cookies(pairs)
+    }
+
+    /// Returns the value of the specified cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/", |req| async move {
+    ///         let theme = req.cookies().get("theme").unwrap_or("light");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Theme: {}", theme))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the value of the specified cookie signed by the [`SetCookie::signed`](./struct.SetCookie.html#method.signed)
+    /// method, if its signature is verified by any of the keys. The signature is removed from the returned value.
+    ///
+    /// If the cookie name appears more than once, the first verified one is returned.
+    pub fn get_signed<'a>(&'a self, name: &str, keys: &CookieKeys) -> Option<&'a str> {
+        self.0
+            .iter()
+            .filter(|(n, _)| n == name)
+            .find_map(|(_, v)| keys.verify(name, v))
+    }
+
+    /// Returns the decrypted value of the specified cookie encrypted by the
+    /// [`SetCookie::encrypted`](./struct.SetCookie.html#method.encrypted) method, if it's decrypted by any of the keys.
+    ///
+    /// If the cookie name appears more than once, the first decrypted one is returned.
+    pub fn get_encrypted(&self, name: &str, keys: &CookieKeys) -> Option<String> {
+        self.0
+            .iter()
+            .filter(|(n, _)| n == name)
+            .find_map(|(_, v)| keys.decrypt(name, v))
+    }
+
+    /// Checks if a cookie exists.
+    pub fn has(&self, name: &str) -> bool {
+        self.0.iter().any(|(n, _)| n == name)
+    }
+
+    /// Returns the number of the cookies.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if there are no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the cookies
+    /// as `(name: &str, value: &str)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+}
+
+fn parse_pair(pair: &str) -> Option<(String, String)> {
+    let mut split = pair.splitn(2, '=');
+    let name = split.next()?.trim();
+    let value = split.next()?.trim();
+
+    if !is_valid_name(name) {
+        return None;
+    }
+
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"')?,
+        None => value,
+    };
+
+    if !value.bytes().all(is_cookie_octet) {
+        return None;
+    }
+
+    Some((name.to_owned(), value.to_owned()))
+}
+
+/// A cookie name is an HTTP token.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+/// The characters allowed in a cookie value by RFC 6265.
+pub(crate) fn is_cookie_octet(b: u8) -> bool {
+    b.is_ascii_graphic() && b != b'"' && b != b',' && b != b';' && b != b'\\'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_cookies_parse() {
+        let mut headers = HeaderMap::new();
+        headers.append(header::COOKIE, HeaderValue::from_static("sid=abc123; theme=\"dark\""));
+        headers.append(
+            header::COOKIE,
+            HeaderValue::from_static("broken; =empty; sid=later;lang=en ; bad=\"x"),
+        );
+
+        let cookies = Cookies::parse(&headers);
+        assert_eq!(cookies.get("sid"), Some("abc123"));
+        assert_eq!(cookies.get("theme"), Some("dark"));
+        assert_eq!(cookies.get("lang"), Some("en"));
+        assert_eq!(cookies.get("broken"), None);
+        assert_eq!(cookies.get("bad"), None);
+        assert_eq!(
+            cookies.iter().collect::<Vec<_>>(),
+            vec![("sid", "abc123"), ("theme", "dark"), ("sid", "later"), ("lang", "en")]
+        );
+
+        assert!(Cookies::parse(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_signed_cookies() {
+        let keys = CookieKeys::new([7; 32]).unwrap();
+        let signed = keys.sign("sid", "abc");
+
+        let mut headers = HeaderMap::new();
+        let cookie = format!("sid=forged.00; sid={}; theme={}", signed, signed);
+        headers.append(header::COOKIE, HeaderValue::from_str(&cookie).unwrap());
+
+        let cookies = Cookies::parse(&headers);
+        assert_eq!(cookies.get_signed("sid", &keys), Some("abc"));
+        assert_eq!(cookies.get_signed("theme", &keys), None);
+    }
+
+    #[test]
+    fn test_encrypted_cookies() {
+        let keys = CookieKeys::new([7; 32]).unwrap();
+        let encrypted = keys.encrypt("flash", "Saved!");
+
+        let mut headers = HeaderMap::new();
+        let cookie = format!("flash=00; flash={}; theme={}", encrypted, encrypted);
+        headers.append(header::COOKIE, HeaderValue::from_str(&cookie).unwrap());
+
+        let cookies = Cookies::parse(&headers);
+        assert_eq!(cookies.get_encrypted("flash", &keys).as_deref(), Some("Saved!"));
+        assert_eq!(cookies.get_encrypted("theme", &keys), None);
+    }
+}
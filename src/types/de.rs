@@ -0,0 +1,302 @@
+use crate::types::{QueryParams, RouteParams};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::{self, Display, Formatter};
+
+/// Deserializes the route params into a type which is usually a struct with a field for each param.
+///
+/// The param values are strings, so the numbers, the booleans and the chars are parsed from them by their `FromStr`
+/// implementation, a missing `Option` field becomes `None` and a unit enum variant is matched by its name. The values of a
+/// repeated param are collected into a sequence like the query params.
+pub(crate) fn from_route_params<T: DeserializeOwned>(route_params: &RouteParams) -> crate::Result<T> {
+    let entries = route_params.entries().collect();
+
+    T::deserialize(ParamsDeserializer::new("route param", entries))
+        .map_err(|err| crate::Error::DeserializeRouteParams(err.0))
+}
+
+/// Deserializes the query params the same way as the route params, besides the values of a repeated key are collected into
+/// a sequence e.g. a `Vec` field. A scalar field takes the first value of a repeated key.
+pub(crate) fn from_query_params<T: DeserializeOwned>(query_params: &QueryParams) -> crate::Result<T> {
+    from_pairs("query param", query_params).map_err(|err| crate::Error::DeserializeQuery(err.0))
+}
+
+/// Deserializes the pairs of a form body the same way as the query params.
+pub(crate) fn from_form_pairs<T: DeserializeOwned>(pairs: &QueryParams) -> crate::Result<T> {
+    from_pairs("form field", pairs).map_err(|err| crate::Error::DeserializeForm(err.0))
+}
+
+fn from_pairs<T: DeserializeOwned>(kind: &'static str, query_params: &QueryParams) -> Result<T, DeError> {
+    let mut entries: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    for (key, value) in query_params.iter() {
+        match entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, values)) => values.push(value),
+            None => entries.push((key, vec![value])),
+        }
+    }
+
+    T::deserialize(ParamsDeserializer::new(kind, entries))
+}
+
+#[derive(Debug)]
+struct DeError(String);
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+struct ParamsDeserializer<'a> {
+    kind: &'static str,
+    entries: std::vec::IntoIter<(&'a str, Vec<&'a str>)>,
+    values: Option<(&'a str, Vec<&'a str>)>,
+}
+
+impl<'a> ParamsDeserializer<'a> {
+    fn new(kind: &'static str, entries: Vec<(&'a str, Vec<&'a str>)>) -> ParamsDeserializer<'a> {
+        ParamsDeserializer {
+            kind,
+            entries: entries.into_iter(),
+            values: None,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ParamsDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for ParamsDeserializer<'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((name, values)) => {
+                self.values = Some((name, values));
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (name, values) = self
+            .values
+            .take()
+            .expect("The `next_value_seed` method is called before `next_key_seed`");
+
+        seed.deserialize(ValuesDeserializer { values })
+            .map_err(|err| DeError(format!("invalid value for the {} `{}`: {}", self.kind, name, err)))
+    }
+}
+
+// Deserializes the values of a key, there is always at least one value.
+struct ValuesDeserializer<'a> {
+    values: Vec<&'a str>,
+}
+
+impl<'a> ValuesDeserializer<'a> {
+    fn first(&self) -> &'a str {
+        self.values[0]
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.first().parse().map_err(de::Error::custom)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValuesDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.first())
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let values = self
+            .values
+            .into_iter()
+            .map(|value| ValuesDeserializer { values: vec![value] });
+        visitor.visit_seq(de::value::SeqDeserializer::new(values))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.first().into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for ValuesDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    fn route_params(params: &[(&str, &str)]) -> RouteParams {
+        let mut route_params = RouteParams::new();
+        for (name, value) in params {
+            route_params.append(*name, *value);
+        }
+        route_params
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum State {
+        Open,
+        Closed,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct IssuePath {
+        owner: String,
+        name: String,
+        number: u32,
+        state: Option<State>,
+        pinned: Option<bool>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Pagination {
+        page: u32,
+        per_page: Option<u32>,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_from_route_params() {
+        let params = route_params(&[
+            ("owner", "rust-lang"),
+            ("name", "rust"),
+            ("number", "42"),
+            ("state", "open"),
+        ]);
+        let path: IssuePath = from_route_params(&params).unwrap();
+        assert_eq!(
+            path,
+            IssuePath {
+                owner: "rust-lang".to_owned(),
+                name: "rust".to_owned(),
+                number: 42,
+                state: Some(State::Open),
+                pinned: None,
+            }
+        );
+
+        let params = route_params(&[("owner", "rust-lang"), ("number", "42")]);
+        let err = from_route_params::<IssuePath>(&params).unwrap_err();
+        assert!(err.to_string().contains("missing field `name`"), "{}", err);
+
+        let params = route_params(&[("owner", "rust-lang"), ("name", "rust"), ("number", "forty-two")]);
+        let err = from_route_params::<IssuePath>(&params).unwrap_err();
+        assert!(err.to_string().contains("route param `number`"), "{}", err);
+
+        let params = route_params(&[("owner", "o"), ("name", "n"), ("number", "1"), ("pinned", "yes")]);
+        let err = from_route_params::<IssuePath>(&params).unwrap_err();
+        assert!(err.to_string().contains("route param `pinned`"), "{}", err);
+
+        let params = route_params(&[("owner", "o"), ("name", "n"), ("number", "1"), ("state", "merged")]);
+        let err = from_route_params::<IssuePath>(&params).unwrap_err();
+        assert!(err.to_string().contains("route param `state`"), "{}", err);
+
+        let params = route_params(&[("page", "1"), ("tags", "rust"), ("tags", "web")]);
+        let pagination: Pagination = from_route_params(&params).unwrap();
+        assert_eq!(pagination.tags, vec!["rust".to_owned(), "web".to_owned()]);
+    }
+
+    #[test]
+    fn test_from_query_params() {
+        let params = QueryParams::parse(Some("page=2&tags=rust&tags=web%20dev&page=3"));
+        let pagination: Pagination = from_query_params(&params).unwrap();
+        assert_eq!(
+            pagination,
+            Pagination {
+                page: 2,
+                per_page: None,
+                tags: vec!["rust".to_owned(), "web dev".to_owned()],
+            }
+        );
+
+        let params = QueryParams::parse(Some("page=1&per_page=50"));
+        let pagination: Pagination = from_query_params(&params).unwrap();
+        assert_eq!(pagination.per_page, Some(50));
+        assert!(pagination.tags.is_empty());
+
+        let err = from_query_params::<Pagination>(&QueryParams::parse(None)).unwrap_err();
+        assert!(err.to_string().contains("missing field `page`"), "{}", err);
+
+        let params = QueryParams::parse(Some("page=1&per_page=all"));
+        let err = from_query_params::<Pagination>(&params).unwrap_err();
+        assert!(err.to_string().contains("query param `per_page`"), "{}", err);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::Error;
+use hyper::header::{self, HeaderMap};
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+
+/// Represents a range of IP addresses in the CIDR notation e.g. `10.0.0.0/8` or `2001:db8::/32`.
+///
+/// A single address without a prefix length e.g. `127.0.0.1` is a range of just that address. The IPv4-mapped IPv6 addresses
+/// e.g. `::ffff:10.0.0.1` are matched as the IPv4 addresses they map.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::IpRange;
+///
+/// # fn run() -> routerify::Result<()> {
+/// let range: IpRange = "192.168.0.0/16".parse()?;
+/// assert!(range.contains(&"192.168.1.10".parse().unwrap()));
+/// assert!(!range.contains(&"10.0.0.1".parse().unwrap()));
+/// # Ok(())
+/// # }
+/// # run().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpRange {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    /// Creates a range from the network address and the prefix length, the host bits of the address are cleared.
+    ///
+    /// It returns an error if the prefix length is greater than `32` for an IPv4 address or `128` for an IPv6 address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> crate::Result<IpRange> {
+        let addr = canonical_ip(addr);
+
+        let addr = match addr {
+            IpAddr::V4(ip) if prefix_len <= 32 => IpAddr::V4(Ipv4Addr::from(u32::from(ip) & v4_mask(prefix_len))),
+            IpAddr::V6(ip) if prefix_len <= 128 => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & v6_mask(prefix_len))),
+            _ => return Err(Error::InvalidIpRange(format!("{}/{}", addr, prefix_len))),
+        };
+
+        Ok(IpRange { addr, prefix_len })
+    }
+
+    /// Returns the network address of the range.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the prefix length of the range.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Checks if the address is in the range.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, canonical_ip(*ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => u32::from(ip) & v4_mask(self.prefix_len) == u32::from(net),
+            (IpAddr::V6(net), IpAddr::V6(ip)) => u128::from(ip) & v6_mask(self.prefix_len) == u128::from(net),
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<IpRange> {
+        let invalid = || Error::InvalidIpRange(s.to_owned());
+
+        let (addr, prefix_len) = match s.trim().split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len.parse::<u8>().map_err(|_| invalid())?)),
+            None => (s.trim(), None),
+        };
+        let addr = canonical_ip(addr.parse::<IpAddr>().map_err(|_| invalid())?);
+
+        let prefix_len = prefix_len.unwrap_or(if addr.is_ipv4() { 32 } else { 128 });
+        IpRange::new(addr, prefix_len).map_err(|_| invalid())
+    }
+}
+
+impl From<IpAddr> for IpRange {
+    fn from(addr: IpAddr) -> IpRange {
+        let addr = canonical_ip(addr);
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        IpRange { addr, prefix_len }
+    }
+}
+
+impl Display for IpRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+fn v4_mask(prefix_len: u8) -> u32 {
+    u32::MAX.checked_shl(32 - u32::from(prefix_len)).unwrap_or(0)
+}
+
+fn v6_mask(prefix_len: u8) -> u128 {
+    u128::MAX.checked_shl(128 - u32::from(prefix_len)).unwrap_or(0)
+}
+
+// Converts an IPv4-mapped IPv6 address e.g. `::ffff:10.0.0.1` to the IPv4 address it maps.
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    }
+}
+
+/// Resolves the address of the client behind the trusted proxies.
+///
+/// The forwarded headers are only considered if the peer is a trusted proxy. The hops in the `Forwarded` header, or in the
+/// `X-Forwarded-For` header if it's absent, are walked from the nearest one and the first address which isn't a trusted
+/// proxy is the client. The `X-Real-IP` header is used if neither of them is present.
+pub(crate) fn resolve_client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpRange]) -> IpAddr {
+    let peer = canonical_ip(peer);
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|range| range.contains(ip));
+
+    if !is_trusted(&peer) {
+        return peer;
+    }
+
+    let hops = if headers.contains_key(header::FORWARDED) {
+        header_values(headers, &header::FORWARDED)
+            .flat_map(|val| val.split(','))
+            .map(|elem| elem.split(';').find_map(forwarded_for).flatten())
+            .collect::<Vec<_>>()
+    } else if headers.contains_key("x-forwarded-for") {
+        header_values(headers, &header::HeaderName::from_static("x-forwarded-for"))
+            .flat_map(|val| val.split(','))
+            .map(parse_ip)
+            .collect::<Vec<_>>()
+    } else {
+        return header_values(headers, &header::HeaderName::from_static("x-real-ip"))
+            .next()
+            .and_then(parse_ip)
+            .unwrap_or(peer);
+    };
+
+    let mut client = peer;
+    for hop in hops.into_iter().rev() {
+        // An obfuscated or a malformed hop can't be trusted, so the last known address is used.
+        let hop = match hop {
+            Some(hop) => canonical_ip(hop),
+            None => break,
+        };
+
+        client = hop;
+        if !is_trusted(&hop) {
+            break;
+        }
+    }
+
+    client
+}
+
+fn header_values<'a>(headers: &'a HeaderMap, name: &header::HeaderName) -> impl Iterator<Item = &'a str> {
+    headers.get_all(name).iter().filter_map(|val| val.to_str().ok())
+}
+
+// Parses the address of a `for` parameter of a `Forwarded` header element.
+fn forwarded_for(pair: &str) -> Option<Option<IpAddr>> {
+    let (name, val) = pair.trim().split_once('=')?;
+    if !name.trim().eq_ignore_ascii_case("for") {
+        return None;
+    }
+
+    Some(parse_ip(val.trim().trim_matches('"')))
+}
+
+// Parses an address which may have a port e.g. `192.0.2.1:8080` or `[2001:db8::1]:8080`.
+fn parse_ip(val: &str) -> Option<IpAddr> {
+    let val = val.trim();
+
+    val.parse::<IpAddr>()
+        .ok()
+        .or_else(|| val.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| val.strip_prefix('[')?.strip_suffix(']')?.parse::<IpAddr>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ip_range_contains() {
+        let range: IpRange = "10.1.2.3/8".parse().unwrap();
+        assert_eq!(range.to_string(), "10.0.0.0/8");
+        assert!(range.contains(&ip("10.255.0.1")));
+        assert!(range.contains(&ip("::ffff:10.0.0.1")));
+        assert!(!range.contains(&ip("11.0.0.1")));
+
+        let range: IpRange = "2001:db8::/32".parse().unwrap();
+        assert!(range.contains(&ip("2001:db8:1::1")));
+        assert!(!range.contains(&ip("2001:db9::1")));
+        assert!(!range.contains(&ip("10.0.0.1")));
+
+        assert!("0.0.0.0/0".parse::<IpRange>().unwrap().contains(&ip("203.0.113.9")));
+        assert!("127.0.0.1".parse::<IpRange>().unwrap().contains(&ip("127.0.0.1")));
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("10.0.0/8".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_client_ip() {
+        let trusted = vec!["10.0.0.0/8".parse::<IpRange>().unwrap()];
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("198.51.100.1, 203.0.113.7, 10.0.0.2"),
+        );
+
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("203.0.113.7"));
+        assert_eq!(resolve_client_ip(ip("192.0.2.1"), &headers, &trusted), ip("192.0.2.1"));
+
+        headers.insert(
+            header::FORWARDED,
+            HeaderValue::from_static(r#"for=198.51.100.1, for="[2001:db8::1]:4711";proto=https, for=10.0.0.2"#),
+        );
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("2001:db8::1"));
+
+        headers.insert(header::FORWARDED, HeaderValue::from_static("for=_hidden, for=10.0.0.2"));
+        assert_eq!(resolve_client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.2"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("198.51.100.1"));
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.1"), &headers, &trusted),
+            ip("198.51.100.1")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.1"), &HeaderMap::new(), &trusted),
+            ip("10.0.0.1")
+        );
+    }
+}
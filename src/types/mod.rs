@@ -1,7 +1,45 @@
+pub use accept::{Accept, MediaRange};
+#[cfg(feature = "cookies")]
+pub use cookie_keys::CookieKeys;
+#[cfg(feature = "cookies")]
+pub(crate) use cookie_keys::{constant_time_eq, random_token};
+#[cfg(feature = "cookies")]
+pub use cookies::Cookies;
+#[cfg(feature = "serde")]
+pub(crate) use de::{from_form_pairs, from_query_params, from_route_params};
+pub(crate) use ip_range::resolve_client_ip;
+pub use ip_range::IpRange;
+pub use peer_addr::PeerAddr;
+pub use percent_decode_mode::PercentDecodeMode;
+pub use query_params::QueryParams;
+pub(crate) use request_context::RequestContext;
 pub use request_info::RequestInfo;
 pub(crate) use request_meta::RequestMeta;
+pub use request_metrics::RequestMetrics;
+pub(crate) use request_metrics::{ErrHandlerFailure, MatchedRoutePath};
 pub use route_params::RouteParams;
+#[cfg(feature = "cookies")]
+pub use set_cookie::{SameSite, SetCookie};
+pub use slash_normalization::SlashNormalization;
+pub use trailing_slash::TrailingSlash;
 
+mod accept;
+#[cfg(feature = "cookies")]
+mod cookie_keys;
+#[cfg(feature = "cookies")]
+mod cookies;
+#[cfg(feature = "serde")]
+mod de;
+mod ip_range;
+mod peer_addr;
+mod percent_decode_mode;
+mod query_params;
+mod request_context;
 mod request_info;
 mod request_meta;
+mod request_metrics;
 mod route_params;
+#[cfg(feature = "cookies")]
+mod set_cookie;
+mod slash_normalization;
+mod trailing_slash;
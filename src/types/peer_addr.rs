@@ -0,0 +1,53 @@
+use std::fmt::{self, Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Represents the peer of a connection, which could be a TCP client or a Unix domain socket client.
+///
+/// It can be accessed from a request via the [`RequestExt::peer_addr`](./ext/trait.RequestExt.html#tymethod.peer_addr) method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PeerAddr {
+    /// The socket address of a TCP peer.
+    Tcp(SocketAddr),
+
+    /// A Unix domain socket peer.
+    Unix {
+        /// The path the peer is bound to, the clients are usually not bound to any path.
+        path: Option<PathBuf>,
+        /// The user id of the peer process, if available.
+        uid: Option<u32>,
+        /// The group id of the peer process, if available.
+        gid: Option<u32>,
+    },
+
+    /// The peer is unknown e.g. the router is not served over a connection.
+    Unknown,
+}
+
+impl PeerAddr {
+    /// Returns the socket address if it's a TCP peer.
+    pub fn as_socket_addr(&self) -> Option<&SocketAddr> {
+        match self {
+            PeerAddr::Tcp(addr) => Some(addr),
+            _ => None,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> PeerAddr {
+        PeerAddr::Tcp(addr)
+    }
+}
+
+impl Display for PeerAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerAddr::Tcp(addr) => write!(f, "{}", addr),
+            PeerAddr::Unix { path: Some(path), .. } => write!(f, "unix:{}", path.display()),
+            PeerAddr::Unix { path: None, .. } => write!(f, "unix:(unnamed)"),
+            PeerAddr::Unknown => write!(f, "unknown"),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/// Specifies how the request path is percent-decoded before matching it against the routes.
+///
+/// It can be set by the [`RouterBuilder::percent_decode`](./struct.RouterBuilder.html#method.percent_decode) method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum PercentDecodeMode {
+    /// Decodes every percent-encoded byte, so `/files/a%2Fb` is matched as `/files/a/b`. This is the default mode.
+    #[default]
+    Full,
+
+    /// Decodes every percent-encoded byte except the encoded slash `%2F` and the encoded percent sign `%25`, so the encoded slashes
+    /// don't change the segment boundaries, e.g. `/files/a%2Fb` matches the `/files/:name` route. The param values are still
+    /// fully decoded, so the `name` param is `a/b` and its raw value is `a%2Fb`.
+    KeepEncodedSlashes,
+
+    /// Matches the path as it's received without decoding anything.
+    Off,
+}
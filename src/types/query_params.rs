@@ -0,0 +1,114 @@
+use percent_encoding::percent_decode_str;
+
+/// Represents the decoded pairs of the request query string in their original order, a key can appear more than once.
+///
+/// The `+` characters are decoded as spaces and the invalid UTF-8 sequences are replaced with `U+FFFD`.
+///
+/// **Note:** This type shouldn't be created directly. It's parsed from the `req` object on the first access by the
+/// `req.query_params()` or `req.query("key")` methods.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryParams(Vec<(String, String)>);
+
+impl QueryParams {
+    pub(crate) fn parse(query: Option<&str>) -> QueryParams {
+        let query = match query {
+            Some(query) => query,
+            None => return QueryParams::default(),
+        };
+
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut split = pair.splitn(2, '=');
+                let key = decode_component(split.next().unwrap_or(""));
+                let val = decode_component(split.next().unwrap_or(""));
+                (key, val)
+            })
+            .collect();
+
+        QueryParams(pairs)
+    }
+
+    pub(crate) fn into_pairs(self) -> Vec<(String, String)> {
+        self.0
+    }
+
+    /// Returns the first value of the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/search", |req| async move {
+    ///         let term = req.query_params().get("q").unwrap_or("");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Searching for: {}", term))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over all the values of the specified key.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Checks if a key exists.
+    pub fn has(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+
+    /// Returns the number of the pairs.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the pairs
+    /// as `(key: &str, value: &str)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+fn decode_component(val: &str) -> String {
+    let val = val.replace('+', " ");
+    percent_decode_str(&val).decode_utf8_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_params_parse() {
+        let params = QueryParams::parse(Some("page=2&tags=rust&tags=web+dev&q=a%26b&flag&&empty="));
+        assert_eq!(params.get("page"), Some("2"));
+        assert_eq!(params.get_all("tags").collect::<Vec<_>>(), vec!["rust", "web dev"]);
+        assert_eq!(params.get("q"), Some("a&b"));
+        assert_eq!(params.get("flag"), Some(""));
+        assert_eq!(params.get("empty"), Some(""));
+        assert_eq!(params.get("missing"), None);
+        assert_eq!(params.len(), 6);
+
+        assert!(QueryParams::parse(None).is_empty());
+        assert!(QueryParams::parse(Some("")).is_empty());
+    }
+}
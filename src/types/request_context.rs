@@ -0,0 +1,70 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+type ContextMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// A type map which holds the request-local values set by the middlewares and the route handlers.
+///
+/// It's stored in the request extensions under its own type, so the values don't collide with the extensions added by the
+/// user code. Every value is also written to a map shared with the [`RequestInfo`](./struct.RequestInfo.html) of the request,
+/// so the values set in a route handler are visible to the post middlewares and the error handler.
+#[derive(Clone, Default)]
+pub(crate) struct RequestContext {
+    values: ContextMap,
+    shared: Arc<Mutex<ContextMap>>,
+}
+
+impl RequestContext {
+    pub fn set<T: Send + Sync + 'static>(&mut self, value: T) {
+        let value: Arc<dyn Any + Send + Sync> = Arc::new(value);
+        self.lock_shared().insert(TypeId::of::<T>(), value.clone());
+        self.values.insert(TypeId::of::<T>(), value);
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    pub fn get_shared<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.lock_shared()
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    fn lock_shared(&self) -> std::sync::MutexGuard<'_, ContextMap> {
+        self.shared.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Debug for RequestContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{ len: {:?} }}", self.values.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_context() {
+        let mut context = RequestContext::default();
+        let info_context = context.clone();
+
+        context.set(10_u32);
+        context.set(String::from("alice"));
+        context.set(20_u32);
+
+        assert_eq!(context.get::<u32>(), Some(&20));
+        assert_eq!(context.get::<String>().map(String::as_str), Some("alice"));
+        assert_eq!(context.get::<u64>(), None);
+
+        assert_eq!(info_context.get_shared::<u32>().as_deref(), Some(&20));
+        assert_eq!(info_context.get_shared::<u64>(), None);
+    }
+}
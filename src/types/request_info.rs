@@ -1,5 +1,6 @@
 use crate::data_map::SharedDataMap;
-use hyper::{Body, HeaderMap, Method, Request, Uri, Version};
+use crate::types::{MatchedRoutePath, RequestContext};
+use hyper::{HeaderMap, Method, Request, Uri, Version};
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
 
@@ -10,7 +11,9 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct RequestInfo {
     pub(crate) req_info_inner: Arc<RequestInfoInner>,
-    pub(crate) shared_data_maps: Option<Box<Vec<SharedDataMap>>>,
+    pub(crate) shared_data_maps: Option<Vec<SharedDataMap>>,
+    pub(crate) context: RequestContext,
+    pub(crate) matched_route: MatchedRoutePath,
 }
 
 #[derive(Debug)]
@@ -22,7 +25,7 @@ pub(crate) struct RequestInfoInner {
 }
 
 impl RequestInfo {
-    pub(crate) fn new_from_req(req: &Request<Body>) -> Self {
+    pub(crate) fn new_from_req<ReqB>(req: &Request<ReqB>) -> Self {
         let inner = RequestInfoInner {
             headers: req.headers().clone(),
             method: req.method().clone(),
@@ -33,6 +36,8 @@ impl RequestInfo {
         RequestInfo {
             req_info_inner: Arc::new(inner),
             shared_data_maps: None,
+            context: RequestContext::default(),
+            matched_route: MatchedRoutePath::default(),
         }
     }
 
@@ -56,6 +61,14 @@ impl RequestInfo {
         self.req_info_inner.version
     }
 
+    /// Returns the path template of the matched route e.g. `/users/:id`, including the prefix of the scope it's mounted on.
+    ///
+    /// It's `None` until a route is matched, e.g. in the error handler for an error raised by a pre middleware, and for the
+    /// requests handled by the default 404 route.
+    pub fn matched_route(&self) -> Option<&str> {
+        self.matched_route.get()
+    }
+
     /// Access data which was shared by the [`RouterBuilder`](./struct.RouterBuilder.html) method
     /// [`data`](./struct.RouterBuilder.html#method.data).
     ///
@@ -69,7 +82,15 @@ impl RequestInfo {
             }
         }
 
-        return None;
+        None
+    }
+
+    /// Access the request-local value of type `T` which was set by the
+    /// [`set_context`](./ext/trait.RequestExt.html#tymethod.set_context) method, including the values set in the route handler.
+    ///
+    /// Please refer to the [Request Context](./index.html#request-context) for more info.
+    pub fn context<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.context.get_shared::<T>()
     }
 }
 
@@ -1,24 +1,77 @@
+use crate::types::accept::Accept;
+#[cfg(feature = "cookies")]
+use crate::types::cookies::Cookies;
+use crate::types::ip_range::IpRange;
+use crate::types::peer_addr::PeerAddr;
+use crate::types::query_params::QueryParams;
 use crate::types::route_params::RouteParams;
-use std::net::SocketAddr;
+use hyper::HeaderMap;
+use std::sync::{Arc, OnceLock};
 
 #[derive(Debug, Clone)]
 pub(crate) struct RequestMeta {
     route_params: Option<RouteParams>,
-    remote_addr: Option<SocketAddr>,
+    matched_route: Option<Arc<str>>,
+    // The path template of the scope the matched route is mounted on and the part of the request path matched by it.
+    mount_prefix: Option<(Arc<str>, String)>,
+    peer_addr: Option<PeerAddr>,
+    // The proxies whose forwarded headers are trusted, it's set along with the peer address.
+    trusted_proxies: Option<Arc<[IpRange]>>,
+    host_param: Option<String>,
+    host_params: Option<RouteParams>,
+    // The query string, the cookies and the accepted media types are parsed once on the first access.
+    query_params: OnceLock<QueryParams>,
+    #[cfg(feature = "cookies")]
+    cookies: OnceLock<Cookies>,
+    accept: OnceLock<Accept>,
 }
 
 impl RequestMeta {
-    pub fn with_route_params(route_params: RouteParams) -> RequestMeta {
+    pub fn with_route(route_params: RouteParams, matched_route: Option<Arc<str>>) -> RequestMeta {
         RequestMeta {
             route_params: Some(route_params),
-            remote_addr: None,
+            matched_route,
+            mount_prefix: None,
+            peer_addr: None,
+            trusted_proxies: None,
+            host_param: None,
+            host_params: None,
+            query_params: OnceLock::new(),
+            #[cfg(feature = "cookies")]
+            cookies: OnceLock::new(),
+            accept: OnceLock::new(),
         }
     }
 
-    pub fn with_remote_addr(remote_addr: SocketAddr) -> RequestMeta {
+    pub fn with_peer_addr(peer_addr: PeerAddr, trusted_proxies: Arc<[IpRange]>) -> RequestMeta {
         RequestMeta {
             route_params: None,
-            remote_addr: Some(remote_addr),
+            matched_route: None,
+            mount_prefix: None,
+            peer_addr: Some(peer_addr),
+            trusted_proxies: Some(trusted_proxies),
+            host_param: None,
+            host_params: None,
+            query_params: OnceLock::new(),
+            #[cfg(feature = "cookies")]
+            cookies: OnceLock::new(),
+            accept: OnceLock::new(),
+        }
+    }
+
+    pub fn with_host_param(host_param: String, host_params: RouteParams) -> RequestMeta {
+        RequestMeta {
+            route_params: None,
+            matched_route: None,
+            mount_prefix: None,
+            peer_addr: None,
+            trusted_proxies: None,
+            host_param: Some(host_param),
+            host_params: Some(host_params),
+            query_params: OnceLock::new(),
+            #[cfg(feature = "cookies")]
+            cookies: OnceLock::new(),
+            accept: OnceLock::new(),
         }
     }
 
@@ -26,13 +79,74 @@ impl RequestMeta {
         self.route_params.as_ref()
     }
 
-    pub fn remote_addr(&self) -> Option<&SocketAddr> {
-        self.remote_addr.as_ref()
+    pub fn matched_route(&self) -> Option<&str> {
+        self.matched_route.as_deref()
+    }
+
+    pub fn mount_prefix(&self) -> Option<&(Arc<str>, String)> {
+        self.mount_prefix.as_ref()
+    }
+
+    pub fn set_mount_prefix(&mut self, template: Arc<str>, matched: String) {
+        self.mount_prefix = Some((template, matched));
+    }
+
+    pub fn peer_addr(&self) -> Option<&PeerAddr> {
+        self.peer_addr.as_ref()
+    }
+
+    pub fn trusted_proxies(&self) -> &[IpRange] {
+        self.trusted_proxies.as_deref().unwrap_or_default()
+    }
+
+    pub fn host_param(&self) -> Option<&str> {
+        self.host_param.as_deref()
+    }
+
+    pub fn host_params(&self) -> Option<&RouteParams> {
+        self.host_params.as_ref()
+    }
+
+    pub fn query_params(&self, query: Option<&str>) -> &QueryParams {
+        self.query_params.get_or_init(|| QueryParams::parse(query))
+    }
+
+    #[cfg(feature = "cookies")]
+    pub fn cookies(&self, headers: &HeaderMap) -> &Cookies {
+        self.cookies.get_or_init(|| Cookies::parse(headers))
+    }
+
+    pub fn accept(&self, headers: &HeaderMap) -> &Accept {
+        self.accept.get_or_init(|| Accept::parse(headers))
+    }
+
+    pub fn reset_query_params(&mut self) {
+        self.query_params = OnceLock::new();
     }
 
     pub fn extend(&mut self, other_req_meta: RequestMeta) {
-        if let Some(other_ra) = other_req_meta.remote_addr {
-            self.remote_addr = Some(other_ra)
+        if let Some(other_pa) = other_req_meta.peer_addr {
+            self.peer_addr = Some(other_pa)
+        }
+
+        if let Some(other_tp) = other_req_meta.trusted_proxies {
+            self.trusted_proxies = Some(other_tp)
+        }
+
+        if let Some(other_hp) = other_req_meta.host_param {
+            self.host_param = Some(other_hp)
+        }
+
+        if let Some(other_hps) = other_req_meta.host_params {
+            self.host_params = Some(other_hps)
+        }
+
+        if let Some(other_mr) = other_req_meta.matched_route {
+            self.matched_route = Some(other_mr)
+        }
+
+        if let Some(other_mp) = other_req_meta.mount_prefix {
+            self.mount_prefix = Some(other_mp)
         }
 
         if let Some(other_pm) = other_req_meta.route_params {
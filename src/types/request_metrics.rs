@@ -0,0 +1,53 @@
+use hyper::{Method, StatusCode};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// The metrics of a completed request, which are passed to the callback registered by the
+/// [`on_request_complete`](./struct.RouterBuilder.html#method.on_request_complete) method.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestMetrics<'a> {
+    /// The path template of the matched route e.g. `/users/:id`, including the scope prefix of a scoped route. It's
+    /// [`UNMATCHED_ROUTE_PATH`](#associatedconstant.UNMATCHED_ROUTE_PATH) if the request isn't handled by any route added by the user.
+    pub route_path: &'a str,
+
+    /// The request method.
+    pub method: &'a Method,
+
+    /// The response status, after the post middlewares and the error handler are run.
+    pub status: StatusCode,
+
+    /// The time elapsed from receiving the request to generating the response. The time to send the response body isn't included.
+    pub latency: Duration,
+
+    /// The error returned by the error handler itself or its panic, if it failed and the fallback `500` response was sent
+    /// instead.
+    pub err_handler_error: Option<&'a (dyn std::error::Error + Send + Sync + 'static)>,
+}
+
+impl RequestMetrics<'_> {
+    /// The route path of the requests which are not handled by any route added by the user, e.g. the ones handled by the default
+    /// 404 route or the ones rejected before routing.
+    pub const UNMATCHED_ROUTE_PATH: &'static str = "<unmatched>";
+}
+
+/// The slot where the router records the path of the matched route, so it can be read after the request is consumed. It's added
+/// to the request extensions only when the metrics callback is registered or the request info is generated.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MatchedRoutePath(Arc<OnceLock<Arc<str>>>);
+
+impl MatchedRoutePath {
+    /// The first matched route wins, e.g. an around middleware can't record another route by re-running the request.
+    pub(crate) fn set(&self, path: Arc<str>) {
+        let _ = self.0.set(path);
+    }
+
+    pub(crate) fn get(&self) -> Option<&str> {
+        self.0.get().map(|path| &**path)
+    }
+}
+
+/// The failure of the error handler, it's added to the extensions of the fallback `500` response so the router can pass it
+/// to the metrics callback.
+#[derive(Debug)]
+pub(crate) struct ErrHandlerFailure(pub(crate) Box<dyn std::error::Error + Send + Sync + 'static>);
@@ -1,13 +1,29 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents a map of the route parameters using the name of the parameter specified in the path as their respective keys.
 ///
 /// Please refer to the [Route Parameters](./index.html#route-parameters) section for more info.
 ///
+/// A parameter name can be repeated in a path e.g. `/compare/:ver/:ver` or when a scope path and a route path of the
+/// mounted router use the same name, then every captured value is kept in the order of the path. The [`get`](#method.get)
+/// method returns the first one and the [`get_all`](#method.get_all) method returns them all.
+///
+/// The values are percent-decoded like the request path, the values as they're sent are available by the
+/// [`get_raw`](#method.get_raw) method.
+///
 /// **Note:** This type shouldn't be created directly. It will be populated into the `req` object of the route handler and
 /// can be accessed as `req.params()`.
+#[derive(Debug, Clone, Default)]
+pub struct RouteParams(pub(crate) HashMap<String, Vec<ParamValue>>);
+
 #[derive(Debug, Clone)]
-pub struct RouteParams(HashMap<String, String>);
+pub(crate) struct ParamValue {
+    value: String,
+    // It's only kept if it's different from the decoded value.
+    raw: Option<String>,
+}
 
 impl RouteParams {
     /// Creates an empty route parameters map.
@@ -20,12 +36,33 @@ impl RouteParams {
         RouteParams(HashMap::with_capacity(capacity))
     }
 
-    /// Sets a new parameter entry with the specified key and the value.
+    /// Sets a new parameter entry with the specified key and the value, it replaces all the existing values of the key.
     pub fn set<N: Into<String>, V: Into<String>>(&mut self, param_name: N, param_val: V) {
-        self.0.insert(param_name.into(), param_val.into());
+        let value = ParamValue {
+            value: param_val.into(),
+            raw: None,
+        };
+        self.0.insert(param_name.into(), vec![value]);
     }
 
-    /// Returns the route parameter value mapped with the specified key.
+    /// Appends a value to the specified key, the existing values of the key are kept.
+    pub fn append<N: Into<String>, V: Into<String>>(&mut self, param_name: N, param_val: V) {
+        self.append_with_raw(param_name, param_val, None);
+    }
+
+    pub(crate) fn append_with_raw<N: Into<String>, V: Into<String>>(
+        &mut self,
+        param_name: N,
+        param_val: V,
+        raw: Option<String>,
+    ) {
+        self.0.entry(param_name.into()).or_default().push(ParamValue {
+            value: param_val.into(),
+            raw,
+        });
+    }
+
+    /// Returns the route parameter value mapped with the specified key, it's the first value if the key is repeated in the path.
     ///
     /// # Examples
     ///
@@ -52,7 +89,123 @@ impl RouteParams {
     /// # run();
     /// ```
     pub fn get<N: Into<String>>(&self, param_name: N) -> Option<&String> {
-        self.0.get(&param_name.into())
+        self.0
+            .get(&param_name.into())
+            .and_then(|values| values.first())
+            .map(|val| &val.value)
+    }
+
+    /// Parses the route parameter value mapped with the specified key into a type which implements the `FromStr` trait e.g.
+    /// a number, it's the first value if the key is repeated in the path.
+    ///
+    /// It returns an [`Error::InvalidRouteParam`](./enum.Error.html#variant.InvalidRouteParam) error if the parameter is
+    /// missing or it couldn't be parsed, the default error handler responds to it with `400 Bad Request`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    ///
+    /// # fn run() -> Router<Body, routerify::Error> {
+    /// let router = Router::builder()
+    ///     .get("/users/:id", |req| async move {
+    ///         let id: u64 = req.params().get_as("id")?;
+    ///
+    ///         Ok(Response::new(Body::from(format!("User #{}", id))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_as<T, N>(&self, param_name: N) -> crate::Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        N: Into<String>,
+    {
+        let param_name = param_name.into();
+        let value = match self.get(param_name.as_str()) {
+            Some(value) => value,
+            None => {
+                return Err(crate::Error::InvalidRouteParam(
+                    param_name,
+                    "the param is missing".into(),
+                ))
+            }
+        };
+
+        value
+            .parse()
+            .map_err(|err: T::Err| crate::Error::InvalidRouteParam(param_name, err.to_string()))
+    }
+
+    /// Returns the route parameter value mapped with the specified key as it's sent in the request path i.e. before the
+    /// percent-decoding, it's the first value if the key is repeated in the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::{Router, PercentDecodeMode};
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .percent_decode(PercentDecodeMode::KeepEncodedSlashes)
+    ///     .get("/proxy/:url", |req| async move {
+    ///         // It prints `https://example.com/a b` and `https%3A%2F%2Fexample.com%2Fa%20b` for a request
+    ///         // to `/proxy/https%3A%2F%2Fexample.com%2Fa%20b`.
+    ///         println!("{}", req.params().get("url").unwrap());
+    ///         println!("{}", req.params().get_raw("url").unwrap());
+    ///
+    ///         Ok(Response::new(Body::empty()))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_raw<N: Into<String>>(&self, param_name: N) -> Option<&str> {
+        self.0
+            .get(&param_name.into())
+            .and_then(|values| values.first())
+            .map(|val| val.raw.as_deref().unwrap_or(&val.value))
+    }
+
+    /// Returns all the values of the specified key in the order of the path, it's empty if the key doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use routerify::Router;
+    /// use routerify::ext::RequestExt;
+    /// use hyper::{Response, Body};
+    /// # use std::convert::Infallible;
+    ///
+    /// # fn run() -> Router<Body, Infallible> {
+    /// let router = Router::builder()
+    ///     .get("/compare/:ver/:ver", |req| async move {
+    ///         let versions = req.params().get_all("ver");
+    ///
+    ///         Ok(Response::new(Body::from(format!("Comparing {} with {}", versions[0], versions[1]))))
+    ///      })
+    ///      .build()
+    ///      .unwrap();
+    /// # router
+    /// # }
+    /// # run();
+    /// ```
+    pub fn get_all<N: Into<String>>(&self, param_name: N) -> Vec<&str> {
+        self.0
+            .get(&param_name.into())
+            .map(|values| values.iter().map(|val| val.value.as_str()).collect())
+            .unwrap_or_default()
     }
 
     /// Checks if a route parameter exists.
@@ -86,26 +239,40 @@ impl RouteParams {
         self.0.contains_key(&param_name.into())
     }
 
-    /// Returns the length of the route parameters.
+    /// Returns the number of the distinct parameter names.
     pub fn len(&self) -> usize {
         self.0.len()
     }
 
+    /// Checks if there are no route parameters.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter names.
     pub fn params_names(&self) -> impl Iterator<Item = &String> {
         self.0.keys()
     }
 
     /// Returns an [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) over the parameter entries
-    /// as `(parameter_name: &String, parameter_value:  &String)`.
+    /// as `(parameter_name: &String, parameter_value:  &String)`. A repeated parameter yields an entry for each value.
     pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
-        self.0.iter()
+        self.0
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |val| (key, &val.value)))
     }
 
-    /// Extends the current parameters map with other one.
+    /// Extends the current parameters map with other one, the values of a key existing in both are all kept.
     pub fn extend(&mut self, other_route_params: RouteParams) {
-        other_route_params.0.into_iter().for_each(|(key, val)| {
-            self.set(key, val);
+        other_route_params.0.into_iter().for_each(|(key, vals)| {
+            self.0.entry(key).or_default().extend(vals);
         })
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&str, Vec<&str>)> {
+        self.0
+            .iter()
+            .map(|(key, values)| (key.as_str(), values.iter().map(|val| val.value.as_str()).collect()))
+    }
 }
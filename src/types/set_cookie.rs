@@ -0,0 +1,238 @@
+use crate::types::cookie_keys::CookieKeys;
+use crate::types::cookies::{is_cookie_octet, is_valid_name};
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, SystemTime};
+
+/// A builder for a `Set-Cookie` response header value.
+///
+/// It can be added to a response by the [`set_cookie`](./ext/trait.ResponseExt.html#tymethod.set_cookie) method, or it can be
+/// converted to the header value by its `Display` implementation.
+///
+/// This type is available only if the `cookies` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use routerify::{Router, SameSite, SetCookie};
+/// use routerify::prelude::*;
+/// use hyper::{Response, Body};
+/// use std::time::Duration;
+///
+/// # fn run() -> Router<Body, routerify::Error> {
+/// let router = Router::builder()
+///     .post("/login", |_| async move {
+///         let cookie = SetCookie::new("sid", "abc123")
+///             .path("/")
+///             .http_only(true)
+///             .secure(true)
+///             .same_site(SameSite::Lax)
+///             .max_age(Duration::from_secs(3600));
+///
+///         let mut res = Response::new(Body::from("Logged in"));
+///         res.set_cookie(&cookie)?;
+///         Ok(res)
+///      })
+///      .build()
+///      .unwrap();
+/// # router
+/// # }
+/// # run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetCookie {
+    pub(crate) name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    expires: Option<SystemTime>,
+    http_only: bool,
+    secure: bool,
+    same_site: Option<SameSite>,
+}
+
+/// The value of the `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SameSite {
+    /// The cookie is sent only with the same-site requests.
+    Strict,
+
+    /// The cookie is also sent when the user navigates to the site from another site.
+    Lax,
+
+    /// The cookie is sent with the cross-site requests too, it requires the `Secure` attribute.
+    None,
+}
+
+impl SetCookie {
+    /// Creates a cookie with the specified name and value, the value is written as is, so it must be already encoded if it contains
+    /// any character which is not allowed in a cookie value.
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> SetCookie {
+        SetCookie {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    /// Creates a cookie whose value is signed by the specified [`CookieKeys`](./struct.CookieKeys.html), so it can be read back
+    /// by the [`Cookies::get_signed`](./struct.Cookies.html#method.get_signed) method only if the client didn't tamper with it.
+    ///
+    /// The value is written as is like the [`new`](#method.new) method does, only the signature is appended to it.
+    pub fn signed<N: Into<String>, V: AsRef<str>>(name: N, value: V, keys: &CookieKeys) -> SetCookie {
+        let name = name.into();
+        let value = keys.sign(&name, value.as_ref());
+        SetCookie::new(name, value)
+    }
+
+    /// Creates a cookie whose value is encrypted by the specified [`CookieKeys`](./struct.CookieKeys.html), so it can be read
+    /// back only by the [`Cookies::get_encrypted`](./struct.Cookies.html#method.get_encrypted) method, and the client can
+    /// neither read nor tamper with it.
+    pub fn encrypted<N: Into<String>, V: AsRef<str>>(name: N, value: V, keys: &CookieKeys) -> SetCookie {
+        let name = name.into();
+        let value = keys.encrypt(&name, value.as_ref());
+        SetCookie::new(name, value)
+    }
+
+    /// Creates a cookie which removes the specified cookie from the client, the same `Path` and `Domain` attributes
+    /// must be set to remove it.
+    pub fn removal<N: Into<String>>(name: N) -> SetCookie {
+        SetCookie::new(name, "")
+            .max_age(Duration::from_secs(0))
+            .expires(SystemTime::UNIX_EPOCH)
+    }
+
+    /// Sets the `Path` attribute.
+    pub fn path<P: Into<String>>(mut self, path: P) -> SetCookie {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the `Domain` attribute.
+    pub fn domain<D: Into<String>>(mut self, domain: D) -> SetCookie {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the `Max-Age` attribute, the sub-second part of the duration is ignored.
+    pub fn max_age(mut self, max_age: Duration) -> SetCookie {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the `Expires` attribute.
+    pub fn expires(mut self, expires: SystemTime) -> SetCookie {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Sets the `HttpOnly` attribute.
+    pub fn http_only(mut self, http_only: bool) -> SetCookie {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Sets the `Secure` attribute.
+    pub fn secure(mut self, secure: bool) -> SetCookie {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets the `SameSite` attribute.
+    pub fn same_site(mut self, same_site: SameSite) -> SetCookie {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        let is_valid_attr = |attr: &Option<String>| {
+            attr.as_ref()
+                .map(|val| val.bytes().all(|b| b.is_ascii_graphic() && b != b';'))
+                .unwrap_or(true)
+        };
+
+        if is_valid_name(&self.name)
+            && self.value.bytes().all(is_cookie_octet)
+            && is_valid_attr(&self.path)
+            && is_valid_attr(&self.domain)
+        {
+            Ok(())
+        } else {
+            Err(crate::Error::InvalidCookie(self.name.clone()))
+        }
+    }
+}
+
+impl Display for SetCookie {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+
+        if let Some(ref path) = self.path {
+            write!(f, "; Path={}", path)?;
+        }
+
+        if let Some(ref domain) = self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", httpdate::fmt_http_date(expires))?;
+        }
+
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+
+        match self.same_site {
+            Some(SameSite::Strict) => f.write_str("; SameSite=Strict"),
+            Some(SameSite::Lax) => f.write_str("; SameSite=Lax"),
+            Some(SameSite::None) => f.write_str("; SameSite=None"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_cookie() {
+        let cookie = SetCookie::new("sid", "abc123")
+            .path("/")
+            .domain("example.com")
+            .max_age(Duration::from_secs(3600))
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax);
+        assert!(cookie.validate().is_ok());
+        assert_eq!(
+            cookie.to_string(),
+            "sid=abc123; Path=/; Domain=example.com; Max-Age=3600; HttpOnly; Secure; SameSite=Lax"
+        );
+
+        assert_eq!(
+            SetCookie::removal("sid").to_string(),
+            "sid=; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+
+        assert!(SetCookie::new("sid", "a b").validate().is_err());
+        assert!(SetCookie::new("s;d", "ab").validate().is_err());
+        assert!(SetCookie::new("sid", "ab").path("/; Secure").validate().is_err());
+    }
+}
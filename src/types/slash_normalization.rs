@@ -0,0 +1,19 @@
+/// Specifies how the consecutive slashes in the request path e.g. `//users///3` are handled before matching it against the routes.
+///
+/// It can be set by the [`RouterBuilder::normalize_slashes`](./struct.RouterBuilder.html#method.normalize_slashes) method. The
+/// raw path is always available via the `req.uri()` method and the percent-encoded slashes are never collapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum SlashNormalization {
+    /// The path is matched as it's received. This is the default mode.
+    #[default]
+    Off,
+
+    /// The consecutive slashes are collapsed into a single one, so `//users///3` matches the `/users/:id` route.
+    Collapse,
+
+    /// The requests with idempotent methods e.g. `GET` are redirected to the normalized path with `308 Permanent Redirect` status,
+    /// the other requests are handled like the [`Collapse`](#variant.Collapse) mode. The redirection is only supported for the
+    /// `hyper::Body` response body type, it falls back to the `Collapse` mode for the other body types.
+    Redirect,
+}
@@ -0,0 +1,22 @@
+/// Specifies how a trailing slash in the request path is handled when it's matched against the routes.
+///
+/// It can be set by the [`RouterBuilder::trailing_slash`](./struct.RouterBuilder.html#method.trailing_slash) method. A route
+/// ending with a wildcard e.g. `/assets/*` matches the paths with or without a trailing slash in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum TrailingSlash {
+    /// The trailing slash is ignored, so `/users` and `/users/` both match the `/users` and the `/users/` routes. This is the
+    /// default mode.
+    #[default]
+    Ignore,
+
+    /// The request path matches a route only if both have a trailing slash or neither has, so `/users/` doesn't match the
+    /// `/users` route.
+    Strict,
+
+    /// The requests with idempotent methods e.g. `GET` are redirected with `301 Moved Permanently` status to the form of the path
+    /// which the matched route is added with e.g. `/users/` to `/users`, the other requests are handled like the
+    /// [`Ignore`](#variant.Ignore) mode. The redirection is only supported for the `hyper::Body` response body type, it falls
+    /// back to the `Ignore` mode for the other body types.
+    RedirectToCanonical,
+}